@@ -17,6 +17,7 @@ type DecorationColorItem = {
     nonCompatibleLatest: string
     yanked: string
     git: string
+    member: string
 }
 
 const defaultLight: DecorationColorItem = {
@@ -28,7 +29,8 @@ const defaultLight: DecorationColorItem = {
     compatibleLatest: "#FF8C00",
     nonCompatibleLatest: "#FF8C00",
     yanked: "#FF0000",
-    git: "#800080"
+    git: "#800080",
+    member: "#0000FF"
 }
 
 const defaultDark: DecorationColorItem = {
@@ -40,7 +42,8 @@ const defaultDark: DecorationColorItem = {
     compatibleLatest: "#FF8C00",
     nonCompatibleLatest: "#FF8C00",
     yanked: "#FF0000",
-    git: "#800080"
+    git: "#800080",
+    member: "#0000FF"
 }
 
 type InitializationOptions = {
@@ -153,6 +156,10 @@ class Config {
         if (typeof git === "string") {
             this.colors.light.git = git
         }
+        const member = workspace.getConfiguration("cargo-appraiser").get("decorationColor.light.member")
+        if (typeof member === "string") {
+            this.colors.light.member = member
+        }
 
         const notParsedDark = workspace.getConfiguration("cargo-appraiser").get("decorationColor.dark.notParsed")
         if (typeof notParsedDark === "string") {
@@ -190,6 +197,10 @@ class Config {
         if (typeof gitDark === "string") {
             this.colors.dark.git = gitDark
         }
+        const memberDark = workspace.getConfiguration("cargo-appraiser").get("decorationColor.dark.member")
+        if (typeof memberDark === "string") {
+            this.colors.dark.member = memberDark
+        }
 
         const notParsedHighContrast = workspace.getConfiguration("cargo-appraiser").get("decorationColor.highContrast.notParsed")
         if (typeof notParsedHighContrast === "string") {
@@ -227,6 +238,10 @@ class Config {
         if (typeof gitHighContrast === "string") {
             this.colors.highContrast.git = gitHighContrast
         }
+        const memberHighContrast = workspace.getConfiguration("cargo-appraiser").get("decorationColor.highContrast.member")
+        if (typeof memberHighContrast === "string") {
+            this.colors.highContrast.member = memberHighContrast
+        }
 
         const notParsedHighContrastLight = workspace.getConfiguration("cargo-appraiser").get("decorationColor.highContrastLight.notParsed")
         if (typeof notParsedHighContrastLight === "string") {
@@ -264,6 +279,10 @@ class Config {
         if (typeof gitHighContrastLight === "string") {
             this.colors.highContrastLight.git = gitHighContrastLight
         }
+        const memberHighContrastLight = workspace.getConfiguration("cargo-appraiser").get("decorationColor.highContrastLight.member")
+        if (typeof memberHighContrastLight === "string") {
+            this.colors.highContrastLight.member = memberHighContrastLight
+        }
     }
 }
 
@@ -1,26 +1,452 @@
 use once_cell::sync::Lazy;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::sync::RwLock;
+use tower_lsp::lsp_types::DiagnosticSeverity;
 
-use crate::decoration::{CompiledFormatter, DecorationFormatter};
+use crate::{
+    controller::AuditSeverity,
+    decoration::{CompiledFormatter, DecorationFormatter, IconSet},
+};
 
-#[derive(Default, Debug, Clone)]
+//the effective config, serialized by the `cargo-appraiser/config` command
+#[derive(Default, Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct Config {
+    //the shared `decorationFormatter` templates, the renderers use their own compiled copies
     pub decoration_formatter: CompiledFormatter,
+    pub inlay_hint_formatter: CompiledFormatter,
+    pub vscode_formatter: CompiledFormatter,
+    pub audit: AuditConfig,
+    pub sources: SourcesConfig,
+    pub inlay_hint: InlayHintConfig,
+    pub resolve: ResolveConfig,
+    pub code_action: CodeActionConfig,
+    pub files: FilesConfig,
+    pub style: StyleConfig,
+    pub hover: HoverConfig,
+    pub completion: CompletionConfig,
 }
 
-#[derive(Default, Debug, Deserialize, Clone)]
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UserConfig {
     #[serde(default)]
     pub decoration_formatter: DecorationFormatter,
+    #[serde(default)]
+    pub decoration: DecorationConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub sources: SourcesConfig,
+    #[serde(default)]
+    pub inlay_hint: InlayHintConfig,
+    #[serde(default)]
+    pub resolve: ResolveConfig,
+    #[serde(default)]
+    pub code_action: CodeActionConfig,
+    #[serde(default)]
+    pub files: FilesConfig,
+    #[serde(default)]
+    pub style: StyleConfig,
+    #[serde(default)]
+    pub hover: HoverConfig,
+    #[serde(default)]
+    pub completion: CompletionConfig,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DecorationConfig {
+    //icons used by the default decoration templates
+    #[serde(default)]
+    pub icon_set: IconSet,
+    //append `(n versions in tree)` when a dependency is resolved at multiple versions
+    #[serde(default)]
+    pub show_duplicates: bool,
+    //append `(unpublished)` to local dependencies with `publish = false`
+    #[serde(default)]
+    pub show_publish_status: bool,
+    //show the package version after each path of the root `[workspace] members`
+    #[serde(default)]
+    pub show_member_versions: bool,
+    //templates for the inlay hint renderer only, unset templates use `decorationFormatter`
+    #[serde(default)]
+    pub inlay_hint: DecorationFormatter,
+    //templates for the vscode renderer only, unset templates use `decorationFormatter`
+    #[serde(default)]
+    pub vscode: DecorationFormatter,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditConfig {
+    //the diagnostic severity of unmaintained, unsound and yanked warnings
+    #[serde(default)]
+    pub warning_severity: AuditWarningSeverity,
+    //attach audit diagnostics to the crate name key or the whole entry
+    #[serde(default)]
+    pub diagnostic_range: AuditDiagnosticRange,
+    //accepted advisories, an advisory id or `crate:kind` for warnings
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    //`minimal` keeps the title, id and severity of an advisory, `full` keeps everything
+    #[serde(default)]
+    pub hover_detail: AuditHoverDetail,
+    //warning kinds reported as diagnostics, vulnerabilities are always reported
+    #[serde(default = "default_warning_kinds")]
+    pub warning_kinds: Vec<String>,
+    //seconds to wait after the last resolve before auditing, 0 audits right away
+    #[serde(default = "default_debounce_secs")]
+    pub debounce_secs: u64,
+    //allow the `auditFix` command to run the experimental `cargo audit fix`
+    #[serde(default)]
+    pub enable_fix: bool,
+    //vulnerabilities rated below it get no upgrade quickfix, "none" fixes every vulnerability
+    #[serde(default)]
+    pub autofix_min_severity: AuditSeverity,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            warning_severity: AuditWarningSeverity::default(),
+            diagnostic_range: AuditDiagnosticRange::default(),
+            ignore: Vec::new(),
+            hover_detail: AuditHoverDetail::default(),
+            warning_kinds: default_warning_kinds(),
+            debounce_secs: default_debounce_secs(),
+            enable_fix: false,
+            autofix_min_severity: AuditSeverity::default(),
+        }
+    }
+}
+
+fn default_warning_kinds() -> Vec<String> {
+    ["unmaintained", "unsound", "yanked", "notice"]
+        .iter()
+        .map(|k| k.to_string())
+        .collect()
+}
+
+fn default_debounce_secs() -> u64 {
+    60
+}
+
+impl AuditConfig {
+    pub fn reports_warning_kind(&self, kind: &str) -> bool {
+        self.warning_kinds.iter().any(|k| k.eq_ignore_ascii_case(kind))
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditHoverDetail {
+    Minimal,
+    #[default]
+    Full,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditDiagnosticRange {
+    Key,
+    #[default]
+    Entry,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditWarningSeverity {
+    Info,
+    #[default]
+    Warning,
+    Hint,
+}
+
+impl From<AuditWarningSeverity> for DiagnosticSeverity {
+    fn from(value: AuditWarningSeverity) -> Self {
+        match value {
+            AuditWarningSeverity::Info => DiagnosticSeverity::INFORMATION,
+            AuditWarningSeverity::Warning => DiagnosticSeverity::WARNING,
+            AuditWarningSeverity::Hint => DiagnosticSeverity::HINT,
+        }
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SourcesConfig {
+    //allowed dependency sources, e.g. "crates-io", "registry:<name>", "git", "path"
+    //all sources are allowed when not set
+    #[serde(default)]
+    pub allow: Option<Vec<String>>,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintConfig {
+    //show `(n features)` after the crate name of dependencies with a features array
+    #[serde(default)]
+    pub show_feature_count: bool,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveConfig {
+    //defer resolving an opened document until the first hover, completion, code action or save
+    #[serde(default)]
+    pub lazy: bool,
+    //resolve from Cargo.lock and the local cache only, the registry is never queried
+    #[serde(default)]
+    pub offline: bool,
+    //warn on dependencies declared in the manifest but missing from Cargo.lock
+    #[serde(default)]
+    pub check_lockfile: bool,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeActionConfig {
+    //the maximum number of code actions returned for a node, quickfixes are kept first
+    #[serde(default)]
+    pub max_actions: Option<usize>,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HoverConfig {
+    //show when the latest version was published in the version hover, fetched from crates.io
+    #[serde(default)]
+    pub show_publish_date: bool,
+    //show download counts when hovering a crate name, fetched from crates.io
+    #[serde(default)]
+    pub show_downloads: bool,
+    //list the packages depending on a crate when hovering its name
+    #[serde(default)]
+    pub show_required_by: bool,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleConfig {
+    //hint when a crate is required with different version syntax across the workspace
+    #[serde(default)]
+    pub consistent_versions: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionConfig {
+    //wait for typing to pause before searching crates.io for crate names,
+    //a newer search in the same document cancels the pending one
+    #[serde(default = "default_search_debounce_ms")]
+    pub search_debounce_ms: u64,
+    //cap crate name and version completions, a capped list is marked incomplete
+    #[serde(default = "default_max_items")]
+    pub max_items: usize,
+    //only suggest `path` dependencies inside the workspace root
+    #[serde(default)]
+    pub path_within_workspace: bool,
+}
+
+impl Default for CompletionConfig {
+    fn default() -> Self {
+        Self {
+            search_debounce_ms: default_search_debounce_ms(),
+            max_items: default_max_items(),
+            path_within_workspace: false,
+        }
+    }
+}
+
+fn default_search_debounce_ms() -> u64 {
+    150
+}
+
+fn default_max_items() -> usize {
+    50
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FilesConfig {
+    //glob patterns of documents treated as manifests
+    //`*` doesn't match `/`, `**` matches any number of directories
+    #[serde(default = "default_file_patterns")]
+    pub patterns: Vec<String>,
+}
+
+impl Default for FilesConfig {
+    fn default() -> Self {
+        Self {
+            patterns: default_file_patterns(),
+        }
+    }
+}
+
+fn default_file_patterns() -> Vec<String> {
+    vec!["**/Cargo.toml".to_string()]
+}
+
+impl FilesConfig {
+    pub fn is_manifest(&self, path: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|p| glob_match(p.as_bytes(), path.as_bytes()))
+    }
+}
+
+fn glob_match(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        [b'*', b'*', rest @ ..] => match rest.strip_prefix(b"/") {
+            //`**/` matches zero or more whole directories
+            Some(rest) => (0..=path.len())
+                .filter(|&i| i == 0 || path[i - 1] == b'/')
+                .any(|i| glob_match(rest, &path[i..])),
+            None => (0..=path.len()).any(|i| glob_match(rest, &path[i..])),
+        },
+        [b'*', rest @ ..] => {
+            for i in 0..=path.len() {
+                if glob_match(rest, &path[i..]) {
+                    return true;
+                }
+                if i < path.len() && path[i] == b'/' {
+                    break;
+                }
+            }
+            false
+        }
+        [b'?', rest @ ..] => !path.is_empty() && path[0] != b'/' && glob_match(rest, &path[1..]),
+        [c, rest @ ..] => !path.is_empty() && path[0] == *c && glob_match(rest, &path[1..]),
+    }
 }
 
 pub static GLOBAL_CONFIG: Lazy<RwLock<Config>> = Lazy::new(|| RwLock::new(Config::default()));
 
+impl From<UserConfig> for Config {
+    fn from(config: UserConfig) -> Self {
+        Config {
+            decoration_formatter: config.decoration_formatter.compile(&config.decoration),
+            inlay_hint_formatter: config
+                .decoration
+                .inlay_hint
+                .or(&config.decoration_formatter)
+                .compile(&config.decoration),
+            vscode_formatter: config
+                .decoration
+                .vscode
+                .or(&config.decoration_formatter)
+                .compile(&config.decoration),
+            audit: config.audit,
+            sources: config.sources,
+            inlay_hint: config.inlay_hint,
+            resolve: config.resolve,
+            code_action: config.code_action,
+            files: config.files,
+            style: config.style,
+            hover: config.hover,
+            completion: config.completion,
+        }
+    }
+}
+
 pub fn initialize_config(config: UserConfig) {
-    let mut global_config = GLOBAL_CONFIG.write().unwrap();
-    *global_config = Config {
-        decoration_formatter: config.decoration_formatter.compile(),
+    //compile the formatter before taking the lock, readers only wait for the swap
+    let config: Config = config.into();
+    *GLOBAL_CONFIG.write().unwrap() = config;
+}
+
+//`workspace/didChangeConfiguration` settings are either the config itself
+//or nested in the `cargo-appraiser` section
+pub fn settings_config(settings: serde_json::Value) -> Option<UserConfig> {
+    let settings = match settings.get("cargo-appraiser") {
+        Some(section) => section.clone(),
+        None => settings,
     };
+    serde_json::from_value(settings).ok()
+}
+
+pub fn effective_config() -> serde_json::Value {
+    serde_json::to_value(&*GLOBAL_CONFIG.read().unwrap()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{settings_config, Config, FilesConfig, UserConfig};
+    use crate::decoration::{DecorationPayload, VersionDecorationKind};
+
+    #[test]
+    fn test_effective_config() {
+        let user: UserConfig = serde_json::from_value(serde_json::json!({
+            "audit": { "warningSeverity": "hint" }
+        }))
+        .unwrap();
+        let effective = serde_json::to_value(Config::from(user)).unwrap();
+        assert_eq!(effective["audit"]["warningSeverity"], "hint");
+        assert_eq!(effective["audit"]["diagnosticRange"], "entry");
+        assert_eq!(effective["files"]["patterns"][0], "**/Cargo.toml");
+    }
+
+    #[test]
+    fn test_renderer_formatters() {
+        let user: UserConfig = serde_json::from_value(serde_json::json!({
+            "decorationFormatter": { "latest": "shared {{installed}}" },
+            "decoration": { "inlayHint": { "latest": "hint {{installed}}" } }
+        }))
+        .unwrap();
+        let config = Config::from(user);
+        let payload = DecorationPayload {
+            kind: VersionDecorationKind::Latest,
+            installed: Some(semver::Version::new(1, 0, 0)),
+            ..Default::default()
+        };
+        assert_eq!(config.inlay_hint_formatter.format(&payload).unwrap(), "hint 1.0.0");
+        assert_eq!(config.vscode_formatter.format(&payload).unwrap(), "shared 1.0.0");
+    }
+
+    #[test]
+    fn test_file_patterns() {
+        let config = FilesConfig::default();
+        assert!(config.is_manifest("/home/a/project/Cargo.toml"));
+        assert!(!config.is_manifest("/home/a/project/Cargo.toml.tmpl"));
+        assert!(!config.is_manifest("/home/a/project/pyproject.toml"));
+        assert!(!config.is_manifest("/home/a/project/xCargo.toml"));
+
+        let config = FilesConfig {
+            patterns: vec![
+                "**/Cargo.toml".to_string(),
+                "**/Cargo.toml.tmpl".to_string(),
+                "/home/a/manifests/*.toml".to_string(),
+            ],
+        };
+        assert!(config.is_manifest("/home/a/project/Cargo.toml.tmpl"));
+        assert!(config.is_manifest("/home/a/manifests/wasm.toml"));
+        assert!(!config.is_manifest("/home/a/manifests/nested/wasm.toml"));
+    }
+
+    #[test]
+    fn test_settings_config() {
+        let waiting = |settings: serde_json::Value| {
+            let config = Config::from(settings_config(settings).unwrap());
+            let config = serde_json::to_value(config).unwrap();
+            //`decorationFormatter` is shared by both renderers
+            let waiting = config["decorationFormatter"]["waiting"].clone();
+            assert_eq!(config["inlayHintFormatter"]["waiting"], waiting);
+            assert_eq!(config["vscodeFormatter"]["waiting"], waiting);
+            waiting
+        };
+        assert_eq!(waiting(serde_json::json!({})), "Waiting...");
+        assert_eq!(
+            waiting(serde_json::json!({
+                "cargo-appraiser": { "decorationFormatter": { "waiting": "⏳" } }
+            })),
+            "⏳"
+        );
+        assert_eq!(
+            waiting(serde_json::json!({ "decorationFormatter": { "waiting": "⏳" } })),
+            "⏳"
+        );
+        assert!(settings_config(serde_json::json!({ "audit": "all" })).is_none());
+    }
 }
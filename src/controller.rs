@@ -2,13 +2,27 @@ pub mod appraiser;
 mod audit;
 mod capabilities;
 mod cargo;
+mod check;
 mod code_action;
 mod completion;
+mod crates_io;
 mod debouncer;
 mod diagnostic;
+mod document_symbol;
 mod gd;
 mod hover;
+mod lazy;
+mod lint;
+mod outdated;
 mod read_file;
+mod registry_cache;
+mod rename;
+mod semantic_tokens;
+mod validate;
 
-pub use appraiser::{Appraiser, CargoDocumentEvent, CargoTomlPayload};
+pub use appraiser::{Appraiser, AppraiserSignal, CargoDocumentEvent, CargoTomlPayload};
+pub use audit::{audit_fix_args, AuditSeverity};
 pub use capabilities::ClientCapability;
+pub use cargo::{cargo_net_offline, feature_impact};
+pub use check::check;
+pub use semantic_tokens::semantic_tokens_legend;
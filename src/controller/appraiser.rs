@@ -1,39 +1,60 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, path::Path, str::FromStr, time::Duration};
 
 use cargo::util::VersionExt;
+use moka::future::Cache;
 use semver::Version;
 use tokio::sync::{
+    broadcast,
     mpsc::{self, Sender},
     oneshot,
 };
 use tower_lsp::{
     lsp_types::{
-        CodeActionResponse, CompletionResponse, Diagnostic, GotoDefinitionResponse, Hover,
-        Position, Range, Uri,
+        CodeActionResponse, CompletionResponse, DocumentSymbolResponse, GotoDefinitionResponse,
+        Hover, Position, Range, SemanticToken, TextDocumentContentChangeEvent, Uri, WorkspaceEdit,
     },
     Client,
 };
 use tracing::error;
 
 use crate::{
+    config::GLOBAL_CONFIG,
     controller::{
-        audit::into_diagnostic_text, code_action::code_action, completion::completion,
+        code_action::{cap_actions, code_action, code_action_fix_all_yanked},
+        completion::{completion, path_completion, spawn_crate_name_completion, CrateSearches},
         read_file::ReadFileParam,
     },
-    decoration::DecorationEvent,
+    decoration::{dependency_event, DecorationEvent},
     entity::{into_file_uri, CargoError, Dependency},
-    usecase::{Document, Workspace},
+    usecase::{Document, ResolvedContext, Workspace},
 };
 
 use super::{
-    audit::{into_diagnostic_severity, AuditController, AuditReports, AuditResult},
+    audit::{audit_diagnostic, AuditController, AuditReports, AuditResult},
     capabilities::{ClientCapabilities, ClientCapability},
     cargo::{cargo_resolve, CargoResolveOutput},
+    crates_io::{
+        downloads_text, fetch_crate_meta, fetch_publish_date, relative_date, today, CrateMeta,
+    },
     debouncer::Debouncer,
     diagnostic::DiagnosticController,
-    gd::goto_definition,
-    hover::hover,
+    document_symbol::document_symbols,
+    gd::{goto_definition, unparsed_workspace_root},
+    hover::{
+        audit_hover, hover, is_crate_name_hover, is_version_hover, prepend_hover_text,
+        required_by_text, text_hover,
+    },
+    lazy::LazyResolve,
+    rename::{member_uris, prepare_rename, rename},
+    semantic_tokens::semantic_tokens,
+    lint::{
+        lint_dependencies, lint_dependency_features, lint_dual_declaration,
+        lint_duplicate_versions, lint_feature_refs, lint_optional_not_installed, lint_unlocked,
+        lint_unpublished_requirement, lint_version_syntax, lint_workspace_divergence,
+    },
+    outdated::{outdated_dependencies, upgrade_summary, OutdatedDependency, UpgradeSummary},
     read_file::ReadFile,
+    validate::{merge_findings, validate_document, Finding},
 };
 
 #[derive(Debug, Clone)]
@@ -50,6 +71,16 @@ pub struct Appraiser {
     client: Client,
     render_tx: Sender<DecorationEvent>,
     client_capabilities: ClientCapabilities,
+    signal_tx: broadcast::Sender<AppraiserSignal>,
+}
+
+//sent after an event is processed, lets embedders and tests await it instead of polling
+#[derive(Debug, Clone)]
+pub enum AppraiserSignal {
+    //the resolve output of the document rev is populated
+    Resolved(Ctx),
+    //audit diagnostics of the workspace root are published
+    Audited(Uri),
 }
 
 pub enum CargoDocumentEvent {
@@ -57,7 +88,8 @@ pub enum CargoDocumentEvent {
     Saved(CargoTomlPayload),
     //Parse event won't trigger Cargo.toml resolve compare to Opened and Saved
     Parse(CargoTomlPayload),
-    Changed(CargoTomlPayload),
+    //the content changes of an opened document, applied to the cached text
+    Changed(Uri, Vec<TextDocumentContentChangeEvent>),
     ReadyToResolve(Ctx),
     //mark dependencies dirty, clear decorations
     Closed(Uri),
@@ -66,11 +98,18 @@ pub enum CargoDocumentEvent {
     //cargo.lock change
     //CargoLockCreated,
     CargoLockChanged,
+    //settings changed, render decorations and audit diagnostics again
+    ConfigChanged,
+    //a publish date fetched for a hover, cached for the next hover
+    PublishDate(String, String, i64),
     //code action, path and range
     CodeAction(Uri, Range, oneshot::Sender<CodeActionResponse>),
     //hover event, path and position
     Hovered(Uri, Position, oneshot::Sender<Option<Hover>>),
     Completion(Uri, Position, oneshot::Sender<Option<CompletionResponse>>),
+    //outline of the document
+    DocumentSymbols(Uri, oneshot::Sender<Option<DocumentSymbolResponse>>),
+    SemanticTokens(Uri, oneshot::Sender<Option<Vec<SemanticToken>>>),
     //goto definition
     Gded(
         Uri,
@@ -79,6 +118,21 @@ pub enum CargoDocumentEvent {
     ),
     CargoDiagnostic(Uri, CargoError),
     Audited(AuditReports),
+    //list dependencies with available upgrades across the workspace
+    Outdated(oneshot::Sender<Vec<OutdatedDependency>>),
+    UpgradeSummary(oneshot::Sender<UpgradeSummary>),
+    //run every check over a document and report the findings
+    Validate(Uri, oneshot::Sender<Vec<Finding>>),
+    //the installed versions and sources of the direct dependencies
+    ResolvedManifest(Uri, oneshot::Sender<Option<String>>),
+    PrepareRename(Uri, Position, oneshot::Sender<Option<Range>>),
+    //rename a workspace dependency, the error is an invalid crate name
+    Rename(
+        Uri,
+        Position,
+        String,
+        oneshot::Sender<Result<Option<WorkspaceEdit>, String>>,
+    ),
 }
 
 pub struct CargoTomlPayload {
@@ -93,12 +147,18 @@ impl Appraiser {
         client_capabilities: Option<&[ClientCapability]>,
     ) -> Self {
         let client_capabilities = ClientCapabilities::new(client_capabilities);
+        let (signal_tx, _) = broadcast::channel(16);
         Self {
             client,
             render_tx,
             client_capabilities,
+            signal_tx,
         }
     }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AppraiserSignal> {
+        self.signal_tx.subscribe()
+    }
     pub fn initialize(&self) -> Sender<CargoDocumentEvent> {
         //create mpsc channel
         let (tx, mut rx) = mpsc::channel::<CargoDocumentEvent>(64);
@@ -110,7 +170,10 @@ impl Appraiser {
         let tx_for_cargo = tx.clone();
         tokio::spawn(async move {
             while let Some(event) = cargo_rx.recv().await {
-                match cargo_resolve(&event).await {
+                let offline = GLOBAL_CONFIG.read().unwrap().resolve.offline;
+                //the edges are only collected for the hover
+                let reverse_deps = GLOBAL_CONFIG.read().unwrap().hover.show_required_by;
+                match cargo_resolve(&event, offline, reverse_deps).await {
                     Ok(output) => {
                         if let Err(e) = tx_for_cargo
                             .send(CargoDocumentEvent::CargoResolved(output))
@@ -144,16 +207,32 @@ impl Appraiser {
         let render_tx = self.render_tx.clone();
         let client = self.client.clone();
         let client_capabilities = self.client_capabilities.clone();
+        let signal_tx = self.signal_tx.clone();
         tokio::spawn(async move {
             //workspace state
             let mut state = Workspace::new();
             //diagnostic
             let diag_client = client.clone();
             let mut diagnostic_controller = DiagnosticController::new(diag_client);
+            //audit results of direct dependencies, keyed by document uri and dependency id
+            //has the same lifecycle as audit diagnostics
+            let mut audit_results: HashMap<(Uri, String), Vec<AuditResult>> = HashMap::new();
+            //documents opened with `resolve.lazy`, waiting for the first interaction
+            let mut lazy_resolve = LazyResolve::default();
+            //publish dates from crates.io keyed by package name and version, failures are retried
+            let mut publish_dates: HashMap<(String, String), i64> = HashMap::new();
+            //crate metadata from crates.io keyed by package name, failures are retried
+            let crate_metas: Cache<String, CrateMeta> = Cache::builder()
+                .time_to_live(Duration::from_secs(60 * 60))
+                .build();
+            let mut crate_searches = CrateSearches::default();
 
             while let Some(event) = rx.recv().await {
+                let is_opened = matches!(event, CargoDocumentEvent::Opened(_));
                 match event {
-                    CargoDocumentEvent::Audited(reports) => {
+                    CargoDocumentEvent::Audited(mut reports) => {
+                        let audit_config = GLOBAL_CONFIG.read().unwrap().audit.clone();
+                        reports.retain_reported(&audit_config);
                         //a hashset to record which is already audited
                         let mut audited: HashMap<(Uri, String), (Dependency, Vec<AuditResult>)> =
                             HashMap::new();
@@ -169,6 +248,9 @@ impl Appraiser {
                             };
                             //loop dependencies and write the audited with root_manifest
                             for dep in doc.dependencies.values() {
+                                if dep.hidden {
+                                    continue;
+                                }
                                 //if it has resolved dependency, we can compare the version
                                 //if it doesn't(for virtual workspace), we can just compare the version compatibility
                                 //first find matching dependency name in resports
@@ -231,22 +313,17 @@ impl Appraiser {
                             }
                             //send to diagnostic
                             for ((uri, _), (dep, rr)) in &audited {
-                                let diag = Diagnostic {
-                                    range: dep.range,
-                                    severity: Some(into_diagnostic_severity(rr)),
-                                    code: None,
-                                    code_description: None,
-                                    source: Some("cargo-appraiser".to_string()),
-                                    message: into_diagnostic_text(rr),
-                                    related_information: None,
-                                    tags: None,
-                                    data: None,
-                                };
+                                let diag = audit_diagnostic(dep, rr, &audit_config);
                                 diagnostic_controller
                                     .add_audit_diagnostic(uri, &dep.id, diag)
                                     .await;
                             }
                         }
+                        for (key, (_, rr)) in audited {
+                            audit_results.insert(key, rr);
+                        }
+                        //no subscriber is not an error
+                        let _ = signal_tx.send(AppraiserSignal::Audited(reports.root));
                     }
                     CargoDocumentEvent::CargoDiagnostic(uri, err) => {
                         diagnostic_controller.clear_cargo_diagnostics(&uri).await;
@@ -270,60 +347,278 @@ impl Appraiser {
                         }
                     }
                     CargoDocumentEvent::Hovered(uri, pos, tx) => {
+                        resolve_deferred(&state, &debouncer, &mut lazy_resolve, &uri).await;
                         let Some(doc) = state.document(&uri) else {
                             continue;
                         };
-                        let Some(node) = doc.precise_match(pos) else {
+                        let Some(ResolvedContext {
+                            node,
+                            id,
+                            dependency: dep,
+                        }) = doc.resolve_at(pos)
+                        else {
                             continue;
                         };
-                        let dep = match node.row_id() {
-                            Some(id) => doc.dependency(&id),
-                            None => None,
+                        let mut h = hover(&node, dep, doc.members.as_deref(), &doc.manifest);
+                        if let Some(rr) = id.and_then(|id| audit_results.get(&(uri.clone(), id))) {
+                            h = audit_hover(&node, h, rr);
+                        }
+                        //publish dates and download counts are crates.io requests
+                        let offline = GLOBAL_CONFIG.read().unwrap().resolve.offline;
+                        let show_date =
+                            !offline && GLOBAL_CONFIG.read().unwrap().hover.show_publish_date;
+                        let latest = dep
+                            .filter(|_| show_date && is_version_hover(&node))
+                            .and_then(|dep| {
+                                Some((dep.package_name(), dep.latest_summary.as_ref()?.version()))
+                            });
+                        //uncached lookups are fetched outside the loop, which sends the hover
+                        let mut lookup = HoverLookup::default();
+                        if let (Some(hover), Some((name, version))) = (h.as_mut(), latest) {
+                            let key = (name.to_string(), version.to_string());
+                            match publish_dates.get(&key) {
+                                Some(published) => prepend_hover_text(
+                                    hover,
+                                    &publish_date_text(version, *published),
+                                ),
+                                None => {
+                                    lookup.publish_date = Some((name.to_string(), version.clone()))
+                                }
+                            }
+                        }
+                        let show_downloads =
+                            !offline && GLOBAL_CONFIG.read().unwrap().hover.show_downloads;
+                        if let Some(dep) = dep.filter(|dep| {
+                            show_downloads
+                                && is_crate_name_hover(&node)
+                                && dep.registry.is_none()
+                                && dep.git.is_none()
+                                && dep.path.is_none()
+                        }) {
+                            let name = dep.package_name();
+                            match crate_metas.get(name).await {
+                                Some(meta) => {
+                                    let text = downloads_text(&meta);
+                                    match h.as_mut() {
+                                        Some(hover) => prepend_hover_text(hover, &text),
+                                        None => h = Some(text_hover(text, node.range)),
+                                    }
+                                }
+                                None => lookup.crate_meta = Some(name.to_string()),
+                            }
+                        }
+                        let show_required_by = GLOBAL_CONFIG.read().unwrap().hover.show_required_by;
+                        if let Some(text) = dep
+                            .filter(|_| show_required_by && is_crate_name_hover(&node))
+                            .map(|dep| state.reverse_deps(dep.package_name()))
+                            .and_then(|parents| required_by_text(&parents))
+                        {
+                            match h.as_mut() {
+                                Some(hover) => prepend_hover_text(hover, &text),
+                                None => h = Some(text_hover(text, node.range)),
+                            }
+                        }
+                        if lookup.is_empty() {
+                            let _ = tx.send(h);
+                            continue;
+                        }
+                        spawn_hover_lookup(
+                            lookup,
+                            h,
+                            node.range,
+                            crate_metas.clone(),
+                            tx,
+                            inner_tx.clone(),
+                        );
+                    }
+                    CargoDocumentEvent::PublishDate(name, version, published) => {
+                        publish_dates.insert((name, version), published);
+                    }
+                    CargoDocumentEvent::ResolvedManifest(uri, tx) => {
+                        let _ = tx.send(state.document(&uri).map(|doc| doc.to_resolved_manifest()));
+                    }
+                    CargoDocumentEvent::Outdated(tx) => {
+                        let _ = tx.send(outdated_dependencies(&state));
+                    }
+                    CargoDocumentEvent::UpgradeSummary(tx) => {
+                        let _ = tx.send(upgrade_summary(&state));
+                    }
+                    CargoDocumentEvent::Validate(uri, tx) => {
+                        //documents with parsing errors are not kept in state,
+                        //their parsing errors are still published
+                        let mut findings = match state.document(&uri) {
+                            Some(doc) => {
+                                let config = GLOBAL_CONFIG.read().unwrap().clone();
+                                validate_document(doc, &config)
+                            }
+                            None => Vec::new(),
                         };
-                        let h = hover(&node, dep, doc.members.as_deref());
-                        let _ = tx.send(h);
+                        merge_findings(&mut findings, diagnostic_controller.diagnostics(&uri));
+                        let _ = tx.send(findings);
                     }
                     CargoDocumentEvent::Gded(uri, pos, tx) => {
                         let Some(doc) = state.document(&uri) else {
                             continue;
                         };
-                        let Some(node) = doc.precise_match(pos) else {
+                        let Some(ResolvedContext { node, .. }) = doc.resolve_at(pos) else {
                             continue;
                         };
-                        let gd = goto_definition(&state, doc, &node);
-                        let _ = tx.send(gd);
+                        let _ = tx.send(goto_definition(&state, doc, &node));
+                        //the root manifest is parsed for the next request
+                        if let Some(root_uri) = unparsed_workspace_root(&state, doc, &node) {
+                            parse_root_manifest(root_uri, &inner_tx, &client, &client_capabilities)
+                                .await;
+                        }
                     }
-                    CargoDocumentEvent::Completion(uri, pos, tx) => {
+                    CargoDocumentEvent::PrepareRename(uri, pos, tx) => {
                         let Some(doc) = state.document(&uri) else {
                             continue;
                         };
-                        let Some(node) = doc.precise_match(pos) else {
+                        let Some(ResolvedContext { node, .. }) = doc.resolve_at(pos) else {
+                            continue;
+                        };
+                        let _ = tx.send(prepare_rename(doc, &node));
+                    }
+                    CargoDocumentEvent::Rename(uri, pos, new_name, tx) => {
+                        let Some(doc) = state.document(&uri) else {
                             continue;
                         };
-                        let Some(id) = node.row_id() else {
+                        let Some(ResolvedContext { node, .. }) = doc.resolve_at(pos) else {
+                            continue;
+                        };
+                        //members which aren't opened are read and parsed for the edit only
+                        let mut unopened = Vec::new();
+                        for member in member_uris(doc) {
+                            if state.document(&member).is_some() {
+                                continue;
+                            }
+                            if let Some(text) =
+                                read_manifest(&member, &client, &client_capabilities).await
+                            {
+                                unopened.push(Document::parse(&member, &text));
+                            }
+                        }
+                        let members: Vec<&Document> = member_uris(doc)
+                            .iter()
+                            .filter_map(|member| state.document(member))
+                            .chain(unopened.iter())
+                            .collect();
+                        let _ = tx.send(rename(doc, &node, &new_name, &members));
+                    }
+                    CargoDocumentEvent::DocumentSymbols(uri, tx) => {
+                        let Some(doc) = state.document(&uri) else {
                             continue;
                         };
-                        let dep = doc.dependency(&id);
-                        let completion = completion(&node, dep).await;
-                        let _ = tx.send(completion);
+                        let symbols = document_symbols(doc);
+                        let _ = tx.send(Some(DocumentSymbolResponse::Nested(symbols)));
                     }
-                    CargoDocumentEvent::CodeAction(uri, range, tx) => {
+                    CargoDocumentEvent::SemanticTokens(uri, tx) => {
+                        let _ = tx.send(state.document(&uri).map(semantic_tokens));
+                    }
+                    CargoDocumentEvent::Completion(uri, pos, tx) => {
+                        resolve_deferred(&state, &debouncer, &mut lazy_resolve, &uri).await;
                         let Some(doc) = state.document(&uri) else {
                             continue;
                         };
-                        let Some(node) = doc.precise_match(range.start) else {
+                        let Some(ResolvedContext {
+                            node,
+                            id: Some(_),
+                            dependency: dep,
+                        }) = doc.resolve_at(pos)
+                        else {
                             continue;
                         };
-                        let Some(id) = node.row_id() else {
+                        if let Some(name) = node.crate_name() {
+                            //searching crate names is a crates.io request
+                            if GLOBAL_CONFIG.read().unwrap().resolve.offline {
+                                let _ = tx.send(None);
+                                continue;
+                            }
+                            let config = GLOBAL_CONFIG.read().unwrap().completion.clone();
+                            let debounce = Duration::from_millis(config.search_debounce_ms);
+                            let token = crate_searches.start(&uri);
+                            spawn_crate_name_completion(
+                                name,
+                                node.range,
+                                debounce,
+                                config.max_items,
+                                token,
+                                tx,
+                            );
+                            continue;
+                        }
+                        if node.is_path_entry() {
+                            let manifest = Path::new(uri.path().as_str());
+                            //without a root manifest the package is its own workspace
+                            let root = doc
+                                .root_manifest
+                                .as_ref()
+                                .map_or(manifest, |root| Path::new(root.path().as_str()));
+                            let within =
+                                GLOBAL_CONFIG.read().unwrap().completion.path_within_workspace;
+                            let _ = tx.send(path_completion(
+                                &node,
+                                manifest.parent().unwrap_or(manifest),
+                                root.parent().filter(|_| within),
+                            ));
+                            continue;
+                        }
+                        let max_items = GLOBAL_CONFIG.read().unwrap().completion.max_items;
+                        let _ = tx.send(completion(&node, dep, max_items));
+                    }
+                    CargoDocumentEvent::CodeAction(uri, range, tx) => {
+                        resolve_deferred(&state, &debouncer, &mut lazy_resolve, &uri).await;
+                        let Some(doc) = state.document(&uri) else {
                             continue;
                         };
-                        let dep = doc.dependency(&id);
-                        let Some(action) = code_action(uri, node, dep) else {
+                        let Some(ResolvedContext {
+                            node,
+                            id: Some(id),
+                            dependency: dep,
+                        }) = doc.resolve_at(range.start)
+                        else {
                             continue;
                         };
-                        let _ = tx.send(action);
+                        let min_severity =
+                            GLOBAL_CONFIG.read().unwrap().audit.autofix_min_severity;
+                        let audits: Option<Vec<AuditResult>> =
+                            audit_results.get(&(uri.clone(), id.to_string())).map(|rr| {
+                                rr.iter()
+                                    .filter(|r| r.is_autofixable(min_severity))
+                                    .cloned()
+                                    .collect()
+                            });
+                        let workspace_dep = dep.and_then(|dep| {
+                            doc.root_manifest
+                                .as_ref()
+                                .filter(|root| *root != &uri)
+                                .and_then(|root| state.document(root))
+                                .and_then(|root| root.workspace_dependency(&dep.name))
+                        });
+                        let mut actions = code_action(
+                            uri.clone(),
+                            doc,
+                            node,
+                            dep,
+                            audits.as_deref(),
+                            workspace_dep,
+                        )
+                        .unwrap_or_default();
+                        //document wide, offered on every dependency
+                        actions.extend(code_action_fix_all_yanked(&uri, doc));
+                        if actions.is_empty() {
+                            continue;
+                        }
+                        let max_actions = GLOBAL_CONFIG.read().unwrap().code_action.max_actions;
+                        let _ = tx.send(cap_actions(actions, max_actions));
                     }
                     CargoDocumentEvent::Closed(uri) => {
+                        lazy_resolve.closed(&uri);
+                        crate_searches.cancel(&uri);
+                        state.close(&uri);
+                        if let Err(e) = audit_controller.closed(&uri).await {
+                            error!("audit controller send error: {}", e);
+                        };
                         if let Some(doc) = state.document_mut(&uri) {
                             doc.mark_dirty();
                             if let Err(e) = render_tx.send(DecorationEvent::Reset(uri)).await {
@@ -340,12 +635,49 @@ impl Appraiser {
                             }
                         }
                     }
-                    CargoDocumentEvent::Changed(msg) => {
+                    CargoDocumentEvent::ConfigChanged => {
+                        //decorations are formatted by the render task with the current formatter
+                        for doc in state.documents.values() {
+                            for dep in doc.dependencies.values() {
+                                if dep.is_virtual || doc.dirty_dependencies.contains_key(&dep.id) {
+                                    continue;
+                                }
+                                if let Err(e) =
+                                    render_tx.send(dependency_event(doc.uri.clone(), dep)).await
+                                {
+                                    error!("render tx send error: {}", e);
+                                }
+                            }
+                        }
+                        //filter the kept audit results again instead of running cargo audit
+                        let audit_config = GLOBAL_CONFIG.read().unwrap().audit.clone();
+                        diagnostic_controller.clear_audit_diagnostics().await;
+                        audit_results.retain(|_, rr| {
+                            rr.retain(|r| r.is_reported(&audit_config));
+                            !rr.is_empty()
+                        });
+                        for ((uri, id), rr) in &audit_results {
+                            let Some(dep) =
+                                state.document(uri).and_then(|doc| doc.dependencies.get(id))
+                            else {
+                                continue;
+                            };
+                            let diag = audit_diagnostic(dep, rr, &audit_config);
+                            diagnostic_controller.add_audit_diagnostic(uri, id, diag).await;
+                        }
+                    }
+                    CargoDocumentEvent::Changed(uri, changes) => {
+                        //an undo or retyping the same text leaves nothing to parse
+                        let Some(text) = state.update(&uri, &changes) else {
+                            continue;
+                        };
+                        let msg = CargoTomlPayload { uri, text };
                         diagnostic_controller
                             .clear_parse_diagnostics(&msg.uri)
                             .await;
                         //when Cargo.toml changed, clear audit diagnostics
                         diagnostic_controller.clear_audit_diagnostics().await;
+                        audit_results.clear();
                         let diff = match state.reconsile(&msg.uri, &msg.text) {
                             Ok((_, diff)) => diff,
                             Err(err) => {
@@ -361,6 +693,7 @@ impl Appraiser {
                             }
                         };
                         let doc = state.document(&msg.uri).unwrap();
+                        publish_parsing_hints(&mut diagnostic_controller, &msg.uri, doc).await;
                         for v in &diff.range_updated {
                             if let Some(node) = doc.entry(v) {
                                 render_tx
@@ -410,14 +743,35 @@ impl Appraiser {
                             reconsile_document(&mut state, &mut diagnostic_controller, &msg).await;
                     }
                     CargoDocumentEvent::Opened(msg) | CargoDocumentEvent::Saved(msg) => {
-                        if let Err(e) = audit_controller.send(&msg.uri).await {
-                            error!("audit controller send error: {}", e);
+                        if is_opened {
+                            if let Err(e) = audit_controller.opened(&msg.uri).await {
+                                error!("audit controller send error: {}", e);
+                            };
+                        }
+                        let resolve_now = match is_opened {
+                            true => {
+                                let lazy = GLOBAL_CONFIG.read().unwrap().resolve.lazy;
+                                lazy_resolve.opened(&msg.uri, lazy)
+                            }
+                            //saving resolves a deferred document as well
+                            false => {
+                                lazy_resolve.interacted(&msg.uri);
+                                true
+                            }
                         };
+                        if resolve_now {
+                            if let Err(e) = audit_controller.send(&msg.uri).await {
+                                error!("audit controller send error: {}", e);
+                            };
+                        }
                         let Some(doc) =
                             reconsile_document(&mut state, &mut diagnostic_controller, &msg).await
                         else {
                             continue;
                         };
+                        if !resolve_now {
+                            continue;
+                        }
 
                         if let Err(e) = debouncer
                             .send_interactive(Ctx {
@@ -445,6 +799,15 @@ impl Appraiser {
                                 &client_capabilities,
                             )
                             .await;
+                            //the audit of a workspace is kept while any of its documents is open
+                            if let Some(root) = state
+                                .document(&ctx.uri)
+                                .and_then(|doc| doc.root_manifest.as_ref())
+                            {
+                                if let Err(e) = audit_controller.workspace(&ctx.uri, root).await {
+                                    error!("audit controller send error: {}", e);
+                                };
+                            }
                         }
                     }
                     CargoDocumentEvent::CargoResolved(mut output) => {
@@ -456,80 +819,69 @@ impl Appraiser {
                         diagnostic_controller
                             .clear_cargo_diagnostics(&output.ctx.uri)
                             .await;
+                        doc.reverse_deps = std::mem::take(&mut output.reverse_deps);
                         //populate deps
                         for dep in doc.dependencies.values_mut() {
                             if dep.is_virtual {
                                 continue;
                             }
-                            let key = dep.toml_key();
-
                             if let Some(rev) = doc.dirty_dependencies.get(&dep.id) {
                                 if *rev > output.ctx.rev {
                                     continue;
                                 }
-                                // Take resolved out of the output.dependencies hashmap
-                                let maybe_resolved = output.dependencies.remove(&key);
-                                dep.resolved = maybe_resolved;
-
-                                let package_name = dep.package_name();
-                                let Some(mut summaries) = output.summaries.remove(package_name)
-                                else {
-                                    continue;
-                                };
-                                if let (Some(resolved), Some(unresolved)) =
-                                    (dep.resolved.as_ref(), dep.unresolved.as_ref())
-                                {
-                                    let installed = resolved.version().clone();
-                                    let req_version = unresolved.version_req();
-
-                                    //order summaries by version
-                                    //clear matched result from previous resolve
-                                    dep.matched_summary = None;
-                                    dep.latest_matched_summary = None;
-                                    dep.latest_summary = None;
-                                    summaries.sort_by(|a, b| b.version().cmp(a.version()));
-                                    for summary in &summaries {
-                                        if dep.matched_summary.is_some()
-                                            && dep.latest_matched_summary.is_some()
-                                            && dep.latest_summary.is_some()
-                                        {
-                                            break;
-                                        }
-                                        if &installed == summary.version() {
-                                            dep.matched_summary = Some(summary.clone());
-                                        }
-                                        if dep.latest_summary.is_none()
-                                            && summary.version().is_prerelease()
-                                                == installed.is_prerelease()
-                                        {
-                                            dep.latest_summary = Some(summary.clone());
-                                        }
-                                        if dep.latest_matched_summary.is_none()
-                                            && req_version.matches(summary.version())
-                                        {
-                                            dep.latest_matched_summary = Some(summary.clone());
-                                        }
-                                    }
-                                    dep.summaries = Some(summaries.clone());
-                                };
+                                output.populate(dep);
                                 //send to render task
                                 render_tx
-                                    .send(DecorationEvent::Dependency(
-                                        output.ctx.uri.clone(),
-                                        dep.id.clone(),
-                                        dep.range,
-                                        dep.clone(),
-                                    ))
+                                    .send(dependency_event(output.ctx.uri.clone(), dep))
                                     .await
                                     .unwrap();
                                 doc.dirty_dependencies.remove(&dep.id);
                             }
                         }
-                        if doc.is_dependencies_dirty() {
+                        let config = GLOBAL_CONFIG.read().unwrap().clone();
+                        let dirty = doc.is_dependencies_dirty();
+                        let rev = doc.rev;
+                        let mut lints = lint_dependencies(doc, &config);
+                        lints.extend(lint_feature_refs(doc));
+                        lints.extend(lint_dependency_features(doc));
+                        lints.extend(lint_unpublished_requirement(doc));
+                        lints.extend(lint_optional_not_installed(doc));
+                        lints.extend(lint_dual_declaration(doc));
+                        //the resolve graph is shared by the workspace, report on the root only
+                        if doc.root_manifest.as_ref().map_or(true, |root| root == &output.ctx.uri) {
+                            lints.extend(lint_duplicate_versions(doc, &output.duplicates));
+                        }
+                        if config.resolve.check_lockfile {
+                            lints.extend(lint_unlocked(doc, &output.unlocked));
+                        }
+                        if let Some(doc) = state.document(&output.ctx.uri) {
+                            if let Some(root) = doc
+                                .root_manifest
+                                .as_ref()
+                                .filter(|root| *root != &output.ctx.uri)
+                                .and_then(|root| state.document(root))
+                            {
+                                lints.extend(lint_workspace_divergence(doc, root));
+                            }
+                            //documents outside a workspace have no siblings
+                            if config.style.consistent_versions && doc.root_manifest.is_some() {
+                                let others = state.documents.values().filter(|d| {
+                                    d.uri != doc.uri && d.root_manifest == doc.root_manifest
+                                });
+                                lints.extend(lint_version_syntax(doc, others));
+                            }
+                        }
+                        for (id, diag) in lints {
+                            diagnostic_controller
+                                .add_cargo_diagnostic(&output.ctx.uri, &id, diag)
+                                .await;
+                        }
+                        let _ = signal_tx.send(AppraiserSignal::Resolved(output.ctx.clone()));
+                        if dirty {
                             if let Err(e) = debouncer
                                 .send_background(Ctx {
                                     uri: output.ctx.uri,
-                                    rev: doc.rev,
+                                    rev,
                                 })
                                 .await
                             {
@@ -544,6 +896,114 @@ impl Appraiser {
     }
 }
 
+//`latest 1.0.2 published 3 days ago`
+fn publish_date_text(version: &Version, published: i64) -> String {
+    format!("latest {} published {}", version, relative_date(published, today()))
+}
+
+//crates.io lookups of a hover which aren't cached
+#[derive(Default)]
+struct HoverLookup {
+    //the package name and the latest version
+    publish_date: Option<(String, Version)>,
+    //the package name
+    crate_meta: Option<String>,
+}
+
+impl HoverLookup {
+    fn is_empty(&self) -> bool {
+        self.publish_date.is_none() && self.crate_meta.is_none()
+    }
+}
+
+//fetch outside the event loop, the hover is sent with whatever is fetched
+fn spawn_hover_lookup(
+    lookup: HoverLookup,
+    mut h: Option<Hover>,
+    range: Range,
+    crate_metas: Cache<String, CrateMeta>,
+    tx: oneshot::Sender<Option<Hover>>,
+    event_tx: Sender<CargoDocumentEvent>,
+) {
+    tokio::spawn(async move {
+        let (published, meta) = tokio::join!(
+            async {
+                let (name, version) = lookup.publish_date.as_ref()?;
+                fetch_publish_date(name, version).await
+            },
+            async { fetch_crate_meta(lookup.crate_meta.as_ref()?).await },
+        );
+        if let (Some(hover), Some((_, version)), Some(published)) =
+            (h.as_mut(), lookup.publish_date.as_ref(), published)
+        {
+            prepend_hover_text(hover, &publish_date_text(version, published));
+        }
+        if let (Some(name), Some(meta)) = (lookup.crate_meta, meta) {
+            let text = downloads_text(&meta);
+            match h.as_mut() {
+                Some(hover) => prepend_hover_text(hover, &text),
+                None => h = Some(text_hover(text, range)),
+            }
+            crate_metas.insert(name, meta).await;
+        }
+        let _ = tx.send(h);
+        if let (Some((name, version)), Some(published)) = (lookup.publish_date, published) {
+            let event = CargoDocumentEvent::PublishDate(name, version.to_string(), published);
+            if let Err(e) = event_tx.send(event).await {
+                error!("error sending publish date event: {}", e);
+            }
+        }
+    });
+}
+
+//read the root manifest and parse it without resolving
+async fn parse_root_manifest(
+    root_uri: &Uri,
+    inner_tx: &Sender<CargoDocumentEvent>,
+    client: &Client,
+    client_capabilities: &ClientCapabilities,
+) {
+    let Some(text) = read_manifest(root_uri, client, client_capabilities).await else {
+        return;
+    };
+    if let Err(e) = inner_tx
+        .send(CargoDocumentEvent::Parse(CargoTomlPayload {
+            uri: root_uri.clone(),
+            text,
+        }))
+        .await
+    {
+        error!("inner tx send error: {}", e);
+    }
+}
+
+//read a manifest which isn't opened, through the client when it can read files
+async fn read_manifest(
+    uri: &Uri,
+    client: &Client,
+    client_capabilities: &ClientCapabilities,
+) -> Option<String> {
+    if client_capabilities.can_read_file() {
+        let param = ReadFileParam { uri: uri.clone() };
+        match client.send_request::<ReadFile>(param).await {
+            Ok(content) => Some(content.content),
+            Err(e) => {
+                error!("read file error: {}", e);
+                None
+            }
+        }
+    } else {
+        //read file with os
+        match std::fs::read_to_string(uri.path().as_str()) {
+            Ok(content) => Some(content),
+            Err(e) => {
+                error!("read file error: {}", e);
+                None
+            }
+        }
+    }
+}
+
 async fn start_resolve(
     uri: &Uri,
     state: &mut Workspace,
@@ -558,41 +1018,19 @@ async fn start_resolve(
     };
     doc.populate_dependencies();
 
+    //the renderer skips them unless `decoration.showMemberVersions` is set
+    for (id, range, version) in doc.member_version_hints() {
+        if let Err(e) = render_tx
+            .send(DecorationEvent::Member(uri.clone(), id, range, version))
+            .await
+        {
+            error!("render tx send error: {}", e);
+        }
+    }
+
     if let Some(root_uri) = doc.root_manifest.as_ref() {
         if root_uri != uri {
-            if client_capabilities.can_read_file() {
-                let param = ReadFileParam {
-                    uri: root_uri.clone(),
-                };
-                match client.send_request::<ReadFile>(param).await {
-                    Ok(content) => {
-                        if let Err(e) = inner_tx
-                            .send(CargoDocumentEvent::Parse(CargoTomlPayload {
-                                uri: root_uri.clone(),
-                                text: content.content,
-                            }))
-                            .await
-                        {
-                            error!("inner tx send error: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        error!("read file error: {}", e);
-                    }
-                }
-            } else {
-                //read file with os
-                let content = std::fs::read_to_string(root_uri.path().as_str()).unwrap();
-                if let Err(e) = inner_tx
-                    .send(CargoDocumentEvent::Parse(CargoTomlPayload {
-                        uri: root_uri.clone(),
-                        text: content,
-                    }))
-                    .await
-                {
-                    error!("inner tx send error: {}", e);
-                }
-            }
+            parse_root_manifest(root_uri, inner_tx, client, client_capabilities).await;
         }
     }
 
@@ -607,6 +1045,9 @@ async fn start_resolve(
     }
 
     for v in doc.dirty_dependencies.keys() {
+        if doc.dependency(v).is_some_and(|dep| dep.hidden) {
+            continue;
+        }
         if let Some(n) = doc.entry(v) {
             render_tx
                 .send(DecorationEvent::DependencyWaiting(
@@ -638,6 +1079,8 @@ async fn reconsile_document<'a>(
 ) -> Option<&'a Document> {
     match state.reconsile(&msg.uri, &msg.text) {
         Ok((doc, diff)) => {
+            diagnostic_controller.clear_parse_diagnostics(&msg.uri).await;
+            publish_parsing_hints(diagnostic_controller, &msg.uri, doc).await;
             if diff.is_empty() && !doc.is_dependencies_dirty() {
                 None
             } else {
@@ -657,3 +1100,43 @@ async fn reconsile_document<'a>(
         }
     }
 }
+
+//non fatal parsing errors don't block the document, publish them as is
+async fn publish_parsing_hints(
+    diagnostic_controller: &mut DiagnosticController,
+    uri: &Uri,
+    doc: &Document,
+) {
+    for e in &doc.parsing_errors {
+        let Some((id, diag)) = e.clone().diagnostic() else {
+            continue;
+        };
+        diagnostic_controller
+            .add_parse_diagnostic(uri, &id, diag)
+            .await;
+    }
+}
+
+//start the deferred resolve of a document opened with `resolve.lazy`
+async fn resolve_deferred(
+    state: &Workspace,
+    debouncer: &Debouncer,
+    lazy_resolve: &mut LazyResolve,
+    uri: &Uri,
+) {
+    if !lazy_resolve.interacted(uri) {
+        return;
+    }
+    let Some(doc) = state.document(uri) else {
+        return;
+    };
+    if let Err(e) = debouncer
+        .send_interactive(Ctx {
+            uri: uri.clone(),
+            rev: doc.rev,
+        })
+        .await
+    {
+        error!("debounder send interactive error: {}", e);
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
     pin::Pin,
     str::FromStr,
@@ -7,14 +7,18 @@ use std::{
 };
 
 use petgraph::prelude::NodeIndex;
+use serde::{Deserialize, Serialize};
 use tokio::{
     sync::mpsc::{self, error::SendError, Sender},
     time::Sleep,
 };
-use tower_lsp::lsp_types::{DiagnosticSeverity, Uri};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Range, Uri};
 use tracing::{error, info};
 
-use crate::entity::into_file_uri_str;
+use crate::{
+    config::{AuditConfig, AuditDiagnosticRange, AuditHoverDetail, GLOBAL_CONFIG},
+    entity::{into_file_uri_str, Dependency},
+};
 
 use super::CargoDocumentEvent;
 
@@ -25,43 +29,263 @@ pub struct AuditReports {
     pub members: HashMap<PathBuf, HashMap<String, HashMap<String, Vec<AuditResult>>>>,
 }
 
+impl AuditReports {
+    //drop results that are not reported, and dependencies left without results
+    pub fn retain_reported(&mut self, config: &AuditConfig) {
+        for report in self.members.values_mut() {
+            for versions in report.values_mut() {
+                for rr in versions.values_mut() {
+                    rr.retain(|r| r.is_reported(config));
+                }
+                versions.retain(|_, rr| !rr.is_empty());
+            }
+            report.retain(|_, versions| !versions.is_empty());
+        }
+    }
+
+    //merge results of the same advisory on the same package, keeping every dependency path
+    pub fn dedupe(&mut self) {
+        for report in self.members.values_mut() {
+            for versions in report.values_mut() {
+                for rr in versions.values_mut() {
+                    *rr = dedupe_results(std::mem::take(rr));
+                }
+            }
+        }
+    }
+}
+
+//vulnerabilities and warnings with an advisory are keyed by `(crate name, version, id)`,
+//warnings without an advisory by `(crate name, version, kind)`
+pub fn dedupe_results(results: Vec<AuditResult>) -> Vec<AuditResult> {
+    let mut deduped: Vec<AuditResult> = Vec::with_capacity(results.len());
+    for result in results {
+        let key = result.dedupe_key();
+        let existing = deduped
+            .iter_mut()
+            .find(|r| key.is_some() && r.dedupe_key() == key);
+        match existing {
+            Some(existing) => {
+                for path in result.tree {
+                    if !existing.tree.contains(&path) {
+                        existing.tree.push(path);
+                    }
+                }
+            }
+            None => deduped.push(result),
+        }
+    }
+    deduped
+}
+
 pub struct AuditController {
     tx: Sender<CargoDocumentEvent>,
-    sender: Option<Sender<Uri>>,
+    sender: Option<Sender<AuditEvent>>,
+}
+
+pub enum AuditEvent {
+    //audit the workspace of a manifest or lockfile after the debounce
+    Schedule(Uri),
+    Opened(Uri),
+    Closed(Uri),
+    //the resolved workspace root manifest of a document
+    Workspace(Uri, Uri),
 }
 
 #[derive(Debug, Clone)]
 pub struct AuditResult {
     pub warning: Option<rustsec::Warning>,
     pub vuln: Option<rustsec::Vulnerability>,
+    //the CVSS v3 base score of a vulnerability
+    pub score: Option<f32>,
     pub tree: Vec<Vec<String>>,
 }
 
+//the qualitative CVSS v3 rating
+#[derive(
+    Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditSeverity {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl From<rustsec::advisory::Severity> for AuditSeverity {
+    fn from(severity: rustsec::advisory::Severity) -> Self {
+        match severity {
+            rustsec::advisory::Severity::None => AuditSeverity::None,
+            rustsec::advisory::Severity::Low => AuditSeverity::Low,
+            rustsec::advisory::Severity::Medium => AuditSeverity::Medium,
+            rustsec::advisory::Severity::High => AuditSeverity::High,
+            rustsec::advisory::Severity::Critical => AuditSeverity::Critical,
+        }
+    }
+}
+
+pub const NO_SAFE_UPGRADE: &str = "No safe upgrade available";
+
 impl AuditResult {
-    pub fn severity(&self) -> DiagnosticSeverity {
+    pub fn vulnerability(vuln: rustsec::Vulnerability, tree: Vec<Vec<String>>) -> Self {
+        let score = vuln
+            .advisory
+            .cvss
+            .as_ref()
+            .map(|cvss| f64::from(cvss.score()) as f32);
+        AuditResult {
+            warning: None,
+            vuln: Some(vuln),
+            score,
+            tree,
+        }
+    }
+
+    fn dedupe_key(&self) -> Option<(String, String, String)> {
+        if let Some(vuln) = &self.vuln {
+            return Some((
+                vuln.package.name.to_string(),
+                vuln.package.version.to_string(),
+                vuln.advisory.id.to_string(),
+            ));
+        }
+        let warning = self.warning.as_ref()?;
+        let id = warning
+            .advisory
+            .as_ref()
+            .map_or(warning.kind.to_string(), |a| a.id.to_string());
+        Some((
+            warning.package.name.to_string(),
+            warning.package.version.to_string(),
+            id,
+        ))
+    }
+
+    pub fn severity(&self, config: &AuditConfig) -> DiagnosticSeverity {
         if self.vuln.is_some() {
-            return DiagnosticSeverity::ERROR;
+            return match self.cvss() {
+                Some((_, AuditSeverity::Low | AuditSeverity::Medium)) => DiagnosticSeverity::WARNING,
+                //without a cvss vector we can't tell, report it as an error
+                _ => DiagnosticSeverity::ERROR,
+            };
         }
         if self.warning.is_some() {
-            return DiagnosticSeverity::WARNING;
+            return config.warning_severity.into();
         }
         DiagnosticSeverity::INFORMATION
     }
 
-    pub fn audit_text(&self) -> String {
+    //the affected package and the advisory, e.g. `crossbeam-channel 0.5.14 (RUSTSEC-2020-0001)`
+    pub fn label(&self) -> String {
         if let Some(vuln) = &self.vuln {
+            return format!("{} {} ({})", vuln.package.name, vuln.package.version, vuln.advisory.id);
+        }
+        if let Some(warning) = &self.warning {
+            return format!(
+                "{} {} ({})",
+                warning.package.name, warning.package.version, warning.kind
+            );
+        }
+        String::new()
+    }
+
+    //not ignored and, for warnings, of a kind selected by `audit.warningKinds`
+    pub fn is_reported(&self, config: &AuditConfig) -> bool {
+        if self.is_ignored(&config.ignore) {
+            return false;
+        }
+        match &self.warning {
+            Some(warning) if self.vuln.is_none() => {
+                config.reports_warning_kind(&warning.kind.to_string())
+            }
+            _ => true,
+        }
+    }
+
+    pub fn is_ignored(&self, ignore: &[String]) -> bool {
+        if let Some(vuln) = &self.vuln {
+            return is_ignored(
+                ignore,
+                Some(vuln.advisory.id.as_str()),
+                vuln.package.name.as_str(),
+                "vulnerability",
+            );
+        }
+        if let Some(warning) = &self.warning {
+            return is_ignored(
+                ignore,
+                warning.advisory.as_ref().map(|a| a.id.as_str()),
+                warning.package.name.as_str(),
+                &warning.kind.to_string(),
+            );
+        }
+        false
+    }
+
+    //a vulnerability rated at least `min` gets an upgrade quickfix,
+    //without a cvss vector we can't tell, keep the quickfix
+    pub fn is_autofixable(&self, min: AuditSeverity) -> bool {
+        self.cvss().map_or(true, |(_, severity)| severity >= min)
+    }
+
+    //the cvss score and rating of a vulnerability
+    pub fn cvss(&self) -> Option<(Option<f32>, AuditSeverity)> {
+        let cvss = self.vuln.as_ref()?.advisory.cvss.as_ref()?;
+        Some((self.score, cvss.severity().into()))
+    }
+
+    fn severity_line(&self) -> Option<String> {
+        let cvss = self.vuln.as_ref()?.advisory.cvss.as_ref()?;
+        Some(format!("* Severity: {:.1} ({})", f64::from(cvss.score()), cvss.severity()))
+    }
+
+    //the cargo audit solution of a vulnerability, e.g. `Upgrade to >=0.5.15`
+    pub fn solution(&self) -> Option<String> {
+        let vuln = self.vuln.as_ref()?;
+        let patched = vuln.versions.patched();
+        if patched.is_empty() {
+            return Some(NO_SAFE_UPGRADE.to_string());
+        }
+        Some(format!(
+            "Upgrade to {}",
+            patched
+                .iter()
+                .map(|req| req.to_string())
+                .collect::<Vec<_>>()
+                .join(" OR ")
+        ))
+    }
+
+    pub fn audit_text(&self, detail: AuditHoverDetail) -> String {
+        if let Some(vuln) = &self.vuln {
+            if detail == AuditHoverDetail::Minimal {
+                return format!(
+                    "# {}\n\n\
+                    * ID: {}\n\
+                    {}\n\
+                    ",
+                    vuln.advisory.title,
+                    vuln.advisory.id,
+                    self.severity_line().unwrap_or_default(),
+                );
+            }
             return format!(
                 "# {}\n\n\
                 {}\n\n\
                 * Package: {} {}\n\
                 * ID: {}\n\
-                {}\n\n\
+                {}{}{}\n\n\
                 ",
                 vuln.advisory.title,
                 vuln.advisory.description,
                 vuln.package.name,
                 vuln.package.version,
                 vuln.advisory.id,
+                self.severity_line().map_or("".to_string(), |line| line + "\n"),
+                self.solution().map_or("".to_string(), |s| format!("* Solution: {}\n", s)),
                 vuln.advisory
                     .url
                     .as_ref()
@@ -80,11 +304,20 @@ impl AuditResult {
     }
 }
 
-pub fn into_diagnostic_text(reports: &[AuditResult]) -> String {
+//an ignore entry is either the advisory id or `crate:kind`
+pub fn is_ignored(ignore: &[String], id: Option<&str>, package: &str, kind: &str) -> bool {
+    ignore.iter().any(|i| {
+        Some(i.as_str()) == id
+            || i.split_once(':')
+                .is_some_and(|(name, k)| name == package && k.eq_ignore_ascii_case(kind))
+    })
+}
+
+pub fn into_diagnostic_text(reports: &[AuditResult], config: &AuditConfig) -> String {
     let mut s = String::new();
     let mut tree = String::new();
     for r in reports {
-        s.push_str(&r.audit_text());
+        s.push_str(&r.audit_text(config.hover_detail));
         tree.push_str(
             r.tree
                 .iter()
@@ -94,6 +327,9 @@ pub fn into_diagnostic_text(reports: &[AuditResult]) -> String {
                 .as_str(),
         );
     }
+    if config.hover_detail == AuditHoverDetail::Minimal {
+        return s;
+    }
     s.push_str("# Dependency Paths:\n\n");
     s.push_str(&tree);
     s
@@ -101,47 +337,142 @@ pub fn into_diagnostic_text(reports: &[AuditResult]) -> String {
 
 pub fn into_diagnostic_severity(
     reports: &[AuditResult],
+    config: &AuditConfig,
 ) -> tower_lsp::lsp_types::DiagnosticSeverity {
     reports
         .iter()
-        .map(|r| r.severity())
+        .map(|r| r.severity(config))
         .min()
         .unwrap_or(DiagnosticSeverity::INFORMATION)
 }
 
+//a table dependency spans multiple lines, the key range gives a tighter squiggle
+pub fn into_diagnostic_range(dep: &Dependency, config: &AuditConfig) -> Range {
+    match config.diagnostic_range {
+        AuditDiagnosticRange::Key => dep.key_range,
+        AuditDiagnosticRange::Entry => dep.range,
+    }
+}
+
+pub fn audit_diagnostic(
+    dep: &Dependency,
+    reports: &[AuditResult],
+    config: &AuditConfig,
+) -> Diagnostic {
+    Diagnostic {
+        range: into_diagnostic_range(dep, config),
+        severity: Some(into_diagnostic_severity(reports, config)),
+        code: None,
+        code_description: None,
+        source: Some("cargo-appraiser".to_string()),
+        message: into_diagnostic_text(reports, config),
+        related_information: None,
+        tags: None,
+        data: None,
+    }
+}
+
 impl AuditController {
     pub fn new(tx: Sender<CargoDocumentEvent>) -> Self {
         Self { tx, sender: None }
     }
 
-    pub async fn send(&self, uri: &Uri) -> Result<(), SendError<Uri>> {
-        self.sender.as_ref().unwrap().send(uri.clone()).await
+    pub async fn send(&self, uri: &Uri) -> Result<(), SendError<AuditEvent>> {
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(AuditEvent::Schedule(uri.clone()))
+            .await
+    }
+
+    pub async fn opened(&self, uri: &Uri) -> Result<(), SendError<AuditEvent>> {
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(AuditEvent::Opened(uri.clone()))
+            .await
+    }
+
+    //the pending audit is dropped when no opened document is left in its workspace
+    pub async fn closed(&self, uri: &Uri) -> Result<(), SendError<AuditEvent>> {
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(AuditEvent::Closed(uri.clone()))
+            .await
+    }
+
+    pub async fn workspace(&self, uri: &Uri, root: &Uri) -> Result<(), SendError<AuditEvent>> {
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(AuditEvent::Workspace(uri.clone(), root.clone()))
+            .await
     }
 
     pub fn spawn(&mut self) {
+        self.spawn_with(
+            |uri: &Uri| audit_workspace(uri, &mut None),
+            || Duration::from_secs(GLOBAL_CONFIG.read().unwrap().audit.debounce_secs),
+            AUDIT_BACKOFF,
+        );
+    }
+
+    //the debounce is read on every arm, a config change applies to the next payload
+    //a transient failure re-arms the timer with the backoff delay, events are received meanwhile
+    fn spawn_with<F, D>(&mut self, mut runner: F, debounce: D, backoff: &'static [Duration])
+    where
+        F: FnMut(&Uri) -> Result<AuditReports, anyhow::Error> + Send + 'static,
+        D: Fn() -> Duration + Send + 'static,
+    {
         //create a mpsc channel
         let (internal_tx, mut internal_rx) = mpsc::channel(32);
-        let mut received_uri = None;
+        let mut received_uri: Option<Uri> = None;
+        let mut opened: HashSet<Uri> = HashSet::new();
+        let mut roots: HashMap<Uri, Uri> = HashMap::new();
         self.sender = Some(internal_tx);
         let tx = self.tx.clone();
         let mut timer: Option<Pin<Box<Sleep>>> = None;
+        //the retries of the pending audit
+        let mut attempt = 0;
         //spawn a task to listen to the channel
         tokio::spawn(async move {
             loop {
                 tokio::select! {
-                    Some(uri) = internal_rx.recv() => {
-                        if received_uri.is_none() {
-                            if uri.path().as_str().ends_with(".lock") {
-                                received_uri = Some(
-                                    Uri::from_str(&uri.path().as_str().replace(".lock", ".toml").to_string())
-                                        .unwrap(),
-                                );
-                            } else {
-                                received_uri = Some(uri);
+                    Some(event) = internal_rx.recv() => match event {
+                        AuditEvent::Schedule(uri) => {
+                            if received_uri.is_none() {
+                                if uri.path().as_str().ends_with(".lock") {
+                                    let manifest = uri.path().as_str().replace(".lock", ".toml");
+                                    received_uri = Some(Uri::from_str(&manifest).unwrap());
+                                } else {
+                                    received_uri = Some(uri);
+                                }
                             }
+                            //a zero debounce runs the audit right away
+                            timer = Some(Box::pin(tokio::time::sleep(debounce())));
+                            attempt = 0;
                         }
-                        timer = Some(Box::pin(tokio::time::sleep(Duration::from_secs(60))));
-                    }
+                        AuditEvent::Opened(uri) => {
+                            opened.insert(uri);
+                        }
+                        AuditEvent::Closed(uri) => {
+                            opened.remove(&uri);
+                            //nothing opened is left in the workspace of the pending audit
+                            let is_wasted = received_uri.as_ref().is_some_and(|pending| {
+                                let root = workspace_root(&roots, pending);
+                                !opened.iter().any(|o| workspace_root(&roots, o) == root)
+                            });
+                            if is_wasted {
+                                received_uri = None;
+                                timer = None;
+                                attempt = 0;
+                            }
+                        }
+                        AuditEvent::Workspace(uri, root) => {
+                            roots.insert(uri, root);
+                        }
+                    },
                     () = async {
                         if let Some(ref mut t) = timer {
                             t.await
@@ -150,15 +481,31 @@ impl AuditController {
                         }
                     }, if timer.is_some() => {
                         timer = None;
-                        let mut audited_worksapce=None;
                         let uri = received_uri.take().unwrap();
-                        let reports = match audit_workspace(&uri, &mut audited_worksapce) {
+                        let reports = match runner(&uri) {
                             Ok(r) => r,
                             Err(e) => {
-                                error!("Failed to audit workspace {}: {}", uri.path(), e);
+                                match retry_delay(&e, attempt, backoff) {
+                                    Some(delay) => {
+                                        info!(
+                                            "audit of {} failed, retry in {:?}: {}",
+                                            uri.path(),
+                                            delay,
+                                            e
+                                        );
+                                        attempt += 1;
+                                        received_uri = Some(uri);
+                                        timer = Some(Box::pin(tokio::time::sleep(delay)));
+                                    }
+                                    None => {
+                                        error!("Failed to audit workspace {}: {}", uri.path(), e);
+                                        attempt = 0;
+                                    }
+                                }
                                 continue;
                             }
                         };
+                        attempt = 0;
                         if let Err(e) = tx.send(CargoDocumentEvent::Audited(reports)).await {
                             error!("failed to send Audited event: {}", e);
                         }
@@ -169,6 +516,49 @@ impl AuditController {
     }
 }
 
+//a document not resolved yet is its own workspace
+fn workspace_root<'a>(roots: &'a HashMap<Uri, Uri>, uri: &'a Uri) -> &'a Uri {
+    roots.get(uri).unwrap_or(uri)
+}
+
+//delays before each retry of a transient audit failure
+const AUDIT_BACKOFF: &[Duration] = &[
+    Duration::from_secs(5),
+    Duration::from_secs(15),
+    Duration::from_secs(45),
+];
+
+//the delay before the next attempt of a failed audit,
+//None for a permanent failure or when the retries are used up
+pub fn retry_delay(err: &anyhow::Error, attempt: usize, backoff: &[Duration]) -> Option<Duration> {
+    if !is_transient(err) {
+        return None;
+    }
+    backoff.get(attempt).copied()
+}
+
+//network and io failures, e.g. fetching the advisory database timed out
+//a missing lockfile or an invalid manifest won't recover by retrying
+pub fn is_transient(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+            return matches!(
+                e.kind(),
+                std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::WouldBlock
+            );
+        }
+        if let Some(e) = cause.downcast_ref::<rustsec::Error>() {
+            return matches!(e.kind(), rustsec::ErrorKind::Repo);
+        }
+        false
+    })
+}
+
 //uri should be a Cargo.toml file
 pub fn audit_workspace(
     uri: &Uri,
@@ -178,9 +568,13 @@ pub fn audit_workspace(
     let path = Path::new(uri.path().as_str());
     let workspace = cargo::core::Workspace::new(path, &gctx)?;
 
-    let root = workspace.lock_root().display().to_string();
+    //members in subdirectories share the lockfile at the workspace root
+    let lock_path = ensure_lock(workspace.lock_root().as_path_unlocked(), path)?;
+    let root = lock_path
+        .parent()
+        .map_or(String::new(), |p| p.display().to_string());
     let root_uri = into_file_uri_str(&(root.to_string() + "/Cargo.toml"));
-    let lock = root + "/Cargo.lock";
+    let lock = lock_path.display().to_string();
     //if audited is some and eq to lock, return
     if let Some(audited_lock) = audited {
         if *audited_lock == lock {
@@ -229,9 +623,19 @@ pub fn audit_workspace(
 
     let mut warnings_map: HashMap<NodeIndex, rustsec::Warning> = HashMap::new();
     let mut vulns_map: HashMap<NodeIndex, rustsec::Vulnerability> = HashMap::new();
+    //ignored advisories never reach the reports
+    let ignore = GLOBAL_CONFIG.read().unwrap().audit.ignore.clone();
 
     for warnings in report.warnings.values() {
         for w in warnings {
+            if is_ignored(
+                &ignore,
+                w.advisory.as_ref().map(|a| a.id.as_str()),
+                w.package.name.as_str(),
+                &w.kind.to_string(),
+            ) {
+                continue;
+            }
             let p = w.package.clone();
 
             //this is the warning's package node index
@@ -241,6 +645,14 @@ pub fn audit_workspace(
     }
 
     for vul in &report.vulnerabilities.list {
+        if is_ignored(
+            &ignore,
+            Some(vul.advisory.id.as_str()),
+            vul.package.name.as_str(),
+            "vulnerability",
+        ) {
+            continue;
+        }
         let p = vul.package.clone();
         let package_node_indx = tree.nodes()[&cargo_lock::Dependency::from(&p)];
         vulns_map.insert(package_node_indx, vul.clone());
@@ -304,6 +716,7 @@ pub fn audit_workspace(
                 .push(AuditResult {
                     warning: Some(warning.clone()),
                     vuln: None,
+                    score: None,
                     tree,
                 });
         }
@@ -355,18 +768,48 @@ pub fn audit_workspace(
                 .or_default()
                 .entry(version)
                 .or_default()
-                .push(AuditResult {
-                    warning: None,
-                    vuln: Some(vuln.clone()),
-                    tree,
-                });
+                .push(AuditResult::vulnerability(vuln.clone(), tree));
         }
     }
+    reports.dedupe();
     Ok(reports)
 }
 
+//`cargo update -p <crate> --precise <version>` for a single advisory,
+//otherwise the experimental `cargo audit fix` for the whole lockfile
+pub fn audit_fix_args(fix: Option<(&str, &str)>) -> Vec<String> {
+    match fix {
+        Some((package, version)) => ["update", "-p", package, "--precise", version]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        None => vec!["audit".to_string(), "fix".to_string()],
+    }
+}
+
+//the lockfile at the workspace lock root,
+//or the nearest Cargo.lock above the manifest if the lock root has none
+pub fn ensure_lock(lock_root: &Path, manifest: &Path) -> Result<PathBuf, anyhow::Error> {
+    let lock = lock_root.join("Cargo.lock");
+    if lock.is_file() {
+        return Ok(lock);
+    }
+    manifest
+        .ancestors()
+        .skip(1)
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lock| lock.is_file())
+        .ok_or_else(|| anyhow::anyhow!("Cargo.lock not found for {}", manifest.display()))
+}
+
+#[cfg(test)]
 mod tests {
-    use crate::entity::into_file_uri;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use crate::{entity::into_file_uri, fixture::Fixture};
 
     use super::*;
     #[test]
@@ -395,4 +838,389 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_ensure_lock_in_workspace_root() {
+        let fixture =
+            Fixture::new(&[("Cargo.lock", "version = 4\n"), ("crates/foo/Cargo.toml", "")]);
+        let root = fixture.root();
+        let member = root.join("crates").join("foo");
+        let manifest = member.join("Cargo.toml");
+
+        //lock root points at the workspace root
+        assert_eq!(ensure_lock(root, &manifest).unwrap(), root.join("Cargo.lock"));
+        //lock root without a lockfile, walk up from the member
+        assert_eq!(
+            ensure_lock(&member, &manifest).unwrap(),
+            root.join("Cargo.lock")
+        );
+    }
+
+    fn warning_result(kind: rustsec::WarningKind) -> AuditResult {
+        let package = cargo_lock::Package {
+            name: "dotenv".parse().unwrap(),
+            version: "0.15.0".parse().unwrap(),
+            source: None,
+            checksum: None,
+            dependencies: vec![],
+            replace: None,
+        };
+        AuditResult {
+            warning: Some(rustsec::Warning::new(kind, &package, None, None, None)),
+            vuln: None,
+            score: None,
+            tree: vec![],
+        }
+    }
+
+    fn vuln_result() -> AuditResult {
+        vuln_result_with_cvss("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H")
+    }
+
+    fn vuln_result_with_cvss(cvss: &str) -> AuditResult {
+        let advisory: rustsec::Advisory = format!(
+            r#"```toml
+[advisory]
+id = "RUSTSEC-2020-0001"
+package = "crossbeam-channel"
+date = "2020-01-01"
+url = "https://example.com/RUSTSEC-2020-0001"
+cvss = "{}"
+
+[versions]
+patched = [">= 0.5.15"]
+```
+
+# Memory corruption in crossbeam-channel
+
+The channel may free memory twice.
+"#,
+            cvss
+        )
+        .parse()
+        .unwrap();
+        let package = cargo_lock::Package {
+            name: "crossbeam-channel".parse().unwrap(),
+            version: "0.5.14".parse().unwrap(),
+            source: None,
+            checksum: None,
+            dependencies: vec![],
+            replace: None,
+        };
+        AuditResult::vulnerability(
+            rustsec::Vulnerability::new(&advisory, &package),
+            vec![vec!["tauri".to_string(), "crossbeam-channel".to_string()]],
+        )
+    }
+
+    #[test]
+    fn test_warning_kinds() {
+        let config = AuditConfig {
+            warning_kinds: vec!["unsound".to_string(), "yanked".to_string()],
+            ..Default::default()
+        };
+        assert!(!warning_result(rustsec::WarningKind::Unmaintained).is_reported(&config));
+        assert!(warning_result(rustsec::WarningKind::Unsound).is_reported(&config));
+        assert!(warning_result(rustsec::WarningKind::Yanked).is_reported(&config));
+        //vulnerabilities are not a warning kind
+        assert!(vuln_result().is_reported(&config));
+
+        let mut reports = AuditReports {
+            root: into_file_uri(Path::new("/a/Cargo.toml")),
+            members: HashMap::from([(
+                PathBuf::from("/a"),
+                HashMap::from([(
+                    "dotenv".to_string(),
+                    HashMap::from([(
+                        "0.15.0".to_string(),
+                        vec![
+                            warning_result(rustsec::WarningKind::Unmaintained),
+                            warning_result(rustsec::WarningKind::Unsound),
+                        ],
+                    )]),
+                )]),
+            )]),
+        };
+        reports.retain_reported(&config);
+        let rr = &reports.members[&PathBuf::from("/a")]["dotenv"]["0.15.0"];
+        assert_eq!(rr.len(), 1);
+        assert!(rr[0].warning.as_ref().unwrap().kind == rustsec::WarningKind::Unsound);
+
+        //a dependency without reported results is dropped
+        reports.retain_reported(&AuditConfig {
+            warning_kinds: vec![],
+            ..Default::default()
+        });
+        assert!(reports.members[&PathBuf::from("/a")].is_empty());
+    }
+
+    #[test]
+    fn test_dedupe_results() {
+        let mut a = vuln_result();
+        a.tree = vec![vec!["tokio".to_string(), "crossbeam-channel".to_string()]];
+        let mut b = vuln_result();
+        b.tree = vec![
+            vec!["rayon".to_string(), "crossbeam-channel".to_string()],
+            vec!["tokio".to_string(), "crossbeam-channel".to_string()],
+        ];
+        let deduped = dedupe_results(vec![a, b]);
+        assert_eq!(deduped.len(), 1);
+        assert_eq!(
+            deduped[0].tree,
+            vec![
+                vec!["tokio".to_string(), "crossbeam-channel".to_string()],
+                vec!["rayon".to_string(), "crossbeam-channel".to_string()],
+            ]
+        );
+
+        //warnings without an advisory are keyed by kind
+        let deduped = dedupe_results(vec![
+            warning_result(rustsec::WarningKind::Unmaintained),
+            warning_result(rustsec::WarningKind::Unmaintained),
+            warning_result(rustsec::WarningKind::Yanked),
+        ]);
+        assert_eq!(deduped.len(), 2);
+    }
+
+    #[test]
+    fn test_hover_detail() {
+        let result = vuln_result();
+        let full = AuditConfig::default();
+        let minimal = AuditConfig {
+            hover_detail: AuditHoverDetail::Minimal,
+            ..Default::default()
+        };
+        let full = into_diagnostic_text(std::slice::from_ref(&result), &full);
+        let minimal = into_diagnostic_text(std::slice::from_ref(&result), &minimal);
+        for text in [&full, &minimal] {
+            assert!(text.contains("# Memory corruption in crossbeam-channel"));
+            assert!(text.contains("* ID: RUSTSEC-2020-0001"));
+            assert!(text.contains("* Severity: 9.8 (critical)"));
+        }
+        assert!(full.contains("* Url: https://example.com/RUSTSEC-2020-0001"));
+        assert!(full.contains("* Solution: Upgrade to >=0.5.15"));
+        assert!(full.contains("# Dependency Paths"));
+        assert!(!minimal.contains("* Url"));
+        assert!(!minimal.contains("* Solution"));
+        assert!(!minimal.contains("# Dependency Paths"));
+    }
+
+    #[test]
+    fn test_warning_severity() {
+        let result = warning_result(rustsec::WarningKind::Unmaintained);
+        assert_eq!(
+            result.severity(&AuditConfig::default()),
+            DiagnosticSeverity::WARNING
+        );
+        let config = AuditConfig {
+            warning_severity: crate::config::AuditWarningSeverity::Info,
+            ..Default::default()
+        };
+        assert_eq!(result.severity(&config), DiagnosticSeverity::INFORMATION);
+        assert_eq!(
+            into_diagnostic_severity(&[result], &config),
+            DiagnosticSeverity::INFORMATION
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_range() {
+        let doc = crate::usecase::Document::parse(
+            &into_file_uri(Path::new("/tmp/Cargo.toml")),
+            r#"
+[dependencies.dotenv]
+version = "0.15"
+features = ["clap"]
+"#,
+        );
+        let dep = doc.dependency("dependencies.dotenv").unwrap();
+        let entry = into_diagnostic_range(dep, &AuditConfig::default());
+        let key = into_diagnostic_range(
+            dep,
+            &AuditConfig {
+                diagnostic_range: AuditDiagnosticRange::Key,
+                ..Default::default()
+            },
+        );
+        assert_eq!(key.start.line, key.end.line);
+        assert!(entry.end.line > key.end.line);
+    }
+
+    #[test]
+    fn test_cvss() {
+        let (score, severity) = vuln_result().cvss().unwrap();
+        assert_eq!(score, Some(9.8));
+        assert_eq!(severity, AuditSeverity::Critical);
+        assert_eq!(vuln_result().score, Some(9.8));
+        assert_eq!(warning_result(rustsec::WarningKind::Yanked).cvss(), None);
+    }
+
+    #[tokio::test]
+    async fn test_audit_retry() {
+        const BACKOFF: &[Duration] = &[Duration::ZERO, Duration::ZERO];
+        let timed_out = || anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::TimedOut));
+        assert_eq!(retry_delay(&timed_out(), 1, BACKOFF), Some(Duration::ZERO));
+        assert_eq!(retry_delay(&timed_out(), 2, BACKOFF), None);
+        //permanent failures are not retried
+        assert_eq!(retry_delay(&anyhow::anyhow!("no Cargo.lock found"), 0, BACKOFF), None);
+
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut controller = AuditController::new(tx);
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let runner = move |uri: &Uri| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(timed_out());
+            }
+            Ok(AuditReports {
+                root: uri.clone(),
+                members: HashMap::new(),
+            })
+        };
+        controller.spawn_with(runner, || Duration::ZERO, BACKOFF);
+        let uri = into_file_uri(Path::new("/tmp/Cargo.toml"));
+        controller.send(&uri).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await;
+        assert!(matches!(event, Ok(Some(CargoDocumentEvent::Audited(_)))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_audit_retry_cancelled_on_close() {
+        const BACKOFF: &[Duration] = &[Duration::from_millis(100), Duration::from_millis(100)];
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut controller = AuditController::new(tx);
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let runner = move |_: &Uri| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Err(anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::TimedOut)))
+        };
+        controller.spawn_with(runner, || Duration::ZERO, BACKOFF);
+        let uri = into_file_uri(Path::new("/tmp/ws/Cargo.toml"));
+        controller.opened(&uri).await.unwrap();
+        controller.send(&uri).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        //the loop still receives events while the retry is pending
+        controller.closed(&uri).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_millis(300), rx.recv()).await;
+        assert!(event.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_audit_fix_args() {
+        assert_eq!(
+            audit_fix_args(Some(("time", "0.2.23"))),
+            vec!["update", "-p", "time", "--precise", "0.2.23"]
+        );
+        assert_eq!(audit_fix_args(None), vec!["audit", "fix"]);
+    }
+
+    #[tokio::test]
+    async fn test_audit_debounce() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut controller = AuditController::new(tx);
+        let runner = |uri: &Uri| {
+            Ok(AuditReports {
+                root: uri.clone(),
+                members: HashMap::new(),
+            })
+        };
+        controller.spawn_with(runner, || Duration::from_millis(20), AUDIT_BACKOFF);
+        let uri = into_file_uri(Path::new("/tmp/Cargo.toml"));
+        controller.send(&uri).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await;
+        let Ok(Some(CargoDocumentEvent::Audited(reports))) = event else {
+            panic!("audit didn't run within the debounce window");
+        };
+        assert_eq!(reports.root, uri);
+    }
+
+    #[tokio::test]
+    async fn test_audit_cancelled_on_close() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut controller = AuditController::new(tx);
+        let runner = |uri: &Uri| {
+            Ok(AuditReports {
+                root: uri.clone(),
+                members: HashMap::new(),
+            })
+        };
+        controller.spawn_with(runner, || Duration::from_millis(50), AUDIT_BACKOFF);
+        let root = into_file_uri(Path::new("/tmp/ws/Cargo.toml"));
+        let member = into_file_uri(Path::new("/tmp/ws/member/Cargo.toml"));
+        controller.opened(&root).await.unwrap();
+        controller.opened(&member).await.unwrap();
+        controller.workspace(&member, &root).await.unwrap();
+        controller.send(&member).await.unwrap();
+        //the root is still opened
+        controller.closed(&member).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await;
+        assert!(matches!(event, Ok(Some(CargoDocumentEvent::Audited(_)))));
+
+        controller.send(&root).await.unwrap();
+        controller.closed(&root).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_millis(200), rx.recv()).await;
+        assert!(event.is_err(), "the audit ran after the last document was closed");
+    }
+
+    #[tokio::test]
+    async fn test_audit_nested_workspace_closed() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut controller = AuditController::new(tx);
+        let runner = |uri: &Uri| {
+            Ok(AuditReports {
+                root: uri.clone(),
+                members: HashMap::new(),
+            })
+        };
+        controller.spawn_with(runner, || Duration::from_millis(50), AUDIT_BACKOFF);
+        //an independent workspace nested in the directory of another
+        let outer = into_file_uri(Path::new("/tmp/ws/Cargo.toml"));
+        let nested = into_file_uri(Path::new("/tmp/ws/nested/Cargo.toml"));
+        controller.opened(&outer).await.unwrap();
+        controller.opened(&nested).await.unwrap();
+        controller.workspace(&outer, &outer).await.unwrap();
+        controller.workspace(&nested, &nested).await.unwrap();
+        controller.send(&nested).await.unwrap();
+        controller.closed(&nested).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_millis(200), rx.recv()).await;
+        assert!(event.is_err(), "the outer workspace kept the nested audit");
+    }
+
+    #[test]
+    fn test_ignore_advisory() {
+        let ignore = vec!["RUSTSEC-2024-0436".to_string(), "dotenv:unmaintained".to_string()];
+        assert!(is_ignored(&ignore, Some("RUSTSEC-2024-0436"), "paste", "unmaintained"));
+        assert!(!is_ignored(&ignore, Some("RUSTSEC-2021-0141"), "paste", "unmaintained"));
+        //warnings without an id are ignored by crate and kind
+        assert!(is_ignored(&ignore, None, "dotenv", "unmaintained"));
+        assert!(!is_ignored(&ignore, None, "dotenv", "yanked"));
+        //the kind is matched against the rustsec warning kind
+        let kind = rustsec::WarningKind::Unmaintained.to_string();
+        assert!(is_ignored(&ignore, None, "dotenv", &kind));
+        //kept results are filtered again when the config changes
+        assert!(!vuln_result().is_ignored(&ignore));
+        assert!(vuln_result().is_ignored(&["RUSTSEC-2020-0001".to_string()]));
+    }
+
+    #[test]
+    fn test_autofix_min_severity() {
+        //2.5 low, 7.5 high
+        let low = vuln_result_with_cvss("CVSS:3.1/AV:L/AC:H/PR:L/UI:N/S:U/C:L/I:N/A:N");
+        let high = vuln_result_with_cvss("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:N/A:N");
+        assert_eq!(low.cvss().unwrap().1, AuditSeverity::Low);
+        assert_eq!(high.cvss().unwrap().1, AuditSeverity::High);
+        let min = AuditConfig {
+            autofix_min_severity: AuditSeverity::High,
+            ..Default::default()
+        }
+        .autofix_min_severity;
+        assert!(!low.is_autofixable(min));
+        assert!(high.is_autofixable(min));
+        //every vulnerability gets a quickfix by default
+        assert!(low.is_autofixable(AuditConfig::default().autofix_min_severity));
+    }
 }
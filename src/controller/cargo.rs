@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     path::Path,
     task::Poll,
 };
@@ -8,38 +8,105 @@ use cargo::{
     core::{
         compiler::{CompileKind, RustcTargetData},
         dependency::DepKind,
-        resolver::{CliFeatures, ForceAllTargets, HasDevUnits},
+        resolver::{CliFeatures, ForceAllTargets, HasDevUnits, Resolve},
         Package, PackageId, SourceId, Summary, Workspace,
     },
     ops::{
         tree::{DisplayDepth, EdgeKind, Prefix, Target, TreeOptions},
         Packages,
     },
-    sources::source::{QueryKind, Source},
+    sources::{
+        source::{QueryKind, Source},
+        IndexSummary, SourceConfigMap,
+    },
     util::{cache_lock::CacheLockMode, OptVersionReq},
     GlobalContext,
 };
+use serde::Serialize;
 use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
 use tracing::{error, info};
 
 use crate::entity::{
-    cargo_dependency_to_toml_key, from_resolve_error, CargoError, CargoErrorKind, Dependency,
-    SymbolTree, TomlNode,
+    cargo_dependency_to_toml_key, from_resolve_error, is_unpublishable, match_versions,
+    CargoError, CargoErrorKind, Dependency, SymbolTree, TomlNode,
 };
 
-use super::appraiser::Ctx;
+use super::{appraiser::Ctx, registry_cache::cached_summaries};
 
 pub struct CargoResolveOutput {
     pub ctx: Ctx,
     //the hashmap key is toml_id, which is<table>:<package name>
     pub dependencies: HashMap<String, Package>,
     pub summaries: HashMap<String, Vec<Summary>>,
+    //package names whose summaries come from the local index cache, which has no features
+    pub cached_summaries: HashSet<String>,
+    //package name -> yanked versions, only the resolved versions are queried for yanked
+    pub yanked: HashMap<String, Vec<semver::Version>>,
+    //package name -> versions sorted descending, for packages resolved at multiple versions
+    pub duplicates: HashMap<String, Vec<semver::Version>>,
+    //package names declared in the manifest but missing from the Cargo.lock read before resolving,
+    //empty if there was no lockfile
+    pub unlocked: HashSet<String>,
+    //toml_id -> features activated in the resolve, sorted
+    pub enabled_features: HashMap<String, Vec<String>>,
+    //toml_id -> the replacement of the dependency's source, only for replaced sources
+    pub replaced_sources: HashMap<String, SourceId>,
+    //package name -> names of the packages depending on it, empty unless requested
+    pub reverse_deps: HashMap<String, BTreeSet<String>>,
+}
+
+//offline resolves read Cargo.lock and the local cache, summaries and yanked versions are left empty
+impl CargoResolveOutput {
+    //move the resolve result of a dependency out of the output
+    pub fn populate(&mut self, dep: &mut Dependency) {
+        dep.resolved = self.dependencies.remove(&dep.toml_key());
+        dep.enabled_features = self
+            .enabled_features
+            .remove(&dep.toml_key())
+            .unwrap_or_default();
+        dep.replaced_source = self.replaced_sources.remove(&dep.toml_key());
+        dep.dup_count = self.duplicates.get(dep.package_name()).map(|v| v.len());
+        dep.unpublishable = dep.resolved.as_ref().is_some_and(is_unpublishable);
+        //offline resolves don't query the registry, only the installed state is known
+        let Some(mut summaries) = self.summaries.remove(dep.package_name()) else {
+            return;
+        };
+        dep.summaries_from_cache = self.cached_summaries.contains(dep.package_name());
+        //the registry was queried, no entry means nothing is yanked
+        dep.yanked_versions =
+            Some(self.yanked.get(dep.package_name()).cloned().unwrap_or_default());
+        if let (Some(resolved), Some(unresolved)) =
+            (dep.resolved.as_ref(), dep.unresolved.as_ref())
+        {
+            //order summaries by version
+            summaries.sort_by(|a, b| b.version().cmp(a.version()));
+            //clear matched result from previous resolve
+            let matched = match_versions(
+                &summaries,
+                |s| s.version(),
+                resolved.version(),
+                unresolved.version_req(),
+            );
+            dep.matched_summary = matched.installed;
+            dep.latest_matched_summary = matched.latest_matched;
+            dep.latest_summary = matched.latest;
+            dep.latest_stable_summary = matched.latest_stable;
+            dep.latest_prerelease_summary = matched.latest_prerelease;
+            dep.summaries = Some(summaries);
+        };
+    }
 }
 
 #[tracing::instrument(name = "cargo_resolve", level = "trace")]
-pub async fn cargo_resolve(ctx: &Ctx) -> Result<CargoResolveOutput, CargoError> {
+//`reverse_deps` collects the dependents of every package in the resolve graph
+pub async fn cargo_resolve(
+    ctx: &Ctx,
+    offline: bool,
+    reverse_deps: bool,
+) -> Result<CargoResolveOutput, CargoError> {
     let path = Path::new(ctx.uri.path().as_str());
-    let gctx = cargo::util::context::GlobalContext::default().map_err(CargoError::resolve_error)?;
+    let gctx =
+        new_gctx(offline, path.parent().unwrap_or(path)).map_err(CargoError::resolve_error)?;
     let workspace =
         cargo::core::Workspace::new(path, &gctx).map_err(CargoError::workspace_error)?;
     let Ok(current) = workspace.current() else {
@@ -48,7 +115,17 @@ pub async fn cargo_resolve(ctx: &Ctx) -> Result<CargoResolveOutput, CargoError>
         )));
     };
     let deps = current.dependencies();
+    //the resolve writes the updated lockfile, read the lockfile on disk first
+    let unlocked = match cargo::ops::load_pkg_lockfile(&workspace) {
+        Ok(Some(lock)) => unlocked_packages(deps.iter().map(|d| d.package_name().as_str()), &lock),
+        Ok(None) => HashSet::new(),
+        Err(e) => {
+            error!("failed to load Cargo.lock: {}", e);
+            HashSet::new()
+        }
+    };
 
+    //dev-dependencies of tests, examples and benches are resolved and decorated like the others
     let mut edge_kinds = HashSet::with_capacity(3);
     edge_kinds.insert(EdgeKind::Dep(DepKind::Normal));
     edge_kinds.insert(EdgeKind::Dep(DepKind::Development));
@@ -98,43 +175,299 @@ pub async fn cargo_resolve(ctx: &Ctx) -> Result<CargoResolveOutput, CargoError>
         .map(|pkg| (pkg.package_id(), pkg))
         .collect();
 
+    let sources = SourceConfigMap::new(&gctx).map_err(CargoError::resolve_error)?;
     let mut res = HashMap::with_capacity(deps.len());
+    let mut enabled_features = HashMap::with_capacity(deps.len());
+    let mut replaced_sources = HashMap::new();
     for dep in deps {
         if let Some(pkg) = package_map.values().find(|&pkg| dep.matches(pkg.summary())) {
             let toml_key = cargo_dependency_to_toml_key(dep);
+            //features are unified over every dependent in the resolve
+            let mut features: Vec<String> = ws_resolve
+                .targeted_resolve
+                .features(pkg.package_id())
+                .iter()
+                .map(|f| f.to_string())
+                .collect();
+            features.sort();
+            enabled_features.insert(toml_key.clone(), features);
+            if let Some(replaced) = replaced_source(&sources, dep.source_id()) {
+                replaced_sources.insert(toml_key.clone(), replaced);
+            }
             res.insert(toml_key, (*pkg).clone());
         }
     }
+    let duplicates = duplicate_versions(
+        ws_resolve
+            .targeted_resolve
+            .iter()
+            .map(|id| (id.name().to_string(), id.version().clone())),
+    );
+    let reverse_deps = if reverse_deps {
+        reverse_dependencies(&ws_resolve.targeted_resolve)
+    } else {
+        HashMap::new()
+    };
+    //the registry skips yanked versions unless they are whitelisted,
+    //whitelist the resolved versions to know whether they are yanked
+    let yanked_whitelist: HashSet<PackageId> = ws_resolve.targeted_resolve.iter().collect();
+    let (summaries, cached_summaries, yanked) = if gctx.offline() {
+        (HashMap::new(), HashSet::new(), HashMap::new())
+    } else {
+        summaries_map(&gctx, &sources, &workspace, &yanked_whitelist)
+    };
     Ok(CargoResolveOutput {
         ctx: ctx.clone(),
         dependencies: res,
-        summaries: summaries_map(&gctx, &workspace),
+        summaries,
+        cached_summaries,
+        yanked,
+        duplicates,
+        unlocked,
+        enabled_features,
+        replaced_sources,
+        reverse_deps,
     })
 }
 
-//TODO the current Vec<Summary> didn't include yanked
-fn summaries_map(gctx: &GlobalContext, workspace: &Workspace) -> HashMap<String, Vec<Summary>> {
+fn unlocked_packages<'a>(
+    declared: impl Iterator<Item = &'a str>,
+    lock: &cargo::core::Resolve,
+) -> HashSet<String> {
+    let locked: HashSet<&str> = lock.iter().map(|id| id.name().as_str()).collect();
+    declared
+        .filter(|name| !locked.contains(name))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+//an offline context keeps cargo itself from touching the network,
+//the context loads `[registries]`, `[source]` and credential providers from `.cargo/config.toml`
+//and `CARGO_REGISTRIES_*` lazily, the same as the cargo cli run in the manifest directory
+pub fn new_gctx(offline: bool, manifest_dir: &Path) -> Result<GlobalContext, anyhow::Error> {
+    let mut gctx = GlobalContext::default()?;
+    //the server's working directory may be outside the workspace
+    gctx.reload_rooted_at(manifest_dir)?;
+    //`net.offline` of the workspace's own `.cargo/config.toml` is only known after the reload
+    if offline || net_offline(&gctx) {
+        gctx.configure(0, false, None, false, false, true, &None, &[], &[])?;
+    }
+    Ok(gctx)
+}
+
+//`CARGO_NET_OFFLINE=true` or `net.offline = true` in `.cargo/config.toml`,
+//the same as passing `--offline` to every cargo command
+pub fn cargo_net_offline() -> bool {
+    GlobalContext::default().is_ok_and(|gctx| net_offline(&gctx))
+}
+
+fn net_offline(gctx: &GlobalContext) -> bool {
+    gctx.get::<Option<bool>>("net.offline")
+        .ok()
+        .flatten()
+        .unwrap_or(false)
+}
+
+//the direct parents of each package, a package at several versions merges their parents
+fn reverse_dependencies(resolve: &Resolve) -> HashMap<String, BTreeSet<String>> {
+    let mut res: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for parent in resolve.iter() {
+        for (child, _) in resolve.deps(parent) {
+            res.entry(child.name().to_string())
+                .or_default()
+                .insert(parent.name().to_string());
+        }
+    }
+    res
+}
+
+//the source `[source.<name>] replace-with` redirects queries to, None if not replaced
+fn replaced_source(sources: &SourceConfigMap, source_id: SourceId) -> Option<SourceId> {
+    if source_id.is_path() {
+        return None;
+    }
+    let replaced = sources
+        .load(source_id, &HashSet::new())
+        .ok()?
+        .replaced_source_id();
+    (replaced != source_id).then_some(replaced)
+}
+
+//package name -> the versions in the resolve graph sorted descending,
+//only packages with more than one version
+pub fn duplicate_versions(
+    ids: impl Iterator<Item = (String, semver::Version)>,
+) -> HashMap<String, Vec<semver::Version>> {
+    let mut versions: HashMap<String, BTreeSet<semver::Version>> = HashMap::new();
+    for (name, version) in ids {
+        versions.entry(name).or_default().insert(version);
+    }
+    versions
+        .into_iter()
+        .filter(|(_, v)| v.len() > 1)
+        .map(|(name, v)| (name, v.into_iter().rev().collect()))
+        .collect()
+}
+
+//package name -> version -> activated features
+pub type ResolvedFeatures = BTreeMap<String, BTreeMap<String, BTreeSet<String>>>;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureImpact {
+    pub name: String,
+    pub versions_before: Vec<String>,
+    pub versions_after: Vec<String>,
+    pub features_added: Vec<String>,
+    pub features_removed: Vec<String>,
+}
+
+//compare the default resolve with the resolve that enables `features`,
+//report every package whose versions or activated features changed because of feature unification
+pub fn feature_impact(
+    path: &Path,
+    features: &[String],
+    offline: bool,
+) -> Result<Vec<FeatureImpact>, CargoError> {
+    let gctx =
+        new_gctx(offline, path.parent().unwrap_or(path)).map_err(CargoError::resolve_error)?;
+    let workspace =
+        cargo::core::Workspace::new(path, &gctx).map_err(CargoError::workspace_error)?;
+    let before = resolved_features(&workspace, &CliFeatures::new_all(false))?;
+    let enabled = CliFeatures::from_command_line(features, false, true)
+        .map_err(CargoError::resolve_error)?;
+    let after = resolved_features(&workspace, &enabled)?;
+    Ok(diff_resolved_features(&before, &after))
+}
+
+fn resolved_features(
+    workspace: &Workspace,
+    cli_features: &CliFeatures,
+) -> Result<ResolvedFeatures, CargoError> {
+    let requested_kinds = CompileKind::from_requested_targets(workspace.gctx(), &[])
+        .map_err(CargoError::resolve_error)?;
+    let mut target_data = RustcTargetData::new(workspace, &[CompileKind::Host])
+        .map_err(CargoError::resolve_error)?;
+    let specs = Packages::Default
+        .to_package_id_specs(workspace)
+        .map_err(CargoError::resolve_error)?;
+    let ws_resolve = cargo::ops::resolve_ws_with_opts(
+        workspace,
+        &mut target_data,
+        &requested_kinds,
+        cli_features,
+        &specs,
+        HasDevUnits::Yes,
+        ForceAllTargets::No,
+        false,
+    )
+    .map_err(from_resolve_error)?;
+    let resolve = &ws_resolve.targeted_resolve;
+    let mut res = ResolvedFeatures::new();
+    for id in resolve.iter() {
+        res.entry(id.name().to_string()).or_default().insert(
+            id.version().to_string(),
+            resolve.features(id).iter().map(|f| f.to_string()).collect(),
+        );
+    }
+    Ok(res)
+}
+
+pub fn diff_resolved_features(
+    before: &ResolvedFeatures,
+    after: &ResolvedFeatures,
+) -> Vec<FeatureImpact> {
+    let empty = BTreeMap::new();
+    let names: BTreeSet<_> = before.keys().chain(after.keys()).collect();
+    let mut res = Vec::new();
+    for name in names {
+        let b = before.get(name).unwrap_or(&empty);
+        let a = after.get(name).unwrap_or(&empty);
+        if a == b {
+            continue;
+        }
+        let b_features: BTreeSet<_> = b.values().flatten().collect();
+        let a_features: BTreeSet<_> = a.values().flatten().collect();
+        res.push(FeatureImpact {
+            name: name.to_string(),
+            versions_before: b.keys().cloned().collect(),
+            versions_after: a.keys().cloned().collect(),
+            features_added: a_features
+                .difference(&b_features)
+                .map(|f| f.to_string())
+                .collect(),
+            features_removed: b_features
+                .difference(&a_features)
+                .map(|f| f.to_string())
+                .collect(),
+        });
+    }
+    res
+}
+
+//the summaries exclude yanked versions, yanked versions are returned separately
+//summaries read from the local index cache are listed by package name
+#[allow(clippy::type_complexity)]
+fn summaries_map(
+    gctx: &GlobalContext,
+    sources: &SourceConfigMap,
+    workspace: &Workspace,
+    yanked_whitelist: &HashSet<PackageId>,
+) -> (
+    HashMap<String, Vec<Summary>>,
+    HashSet<String>,
+    HashMap<String, Vec<semver::Version>>,
+) {
     let Ok(_guard) = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive) else {
         error!("failed to acquire package cache lock");
-        return HashMap::new();
+        return (HashMap::new(), HashSet::new(), HashMap::new());
     };
 
     let mut res = HashMap::new();
+    let mut cached = HashSet::new();
+    let mut yanked_res = HashMap::new();
 
     // Step 1: Group dependencies by SourceId
     let mut source_deps: HashMap<SourceId, Vec<_>> = HashMap::new();
 
     for member in workspace.members() {
         for dep in member.dependencies() {
+            //sysroot crates can't be queried from any registry
+            if is_sysroot_crate(dep.package_name().as_str(), dep.source_id()) {
+                continue;
+            }
             source_deps.entry(dep.source_id()).or_default().push(dep);
         }
     }
 
     // Step 2: Process each source
     for (source_id, package_names) in source_deps {
-        let mut source = source_id.load(gctx, &HashSet::new()).unwrap();
+        //a replaced source queries the vendored directory or the mirror
+        let mut source = match sources.load(source_id, yanked_whitelist) {
+            Ok(source) => source,
+            Err(e) => {
+                error!("failed to load source {}: {}", source_id, e);
+                continue;
+            }
+        };
         source.invalidate_cache();
-        source.block_until_ready().unwrap();
+        if let Err(e) = source.block_until_ready() {
+            error!("failed to update source {}, read the local index cache: {}", source_id, e);
+            let index_root = gctx.home().as_path_unlocked().join("registry").join("index");
+            for dep in &package_names {
+                let name = dep.package_name().to_string();
+                let (sums, yanked) = cached_summaries(&index_root, source_id, &name);
+                if sums.is_empty() {
+                    continue;
+                }
+                cached.insert(name.clone());
+                res.insert(name.clone(), sums);
+                if !yanked.is_empty() {
+                    yanked_res.insert(name, yanked);
+                }
+            }
+            continue;
+        }
         let mut summaries = Vec::new();
         for dep in &package_names {
             let mut any_dep = (*dep).clone();
@@ -147,21 +480,63 @@ fn summaries_map(gctx: &GlobalContext, workspace: &Workspace) -> HashMap<String,
             match summary {
                 Poll::Ready(summaries) => {
                     let summaries = summaries.unwrap();
-                    let mut sums = Vec::new();
-                    let mut package_name = "".to_string();
-                    //map summaries to d.as_summary()
-                    for summary in &summaries {
-                        let name = summary.as_summary().name().to_string();
-                        package_name = name;
-                        sums.push(summary.as_summary().clone());
+                    let Some(package_name) = summaries
+                        .first()
+                        .map(|s| s.as_summary().name().to_string())
+                    else {
+                        continue;
+                    };
+                    let (sums, yanked) = partition_yanked(summaries);
+                    res.insert(package_name.to_string(), sums);
+                    if !yanked.is_empty() {
+                        yanked_res.insert(package_name, yanked);
                     }
-                    res.insert(package_name, sums);
                 }
                 Poll::Pending => unreachable!(),
             }
         }
     }
-    res
+    (res, cached, yanked_res)
+}
+
+//split the available summaries from the yanked versions
+pub fn partition_yanked(summaries: Vec<IndexSummary>) -> (Vec<Summary>, Vec<semver::Version>) {
+    let mut available = Vec::with_capacity(summaries.len());
+    let mut yanked = Vec::new();
+    for summary in summaries {
+        match summary {
+            IndexSummary::Yanked(s) => yanked.push(s.version().clone()),
+            s => available.push(s.into_summary()),
+        }
+    }
+    (available, yanked)
+}
+
+const SYSROOT_CRATES: [&str; 10] = [
+    "std",
+    "core",
+    "alloc",
+    "proc_macro",
+    "test",
+    "panic_abort",
+    "panic_unwind",
+    "compiler_builtins",
+    "rustc-std-workspace-core",
+    "rustc-std-workspace-alloc",
+];
+
+//crates from the rust-src component used by `build-std`
+pub fn is_sysroot_crate(name: &str, source_id: SourceId) -> bool {
+    if !SYSROOT_CRATES.contains(&name) {
+        return false;
+    }
+    if !source_id.is_path() {
+        return false;
+    }
+    source_id
+        .url()
+        .path()
+        .contains("/lib/rustlib/src/rust/library")
 }
 
 pub fn resolve_package_with_default_source(
@@ -195,7 +570,7 @@ impl CargoError {
         tree: &SymbolTree,
     ) -> Option<Vec<(String, Diagnostic)>> {
         match &self.kind {
-            CargoErrorKind::NoMatchingPackage(_) => Some(
+            CargoErrorKind::NoMatchingPackage(_) | CargoErrorKind::AuthFailed(_, _) => Some(
                 keys.iter()
                     .map(|key| {
                         (
@@ -299,3 +674,374 @@ impl CargoError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+        path::Path,
+    };
+
+    use cargo::{
+        core::{PackageId, SourceId, SourceKind, Summary},
+        sources::IndexSummary,
+        util::IntoUrl,
+        GlobalContext,
+    };
+
+    use super::{
+        cargo_resolve, diff_resolved_features, duplicate_versions, is_sysroot_crate, net_offline,
+        new_gctx, partition_yanked, ResolvedFeatures,
+    };
+    use tower_lsp::lsp_types::DiagnosticSeverity;
+
+    use crate::{
+        controller::{appraiser::Ctx, lint::lint_unlocked},
+        decoration::{version_decoration, VersionDecorationKind},
+        entity::into_file_uri,
+        fixture::Fixture,
+        usecase::Document,
+    };
+
+    #[tokio::test]
+    async fn test_offline_resolve() {
+        let fixture = Fixture::new(&[
+            (
+                "Cargo.toml",
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+                [dependencies]\nutil = { path = \"util\" }\n",
+            ),
+            ("src/lib.rs", ""),
+            (
+                "util/Cargo.toml",
+                "[package]\nname = \"util\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            ),
+            ("util/src/lib.rs", ""),
+        ]);
+        let root = fixture.root();
+
+        let ctx = Ctx {
+            uri: into_file_uri(&root.join("Cargo.toml")),
+            rev: 0,
+        };
+        let output = cargo_resolve(&ctx, true, false).await.unwrap();
+        assert_eq!(output.dependencies.len(), 1);
+        //the registry is never queried offline
+        assert!(output.summaries.is_empty());
+        assert!(output.yanked.is_empty());
+    }
+
+    #[test]
+    fn test_cargo_net_offline() {
+        let mut gctx = GlobalContext::default().unwrap();
+        gctx.set_env(HashMap::from([("CARGO_NET_OFFLINE".to_string(), "false".to_string())]));
+        assert!(!net_offline(&gctx));
+        gctx.set_env(HashMap::from([("CARGO_NET_OFFLINE".to_string(), "true".to_string())]));
+        assert!(net_offline(&gctx));
+
+        //the resolving context itself is offline
+        let fixture = Fixture::new(&[]);
+        assert!(new_gctx(true, fixture.root()).unwrap().offline());
+        assert!(!new_gctx(false, fixture.root()).unwrap().offline());
+    }
+
+    #[tokio::test]
+    async fn test_net_offline_resolve() {
+        let fixture = Fixture::new(&[
+            (".cargo/config.toml", "[net]\noffline = true\n"),
+            (
+                "Cargo.toml",
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+                [dependencies]\nutil = { path = \"util\" }\n",
+            ),
+            ("src/lib.rs", ""),
+            (
+                "util/Cargo.toml",
+                "[package]\nname = \"util\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            ),
+            ("util/src/lib.rs", ""),
+        ]);
+        let root = fixture.root();
+        //`net.offline` wins over an online request
+        assert!(new_gctx(false, root).unwrap().offline());
+
+        let ctx = Ctx {
+            uri: into_file_uri(&root.join("Cargo.toml")),
+            rev: 0,
+        };
+        let output = cargo_resolve(&ctx, false, false).await.unwrap();
+        assert_eq!(output.dependencies.len(), 1);
+        //the registry is never queried
+        assert!(output.summaries.is_empty());
+        assert!(output.cached_summaries.is_empty());
+        assert!(output.yanked.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unlocked_dependencies() {
+        //`helper` was added after the lockfile was written
+        let text = "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+            [dependencies]\nutil = { path = \"util\" }\nhelper = { path = \"helper\" }\n";
+        let fixture = Fixture::new(&[
+            ("Cargo.toml", text),
+            ("src/lib.rs", ""),
+            ("util/Cargo.toml", "[package]\nname = \"util\"\nversion = \"0.1.0\"\n"),
+            ("util/src/lib.rs", ""),
+            ("helper/Cargo.toml", "[package]\nname = \"helper\"\nversion = \"0.1.0\"\n"),
+            ("helper/src/lib.rs", ""),
+            (
+                "Cargo.lock",
+                "version = 4\n\n[[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\
+                dependencies = [\n \"util\",\n]\n\n\
+                [[package]]\nname = \"util\"\nversion = \"0.1.0\"\n",
+            ),
+        ]);
+        let root = fixture.root();
+
+        let uri = into_file_uri(&root.join("Cargo.toml"));
+        let output = cargo_resolve(&Ctx { uri: uri.clone(), rev: 0 }, true, false)
+            .await
+            .unwrap();
+        assert_eq!(output.unlocked, HashSet::from(["helper".to_string()]));
+
+        let mut doc = Document::parse(&uri, text);
+        doc.populate_dependencies();
+        let diags = lint_unlocked(&doc, &output.unlocked);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].0, "dependencies.helper.unlocked");
+        assert_eq!(diags[0].1.severity, Some(DiagnosticSeverity::WARNING));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_dev_dependencies() {
+        //`helper` is only used by examples
+        let text = "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+            [dependencies]\nutil = { path = \"util\" }\n\n\
+            [dev-dependencies]\nhelper = { path = \"helper\" }\n";
+        let fixture = Fixture::new(&[
+            ("Cargo.toml", text),
+            ("src/lib.rs", ""),
+            ("util/Cargo.toml", "[package]\nname = \"util\"\nversion = \"0.1.0\"\n"),
+            ("util/src/lib.rs", ""),
+            ("helper/Cargo.toml", "[package]\nname = \"helper\"\nversion = \"0.1.0\"\n"),
+            ("helper/src/lib.rs", ""),
+        ]);
+        let root = fixture.root();
+
+        let uri = into_file_uri(&root.join("Cargo.toml"));
+        let mut output = cargo_resolve(&Ctx { uri: uri.clone(), rev: 0 }, true, false)
+            .await
+            .unwrap();
+        let mut doc = Document::parse(&uri, text);
+        doc.populate_dependencies();
+        for dep in doc.dependencies.values_mut() {
+            output.populate(dep);
+        }
+        let normal = &doc.dependencies["dependencies.util"];
+        let dev = &doc.dependencies["dev-dependencies.helper"];
+        assert_eq!(dev.toml_key(), "dev-dependencies:helper:");
+        assert!(dev.resolved.is_some());
+        assert_eq!(version_decoration(dev).kind, version_decoration(normal).kind);
+        assert_eq!(version_decoration(dev).kind, VersionDecorationKind::Local);
+
+        let resolved = doc.to_resolved_manifest();
+        assert!(resolved.starts_with(
+            "[dependencies]\nutil = { version = \"0.1.0\", source = \"path+file://"
+        ));
+        assert!(resolved.contains("\n\n[dev-dependencies]\nhelper = { version = \"0.1.0\""));
+    }
+
+    #[tokio::test]
+    async fn test_unified_features() {
+        let text = "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+            [dependencies]\nutil = { path = \"util\", features = [\"x\"] }\n\
+            helper = { path = \"helper\" }\n";
+        let fixture = Fixture::new(&[
+            ("Cargo.toml", text),
+            ("src/lib.rs", ""),
+            (
+                "util/Cargo.toml",
+                "[package]\nname = \"util\"\nversion = \"0.1.0\"\n\n\
+                [features]\nx = []\ny = []\nz = []\n",
+            ),
+            ("util/src/lib.rs", ""),
+            //`helper` enables `y` on `util` for `app`
+            (
+                "helper/Cargo.toml",
+                "[package]\nname = \"helper\"\nversion = \"0.1.0\"\n\n\
+                [dependencies]\nutil = { path = \"../util\", features = [\"y\"] }\n",
+            ),
+            ("helper/src/lib.rs", ""),
+        ]);
+        let root = fixture.root();
+
+        let uri = into_file_uri(&root.join("Cargo.toml"));
+        let mut output = cargo_resolve(&Ctx { uri: uri.clone(), rev: 0 }, true, false)
+            .await
+            .unwrap();
+        let mut doc = Document::parse(&uri, text);
+        doc.populate_dependencies();
+        for dep in doc.dependencies.values_mut() {
+            output.populate(dep);
+        }
+        assert_eq!(doc.dependencies["dependencies.util"].enabled_features, vec!["x", "y"]);
+        assert!(doc.dependencies["dependencies.helper"].enabled_features.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reverse_deps() {
+        let fixture = Fixture::new(&[
+            (
+                "Cargo.toml",
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+                [dependencies]\nutil = { path = \"util\" }\nhelper = { path = \"helper\" }\n",
+            ),
+            ("src/lib.rs", ""),
+            ("util/Cargo.toml", "[package]\nname = \"util\"\nversion = \"0.1.0\"\n"),
+            ("util/src/lib.rs", ""),
+            (
+                "helper/Cargo.toml",
+                "[package]\nname = \"helper\"\nversion = \"0.1.0\"\n\n\
+                [dependencies]\nutil = { path = \"../util\" }\n",
+            ),
+            ("helper/src/lib.rs", ""),
+        ]);
+        let root = fixture.root();
+
+        let ctx = Ctx {
+            uri: into_file_uri(&root.join("Cargo.toml")),
+            rev: 0,
+        };
+        let output = cargo_resolve(&ctx, true, true).await.unwrap();
+        assert_eq!(
+            output.reverse_deps["util"],
+            BTreeSet::from(["app".to_string(), "helper".to_string()])
+        );
+        assert_eq!(output.reverse_deps["helper"], BTreeSet::from(["app".to_string()]));
+        assert!(!output.reverse_deps.contains_key("app"));
+        //the graph isn't walked unless asked
+        let output = cargo_resolve(&ctx, true, false).await.unwrap();
+        assert!(output.reverse_deps.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_source_replacement() {
+        let text = "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+            [dependencies]\nfoo = \"0.1\"\n";
+        let fixture = Fixture::new(&[
+            ("Cargo.toml", text),
+            ("src/lib.rs", ""),
+            (
+                ".cargo/config.toml",
+                "[source.crates-io]\nreplace-with = \"vendored\"\n\n\
+                [source.vendored]\ndirectory = \"vendor\"\n",
+            ),
+            ("vendor/foo/Cargo.toml", "[package]\nname = \"foo\"\nversion = \"0.1.2\"\n"),
+            ("vendor/foo/src/lib.rs", ""),
+            ("vendor/foo/.cargo-checksum.json", "{\"files\":{}}"),
+        ]);
+        let root = fixture.root();
+
+        //the vendor directory is queried instead of crates.io, nothing goes to the network
+        let uri = into_file_uri(&root.join("Cargo.toml"));
+        let mut output = cargo_resolve(&Ctx { uri: uri.clone(), rev: 0 }, false, false)
+            .await
+            .unwrap();
+        let mut doc = Document::parse(&uri, text);
+        doc.populate_dependencies();
+        for dep in doc.dependencies.values_mut() {
+            output.populate(dep);
+        }
+        let foo = &doc.dependencies["dependencies.foo"];
+        let resolved = foo.resolved.as_ref().unwrap();
+        assert_eq!(resolved.version().to_string(), "0.1.2");
+        assert!(resolved.package_id().source_id().is_crates_io());
+        assert_eq!(foo.replaced_source.unwrap().kind(), &SourceKind::Directory);
+        assert_eq!(foo.matched_summary.as_ref().unwrap().version().to_string(), "0.1.2");
+    }
+
+    #[test]
+    fn test_partition_yanked() {
+        let source_id =
+            SourceId::for_registry(&"https://example.com/index".into_url().unwrap()).unwrap();
+        let summary = |version: &str| {
+            let id = PackageId::try_new("foo", version, source_id).unwrap();
+            Summary::new(id, vec![], &BTreeMap::new(), None::<&str>, None).unwrap()
+        };
+        let summaries = vec![
+            IndexSummary::Candidate(summary("0.1.0")),
+            IndexSummary::Yanked(summary("0.1.1")),
+            IndexSummary::Candidate(summary("0.1.2")),
+        ];
+        let (available, yanked) = partition_yanked(summaries);
+        let available: Vec<String> = available.iter().map(|s| s.version().to_string()).collect();
+        assert_eq!(available, vec!["0.1.0", "0.1.2"]);
+        assert_eq!(yanked, vec![semver::Version::new(0, 1, 1)]);
+    }
+
+    #[test]
+    fn test_duplicate_versions() {
+        let ids = [
+            ("syn", "1.0.109"),
+            ("syn", "2.0.87"),
+            ("syn", "2.0.87"),
+            ("serde", "1.0.210"),
+        ]
+        .into_iter()
+        .map(|(n, v)| (n.to_string(), semver::Version::parse(v).unwrap()));
+        let duplicates = duplicate_versions(ids);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(
+            duplicates["syn"],
+            vec![
+                semver::Version::parse("2.0.87").unwrap(),
+                semver::Version::parse("1.0.109").unwrap()
+            ]
+        );
+    }
+
+    fn resolved(packages: &[(&str, &str, &[&str])]) -> ResolvedFeatures {
+        let mut res = ResolvedFeatures::new();
+        for (name, version, features) in packages {
+            res.entry(name.to_string()).or_insert_with(BTreeMap::new).insert(
+                version.to_string(),
+                features.iter().map(|f| f.to_string()).collect::<BTreeSet<_>>(),
+            );
+        }
+        res
+    }
+
+    #[test]
+    fn test_diff_resolved_features() {
+        let before = resolved(&[
+            ("serde", "1.0.210", &["std"]),
+            ("tokio", "1.41.0", &["rt"]),
+            ("libc", "0.2.160", &[]),
+        ]);
+        let after = resolved(&[
+            ("serde", "1.0.210", &["std", "derive"]),
+            ("serde_derive", "1.0.210", &[]),
+            ("tokio", "1.41.0", &["rt"]),
+            ("libc", "0.2.160", &[]),
+        ]);
+        let impact = diff_resolved_features(&before, &after);
+        assert_eq!(impact.len(), 2);
+        assert_eq!(impact[0].name, "serde");
+        assert_eq!(impact[0].features_added, vec!["derive".to_string()]);
+        assert_eq!(impact[1].name, "serde_derive");
+        assert!(impact[1].versions_before.is_empty());
+        assert_eq!(impact[1].versions_after, vec!["1.0.210".to_string()]);
+    }
+
+    #[test]
+    fn test_is_sysroot_crate() {
+        let sysroot = SourceId::for_path(Path::new(
+            "/home/u/.rustup/toolchains/nightly/lib/rustlib/src/rust/library/std",
+        ))
+        .unwrap();
+        assert!(is_sysroot_crate("std", sysroot));
+        let local = SourceId::for_path(Path::new("/home/u/project/std")).unwrap();
+        assert!(!is_sysroot_crate("std", local));
+        assert!(!is_sysroot_crate("serde", sysroot));
+    }
+}
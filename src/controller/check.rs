@@ -0,0 +1,92 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::{decoration::version_decoration, entity::into_file_uri, usecase::Document};
+
+use super::{
+    appraiser::Ctx,
+    audit::audit_workspace,
+    cargo::cargo_resolve,
+    outdated::{outdated, OutdatedDependency},
+};
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckReport {
+    pub outdated: Vec<OutdatedDependency>,
+    pub vulnerable: Vec<VulnerableDependency>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VulnerableDependency {
+    //the direct dependency pulling in the affected package
+    pub name: String,
+    pub version: String,
+    pub package: String,
+    //the advisory id, or the warning kind of warnings without an advisory
+    pub advisory: String,
+}
+
+impl CheckReport {
+    pub fn is_empty(&self) -> bool {
+        self.outdated.is_empty() && self.vulnerable.is_empty()
+    }
+}
+
+//resolve and audit a manifest without the language server, the audit is skipped offline
+pub async fn check(path: &Path, offline: bool) -> Result<CheckReport, anyhow::Error> {
+    let text = std::fs::read_to_string(path)?;
+    let uri = into_file_uri(path);
+    let mut doc = Document::parse(&uri, &text);
+    let mut output = cargo_resolve(
+        &Ctx {
+            uri: uri.clone(),
+            rev: doc.rev,
+        },
+        offline,
+        false,
+    )
+    .await?;
+    let mut report = CheckReport::default();
+    for dep in doc.dependencies.values_mut() {
+        if dep.is_virtual {
+            continue;
+        }
+        output.populate(dep);
+        report.outdated.extend(outdated(&uri, dep, version_decoration(dep)));
+    }
+    report.outdated.sort_by_key(|o| o.range.start.line);
+    if offline {
+        return Ok(report);
+    }
+    let audit = audit_workspace(&uri, &mut None)?;
+    for deps in audit.members.values() {
+        for (name, versions) in deps {
+            for (version, results) in versions {
+                for r in results {
+                    let (package, advisory) = match (&r.vuln, &r.warning) {
+                        (Some(vuln), _) => (&vuln.package, vuln.advisory.id.to_string()),
+                        (None, Some(warning)) => (
+                            &warning.package,
+                            warning
+                                .advisory
+                                .as_ref()
+                                .map_or(warning.kind.to_string(), |a| a.id.to_string()),
+                        ),
+                        (None, None) => continue,
+                    };
+                    report.vulnerable.push(VulnerableDependency {
+                        name: name.to_string(),
+                        version: version.to_string(),
+                        package: package.name.to_string(),
+                        advisory,
+                    });
+                }
+            }
+        }
+    }
+    report.vulnerable.sort_by(|a, b| (&a.name, &a.advisory).cmp(&(&b.name, &b.advisory)));
+    Ok(report)
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use cargo::util::OptVersionReq;
-use semver::{Op, Version};
+use semver::{Op, Version, VersionReq};
 use serde_json::Value;
 use tower_lsp::lsp_types::{
     CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionResponse, Command, Range, TextEdit,
@@ -9,21 +9,467 @@ use tower_lsp::lsp_types::{
 };
 
 use crate::{
+    controller::{
+        audit::AuditResult,
+        lint::{can_inherit_workspace, diverges_from_workspace, pinned_drift, unknown_features},
+    },
     decoration::{version_decoration, VersionDecorationKind},
     entity::{
-        strip_quotes, Dependency, DependencyEntryKind, DependencyKeyKind, EntryKind, KeyKind,
-        NodeKind, TomlNode, CARGO,
+        bounded_requirement, strip_quotes, Dependency, DependencyEntryKind, DependencyKeyKind,
+        EntryKind, KeyKind, NodeKind, TomlNode, CARGO,
     },
+    usecase::Document,
 };
 
 pub fn code_action(
     uri: Uri,
+    doc: &Document,
     node: TomlNode,
     dep: Option<&Dependency>,
+    audits: Option<&[AuditResult]>,
+    workspace_dep: Option<&Dependency>,
 ) -> Option<CodeActionResponse> {
     //only support dependency code action fro now
     let dep = dep?;
-    code_action_dependency(uri, &node, dep)
+    let mut actions = Vec::new();
+    if let Some(workspace_dep) = workspace_dep {
+        actions.extend(code_action_inherit_workspace(&uri, &node, dep, workspace_dep));
+    }
+    actions.extend(code_action_empty_features(&uri, doc, &node, dep));
+    actions.extend(code_action_unknown_feature(&uri, doc, &node, dep));
+    actions.extend(code_action_trim_requirement(&uri, &node));
+    //a `publish = false` crate is never on a registry, skip registry based suggestions
+    if !dep.unpublishable {
+        if let Some(audits) = audits {
+            actions.extend(code_action_audit(&uri, &node, dep, audits));
+        }
+        actions.extend(code_action_bounded_requirement(&uri, &node));
+        actions.extend(code_action_pin_drift(&node, dep));
+        if let Some(dependency_actions) = code_action_dependency(uri, &node, dep) {
+            actions.extend(dependency_actions);
+        }
+    }
+    if actions.is_empty() {
+        return None;
+    }
+    Some(actions)
+}
+
+//keep at most `max` actions, quickfixes first, then commands, then refactors
+pub fn cap_actions(mut actions: CodeActionResponse, max: Option<usize>) -> CodeActionResponse {
+    let Some(max) = max else {
+        return actions;
+    };
+    //sort is stable, actions of the same priority keep their order
+    actions.sort_by_key(|a| match a {
+        CodeActionOrCommand::CodeAction(CodeAction {
+            kind: Some(kind), ..
+        }) if *kind == CodeActionKind::QUICKFIX => 0,
+        CodeActionOrCommand::Command(_) => 1,
+        CodeActionOrCommand::CodeAction(_) => 2,
+    });
+    actions.truncate(max);
+    actions
+}
+
+//suggest bumping the direct dependency when a newer version of it drops the vulnerable transitive crate
+fn code_action_audit(
+    uri: &Uri,
+    node: &TomlNode,
+    dep: &Dependency,
+    audits: &[AuditResult],
+) -> Vec<CodeActionOrCommand> {
+    if !node.is_version_entry() {
+        return vec![];
+    }
+    let (Some(resolved), Some(summaries)) = (dep.resolved.as_ref(), dep.summaries.as_ref()) else {
+        return vec![];
+    };
+    let mut actions = Vec::new();
+    for audit in audits {
+        let Some(vuln) = audit.vuln.as_ref() else {
+            continue;
+        };
+        let vuln_name = vuln.package.name.as_str();
+        if vuln_name == dep.package_name() {
+            actions.extend(code_action_audit_solution(uri, node, dep, audit));
+            continue;
+        }
+        //only direct children of the dependency can be checked with the dependency's summaries
+        if !audit.tree.iter().any(|path| path.len() == 2) {
+            continue;
+        }
+        let candidates: Vec<_> = summaries
+            .iter()
+            .map(|s| {
+                (
+                    s.version().clone(),
+                    s.dependencies()
+                        .iter()
+                        .find(|d| d.package_name().as_str() == vuln_name)
+                        .map(|d| d.version_req().clone()),
+                )
+            })
+            .collect();
+        let Some(v) = transitive_fix_version(&candidates, resolved.version(), &vuln.package.version)
+        else {
+            continue;
+        };
+        actions.push(new_code_action(
+            uri.clone(),
+            format!("\"{}\"", v),
+            CodeActionKind::QUICKFIX,
+            node.range,
+            Some(format!(
+                "Bump {} to {} to fix {} in {}",
+                dep.package_name(),
+                v,
+                vuln.advisory.id,
+                vuln_name
+            )),
+        ));
+    }
+    actions
+}
+
+//the dependency itself is vulnerable, bump the requirement to the advisory's patched version
+fn code_action_audit_solution(
+    uri: &Uri,
+    node: &TomlNode,
+    dep: &Dependency,
+    audit: &AuditResult,
+) -> Option<CodeActionOrCommand> {
+    let vuln = audit.vuln.as_ref()?;
+    let fix = solution_version(&audit.solution()?)?;
+    let req = preserve_precision(dep.version.as_ref()?.value(), &fix)?;
+    Some(new_code_action(
+        uri.clone(),
+        format!("\"{}\"", req),
+        CodeActionKind::QUICKFIX,
+        node.range,
+        Some(format!("Upgrade to fix {}", vuln.advisory.id)),
+    ))
+}
+
+//the minimum safe version of a solution like `Upgrade to >=0.5.15` or `Upgrade to >=1.2, <2.0`
+//the first alternative is used when there are several, e.g. `>=0.4.3, <0.5 OR >=0.5.2`
+pub fn solution_version(solution: &str) -> Option<Version> {
+    let req = solution.strip_prefix("Upgrade to ")?.split(" OR ").next()?;
+    let req = VersionReq::parse(req.trim()).ok()?;
+    let lower = req
+        .comparators
+        .iter()
+        .find(|c| matches!(c.op, Op::GreaterEq | Op::Exact | Op::Caret | Op::Tilde))?;
+    Some(Version::new(lower.major, lower.minor.unwrap_or(0), lower.patch.unwrap_or(0)))
+}
+
+//keep the operator and the number of components of the requirement
+//when truncating would still allow versions before the fix, use the full version
+//a compound requirement like `>=0.5, <0.6` isn't rewritten, its other bounds would be lost
+pub fn preserve_precision(req: &str, fix: &Version) -> Option<String> {
+    let req = req.trim();
+    if req.contains(',') {
+        return None;
+    }
+    let op_len = req.find(|c: char| c.is_ascii_digit()).unwrap_or(req.len());
+    let (op, version) = req.split_at(op_len);
+    let components = version.split('.').count();
+    let text = match components {
+        1 if fix.minor == 0 && fix.patch == 0 => fix.major.to_string(),
+        2 if fix.patch == 0 => format!("{}.{}", fix.major, fix.minor),
+        _ => fix.to_string(),
+    };
+    Some(format!("{}{}", op, text))
+}
+
+//replace a requirement diverging from `[workspace.dependencies]` with `{ workspace = true }`
+fn code_action_inherit_workspace(
+    uri: &Uri,
+    node: &TomlNode,
+    dep: &Dependency,
+    workspace_dep: &Dependency,
+) -> Option<CodeActionOrCommand> {
+    if !node.is_version_entry() {
+        return None;
+    }
+    let (version, workspace_version) = (dep.version.as_ref()?, workspace_dep.version.as_ref()?);
+    if !can_inherit_workspace(dep, workspace_dep)
+        || !diverges_from_workspace(version.value(), workspace_version.value())
+    {
+        return None;
+    }
+    Some(new_code_action(
+        uri.clone(),
+        inherit_workspace_text(dep),
+        CodeActionKind::QUICKFIX,
+        dep.range,
+        Some("Inherit from workspace".to_string()),
+    ))
+}
+
+//keep the keys a member is allowed to set on an inherited dependency
+pub fn inherit_workspace_text(dep: &Dependency) -> String {
+    let mut keys = vec!["workspace = true".to_string()];
+    if let Some(features) = dep.features.as_ref().filter(|f| !f.is_empty()) {
+        let features: Vec<String> = features
+            .iter()
+            .map(|f| format!("\"{}\"", f.value()))
+            .collect();
+        keys.push(format!("features = [{}]", features.join(", ")));
+    }
+    if dep.optional.as_ref().is_some_and(|o| *o.value()) {
+        keys.push("optional = true".to_string());
+    }
+    format!("{{ {} }}", keys.join(", "))
+}
+
+//replace an open-ended requirement like `>=1.0` with a caret requirement
+fn code_action_bounded_requirement(uri: &Uri, node: &TomlNode) -> Option<CodeActionOrCommand> {
+    if !node.is_version_entry() {
+        return None;
+    }
+    let bounded = bounded_requirement(&strip_quotes(&node.text))?;
+    Some(new_code_action(
+        uri.clone(),
+        format!("\"{}\"", bounded),
+        CodeActionKind::QUICKFIX,
+        node.range,
+        Some(format!("Bound requirement to \"{}\"", bounded)),
+    ))
+}
+
+//`" 1.0 "` -> `"1.0"`
+fn code_action_trim_requirement(uri: &Uri, node: &TomlNode) -> Option<CodeActionOrCommand> {
+    if !node.is_version_entry() {
+        return None;
+    }
+    let req = strip_quotes(&node.text);
+    if req.trim() == req {
+        return None;
+    }
+    Some(new_code_action(
+        uri.clone(),
+        format!("\"{}\"", req.trim()),
+        CodeActionKind::QUICKFIX,
+        node.range,
+        Some("Trim whitespace".to_string()),
+    ))
+}
+
+//sync the lockfile with an exact requirement it doesn't honor
+fn code_action_pin_drift(node: &TomlNode, dep: &Dependency) -> Option<CodeActionOrCommand> {
+    if !node.is_version_entry() {
+        return None;
+    }
+    let resolved = dep.resolved.as_ref()?;
+    let pinned = pinned_drift(&strip_quotes(&node.text), resolved.version())?;
+    Some(new_precise_update_command(dep.package_name(), &pinned).into())
+}
+
+//remove `features = []`, collapse to a simple dependency if only `version` is left
+fn code_action_empty_features(
+    uri: &Uri,
+    doc: &Document,
+    node: &TomlNode,
+    dep: &Dependency,
+) -> Option<CodeActionOrCommand> {
+    if !matches!(
+        node.kind,
+        NodeKind::Key(KeyKind::Dependency(_, DependencyKeyKind::Features))
+            | NodeKind::Entry(EntryKind::Dependency(
+                _,
+                DependencyEntryKind::TableDependencyFeatures
+            ))
+    ) {
+        return None;
+    }
+    let features = dep.features.as_ref()?;
+    if !features.is_empty() {
+        return None;
+    }
+    //exactly `version` and `features`, in any order
+    let mut keys = dep.table_keys();
+    keys.sort_unstable();
+    if let (Some(version), ["features", "version"]) = (dep.version.as_ref(), keys.as_slice()) {
+        return Some(new_code_action(
+            uri.clone(),
+            format!("\"{}\"", version.value()),
+            CodeActionKind::QUICKFIX,
+            dep.range,
+            Some("Remove empty features".to_string()),
+        ));
+    }
+    let features_id = dep.id.to_string() + ".features";
+    let key = doc.tree.keys.get(&features_id)?.range;
+    let entry = doc.tree.entries.get(&features_id)?.range;
+    let siblings: Vec<Range> = doc
+        .dependency_nodes(dep)
+        .filter(|n| n.id != features_id && n.range != dep.range)
+        .map(|n| n.range)
+        .collect();
+    Some(new_code_action(
+        uri.clone(),
+        String::new(),
+        CodeActionKind::QUICKFIX,
+        empty_features_removal(&siblings, key, entry),
+        Some("Remove empty features".to_string()),
+    ))
+}
+
+//remove a `features` entry the resolved package doesn't have, with its separator
+fn code_action_unknown_feature(
+    uri: &Uri,
+    doc: &Document,
+    node: &TomlNode,
+    dep: &Dependency,
+) -> Option<CodeActionOrCommand> {
+    if !matches!(
+        node.kind,
+        NodeKind::Entry(EntryKind::Dependency(_, DependencyEntryKind::TableDependencyFeature))
+    ) {
+        return None;
+    }
+    let feature = unknown_features(dep)
+        .into_iter()
+        .find(|f| f.id() == node.id)?;
+    let siblings: Vec<Range> = dep
+        .features
+        .iter()
+        .flatten()
+        .filter(|f| f.id() != node.id)
+        .filter_map(|f| doc.entry(f.id()).map(|n| n.range))
+        .collect();
+    Some(new_code_action(
+        uri.clone(),
+        String::new(),
+        CodeActionKind::QUICKFIX,
+        unknown_feature_removal(&siblings, node.range),
+        Some(format!("Remove unknown feature \"{}\"", feature.value())),
+    ))
+}
+
+//the range of `features = []` plus the separator to its previous sibling,
+//or to its next sibling if it's the first key
+pub fn empty_features_removal(siblings: &[Range], key: Range, entry: Range) -> Range {
+    if let Some(prev) = siblings
+        .iter()
+        .filter(|r| r.end <= key.start)
+        .map(|r| r.end)
+        .max()
+    {
+        return Range::new(prev, entry.end);
+    }
+    if let Some(next) = siblings
+        .iter()
+        .filter(|r| r.start >= entry.end)
+        .map(|r| r.start)
+        .min()
+    {
+        return Range::new(key.start, next);
+    }
+    Range::new(key.start, entry.end)
+}
+
+//the range of a feature in the `features` array plus the comma to its previous sibling,
+//or to its next sibling if it's the first feature
+pub fn unknown_feature_removal(siblings: &[Range], feature: Range) -> Range {
+    empty_features_removal(siblings, feature, feature)
+}
+
+//rewrite every yanked dependency to the nearest non-yanked version its requirement allows
+pub fn code_action_fix_all_yanked(uri: &Uri, doc: &Document) -> Option<CodeActionOrCommand> {
+    fix_all_yanked_action(uri.clone(), yanked_fixes(doc))
+}
+
+fn yanked_fixes(doc: &Document) -> Vec<(Range, Version)> {
+    let mut fixes = Vec::new();
+    for dep in doc.dependencies.values() {
+        if dep.hidden || version_decoration(dep).kind != VersionDecorationKind::Yanked {
+            continue;
+        }
+        let (Some(resolved), Some(unresolved), Some(summaries), Some(version)) = (
+            dep.resolved.as_ref(),
+            dep.unresolved.as_ref(),
+            dep.summaries.as_ref(),
+            dep.version.as_ref(),
+        ) else {
+            continue;
+        };
+        let Some(node) = doc.entry(version.id()) else {
+            continue;
+        };
+        let versions: Vec<Version> = summaries.iter().map(|s| s.version().clone()).collect();
+        //no safe alternative, a requirement change is needed
+        let Some(v) = nearest_safe_version(&versions, resolved.version(), unresolved.version_req())
+        else {
+            continue;
+        };
+        fixes.push((node.range, v));
+    }
+    fixes
+}
+
+fn fix_all_yanked_action(
+    uri: Uri,
+    mut fixes: Vec<(Range, Version)>,
+) -> Option<CodeActionOrCommand> {
+    if fixes.is_empty() {
+        return None;
+    }
+    fixes.sort_by_key(|(range, _)| range.start);
+    let edits = fixes
+        .into_iter()
+        .map(|(range, v)| TextEdit {
+            range,
+            new_text: format!("\"{}\"", v),
+        })
+        .collect();
+    Some(
+        CodeAction {
+            title: "Fix all yanked dependencies".to_string(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            edit: Some(WorkspaceEdit {
+                changes: Some(HashMap::from([(uri, edits)])),
+                document_changes: None,
+                change_annotations: None,
+            }),
+            ..Default::default()
+        }
+        .into(),
+    )
+}
+
+//the yanked installed version is missing from `versions`,
+//prefer the closest newer version, then the closest older one
+pub fn nearest_safe_version(
+    versions: &[Version],
+    installed: &Version,
+    req: &OptVersionReq,
+) -> Option<Version> {
+    let candidates = || {
+        versions
+            .iter()
+            .filter(|v| !v.is_prerelease() && *v != installed && req.matches(v))
+    };
+    candidates()
+        .filter(|v| *v > installed)
+        .min()
+        .or_else(|| candidates().filter(|v| *v < installed).max())
+        .cloned()
+}
+
+//the minimal version newer than installed whose requirement on the vulnerable crate excludes the vulnerable version
+pub fn transitive_fix_version(
+    candidates: &[(Version, Option<OptVersionReq>)],
+    installed: &Version,
+    vulnerable: &Version,
+) -> Option<Version> {
+    candidates
+        .iter()
+        .filter(|(v, _)| v > installed && !v.is_prerelease())
+        .filter(|(_, req)| req.as_ref().map_or(true, |req| !req.matches(vulnerable)))
+        .map(|(v, _)| v.clone())
+        .min()
 }
 
 pub fn code_action_dependency(
@@ -322,3 +768,301 @@ fn new_update_command(package_name: &str) -> Command {
         ]),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, str::FromStr};
+
+    use cargo::{
+        core::{
+            Dependency as CargoDependency, EitherManifest, Package, PackageId, SourceId, Summary,
+        },
+        util::{toml::read_manifest, IntoUrl, OptVersionReq},
+        GlobalContext,
+    };
+    use semver::{Version, VersionReq};
+
+    use tower_lsp::lsp_types::{CodeActionKind, CodeActionOrCommand, Position, Range, Uri};
+
+    use super::{
+        cap_actions, code_action, code_action_fix_all_yanked, empty_features_removal,
+        inherit_workspace_text, nearest_safe_version, new_code_action, new_update_command,
+        preserve_precision, solution_version, transitive_fix_version, unknown_feature_removal,
+    };
+    use crate::{
+        entity::{Dependency, Value},
+        fixture::Fixture,
+        usecase::Document,
+    };
+
+    #[test]
+    fn test_fix_all_yanked() {
+        let gctx = GlobalContext::default().unwrap();
+        let source_id =
+            SourceId::for_registry(&"https://example.com/index".into_url().unwrap()).unwrap();
+        let fixture = Fixture::new(&[]);
+        let package = |name: &str, version: &str| {
+            let dir = format!("{}-{}", name, version);
+            fixture.write(
+                &format!("{}/Cargo.toml", dir),
+                format!("[package]\nname = \"{}\"\nversion = \"{}\"\n", name, version),
+            );
+            fixture.write(&format!("{}/src/lib.rs", dir), "");
+            let path = fixture.root().join(dir).join("Cargo.toml");
+            let EitherManifest::Real(manifest) = read_manifest(&path, source_id, &gctx).unwrap()
+            else {
+                panic!("expected a package manifest");
+            };
+            Package::new(manifest, &path)
+        };
+        let summary = |name: &str, version: &str| {
+            let id = PackageId::try_new(name, version, source_id).unwrap();
+            Summary::new(id, vec![], &BTreeMap::new(), None::<&str>, None).unwrap()
+        };
+
+        let uri = Uri::from_str("file:///C:/Users/Cargo.toml").unwrap();
+        let mut doc = Document::parse(
+            &uri,
+            r#"
+            [dependencies]
+            a = "1.0"
+            b = { version = "0.2" }
+            c = "1"
+            "#,
+        );
+        //`c` is not yanked and keeps its version
+        assert!(code_action_fix_all_yanked(&uri, &doc).is_none());
+        let yanked = [
+            ("a", "1.0", "1.0.1", vec!["1.0.0", "1.0.2", "2.0.0"]),
+            ("b", "0.2", "0.2.3", vec!["0.2.1", "0.2.2", "0.2.4", "0.2.5", "0.3.0"]),
+        ];
+        for (name, req, installed, versions) in yanked {
+            let dep = doc
+                .dependencies
+                .get_mut(&format!("dependencies.{}", name))
+                .unwrap();
+            let summaries: Vec<_> = versions.iter().map(|v| summary(name, v)).collect();
+            dep.unresolved = Some(CargoDependency::parse(name, Some(req), source_id).unwrap());
+            dep.resolved = Some(package(name, installed));
+            dep.yanked_versions = Some(vec![Version::parse(installed).unwrap()]);
+            dep.latest_summary = summaries.last().cloned();
+            dep.summaries = Some(summaries);
+        }
+
+        let Some(CodeActionOrCommand::CodeAction(action)) = code_action_fix_all_yanked(&uri, &doc)
+        else {
+            panic!("expected a code action");
+        };
+        assert_eq!(action.kind, Some(CodeActionKind::QUICKFIX));
+        let edits = &action.edit.unwrap().changes.unwrap()[&uri];
+        assert_eq!(edits.len(), 2);
+        //the nearest version the requirement allows
+        assert_eq!(edits[0].new_text, "\"1.0.2\"");
+        assert_eq!(edits[1].new_text, "\"0.2.4\"");
+    }
+
+    #[test]
+    fn test_nearest_safe_version() {
+        let versions: Vec<Version> = ["0.2.1", "0.2.2", "0.2.4", "0.2.5", "0.3.0"]
+            .iter()
+            .map(|v| Version::parse(v).unwrap())
+            .collect();
+        let installed = Version::parse("0.2.3").unwrap();
+        let req = OptVersionReq::Req(VersionReq::parse("0.2").unwrap());
+        assert_eq!(
+            nearest_safe_version(&versions, &installed, &req),
+            Some(Version::parse("0.2.4").unwrap())
+        );
+        let req = OptVersionReq::Req(VersionReq::parse(">=0.2.1, <0.2.3").unwrap());
+        let installed = Version::parse("0.2.2").unwrap();
+        assert_eq!(
+            nearest_safe_version(&versions[..1], &installed, &req),
+            Some(Version::parse("0.2.1").unwrap())
+        );
+        let req = OptVersionReq::Req(VersionReq::parse("=0.2.3").unwrap());
+        assert_eq!(nearest_safe_version(&versions, &installed, &req), None);
+    }
+
+    #[test]
+    fn test_unpublishable_no_upgrade_actions() {
+        let uri = Uri::from_str("file:///C:/Users/Cargo.toml").unwrap();
+        let doc = Document::parse(
+            &uri,
+            r#"
+            [dependencies]
+            a = { path = "../a", version = ">=0.1" }
+            "#,
+        );
+        let node = doc.entry("dependencies.a.version").unwrap().clone();
+        let mut dep = doc.dependency("dependencies.a").unwrap().clone();
+        let actions = code_action(uri.clone(), &doc, node.clone(), Some(&dep), None, None);
+        assert!(actions.is_some_and(|a| !a.is_empty()));
+
+        dep.unpublishable = true;
+        let actions = code_action(uri, &doc, node, Some(&dep), None, None);
+        assert!(actions.is_none());
+    }
+
+    #[test]
+    fn test_trim_requirement() {
+        let uri = Uri::from_str("file:///C:/Users/Cargo.toml").unwrap();
+        let doc = Document::parse(
+            &uri,
+            r#"
+            [dependencies]
+            a = { version = " 1.0 " }
+            "#,
+        );
+        assert_eq!(doc.parsing_errors.len(), 1);
+        assert_eq!(doc.parsing_errors[0].id, "dependencies.a.version");
+        assert!(!doc.parsing_errors[0].is_fatal());
+
+        let node = doc.entry("dependencies.a.version").unwrap().clone();
+        let dep = doc.dependency("dependencies.a").unwrap();
+        let actions = code_action(uri.clone(), &doc, node, Some(dep), None, None).unwrap();
+        let action = actions
+            .into_iter()
+            .find_map(|a| match a {
+                CodeActionOrCommand::CodeAction(a) if a.title == "Trim whitespace" => Some(a),
+                _ => None,
+            })
+            .unwrap();
+        let edits = &action.edit.unwrap().changes.unwrap()[&uri];
+        assert_eq!(edits[0].new_text, "\"1.0\"");
+    }
+
+    #[test]
+    fn test_empty_features_collapse() {
+        let uri = Uri::from_str("file:///C:/Users/Cargo.toml").unwrap();
+        let doc = Document::parse(
+            &uri,
+            r#"
+            [dependencies]
+            a = { version = "1", features = [] }
+            b = { features = [], version = "1" }
+            c = { features = [], version = "1", optional = true }
+            "#,
+        );
+        let collapsed = |name: &str| {
+            let id = format!("dependencies.{}", name);
+            let node = doc.entry(&format!("{}.features", id)).unwrap().clone();
+            let dep = doc.dependency(&id).unwrap();
+            let actions = code_action(uri.clone(), &doc, node, Some(dep), None, None).unwrap();
+            let action = actions
+                .into_iter()
+                .find_map(|a| match a {
+                    CodeActionOrCommand::CodeAction(a) if a.title == "Remove empty features" => {
+                        Some(a)
+                    }
+                    _ => None,
+                })
+                .unwrap();
+            action.edit.unwrap().changes.unwrap()[&uri][0].new_text == "\"1\""
+        };
+        assert!(collapsed("a"));
+        assert!(collapsed("b"));
+        //other keys are kept, only the features are removed
+        assert!(!collapsed("c"));
+    }
+
+    #[test]
+    fn test_cap_actions() {
+        let uri = Uri::from_str("file:///C:/Users/Cargo.toml").unwrap();
+        let action = |text: &str, kind: CodeActionKind| {
+            new_code_action(uri.clone(), text.to_string(), kind, Range::default(), None)
+        };
+        let actions = vec![
+            action("\"1\"", CodeActionKind::REFACTOR),
+            action("\"1.2\"", CodeActionKind::REFACTOR),
+            CodeActionOrCommand::Command(new_update_command("serde")),
+            action("\"1.2.3\"", CodeActionKind::QUICKFIX),
+            action("\"2.0.0\"", CodeActionKind::QUICKFIX),
+        ];
+        assert_eq!(cap_actions(actions.clone(), None).len(), 5);
+        let capped = cap_actions(actions, Some(3));
+        let titles: Vec<_> = capped
+            .iter()
+            .map(|a| match a {
+                CodeActionOrCommand::CodeAction(a) => a.title.as_str(),
+                CodeActionOrCommand::Command(c) => c.title.as_str(),
+            })
+            .collect();
+        assert_eq!(titles, vec!["\"1.2.3\"", "\"2.0.0\"", "cargo update serde"]);
+    }
+
+    #[test]
+    fn test_empty_features_removal() {
+        let r = |s: u32, e: u32| Range::new(Position::new(0, s), Position::new(0, e));
+        //a = { version = "1", default-features = false, features = [] }
+        let siblings = vec![r(6, 13), r(16, 19), r(21, 37), r(40, 45)];
+        let removal = empty_features_removal(&siblings, r(47, 55), r(58, 60));
+        assert_eq!(removal, r(45, 60));
+        //a = { features = [], version = "1" }
+        let siblings = vec![r(21, 28), r(31, 34)];
+        let removal = empty_features_removal(&siblings, r(6, 14), r(17, 19));
+        assert_eq!(removal, r(6, 21));
+    }
+
+    #[test]
+    fn test_unknown_feature_removal() {
+        let r = |s: u32, e: u32| Range::new(Position::new(0, s), Position::new(0, e));
+        //features = ["derive", "foo"]
+        let removal = unknown_feature_removal(&[r(12, 20)], r(22, 27));
+        assert_eq!(removal, r(20, 27));
+        //features = ["foo", "derive"]
+        let removal = unknown_feature_removal(&[r(19, 27)], r(12, 17));
+        assert_eq!(removal, r(12, 19));
+    }
+
+    #[test]
+    fn test_inherit_workspace_text() {
+        let dep = Dependency {
+            name: "serde".to_string(),
+            version: Some(Value::new("a".to_string(), "1.0.200".to_string())),
+            features: Some(vec![Value::new("b".to_string(), "derive".to_string())]),
+            optional: Some(Value::new("c".to_string(), true)),
+            ..Default::default()
+        };
+        assert_eq!(
+            inherit_workspace_text(&dep),
+            r#"{ workspace = true, features = ["derive"], optional = true }"#
+        );
+    }
+
+    #[test]
+    fn test_transitive_fix_version() {
+        let v = |s: &str| Version::parse(s).unwrap();
+        let req = |s: &str| Some(OptVersionReq::Req(VersionReq::parse(s).unwrap()));
+        //direct dependency versions and their requirement on the vulnerable crate
+        let candidates = vec![
+            (v("0.3.0"), req("0.5")),
+            (v("0.3.1"), req("0.5")),
+            (v("0.3.2"), req("^0.5.15")),
+            (v("0.4.0"), req("0.6")),
+        ];
+        let fix = transitive_fix_version(&candidates, &v("0.3.0"), &v("0.5.10"));
+        assert_eq!(fix, Some(v("0.3.2")));
+        let fix = transitive_fix_version(&candidates, &v("0.4.0"), &v("0.5.10"));
+        assert_eq!(fix, None);
+    }
+
+    #[test]
+    fn test_audit_solution_version() {
+        let v = |s: &str| Version::parse(s).unwrap();
+        let fix = solution_version("Upgrade to >=0.5.15").unwrap();
+        assert_eq!(fix, v("0.5.15"));
+        //`0.5` still allows the vulnerable 0.5.x, widen to the full version
+        assert_eq!(preserve_precision("0.5", &fix).unwrap(), "0.5.15");
+        assert_eq!(preserve_precision("^0.5.3", &fix).unwrap(), "^0.5.15");
+        //the upper bound of a compound requirement would be dropped
+        assert_eq!(preserve_precision(">=0.5, <0.6", &fix), None);
+
+        let fix = solution_version("Upgrade to >=1.2, <2.0").unwrap();
+        assert_eq!(fix, v("1.2.0"));
+        assert_eq!(preserve_precision("1.1", &fix).unwrap(), "1.2");
+        assert_eq!(preserve_precision("=1.1.4", &fix).unwrap(), "=1.2.0");
+
+        assert_eq!(solution_version("No safe upgrade available"), None);
+        assert_eq!(solution_version("Upgrade to a newer release"), None);
+    }
+}
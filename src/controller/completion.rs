@@ -1,17 +1,31 @@
+use std::{collections::HashMap, future::Future, path::Path, time::Duration};
+
+use cargo::core::FeatureValue;
+use semver::Version;
 use serde::Deserialize;
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
 use tower_lsp::lsp_types::{
-    CompletionItem, CompletionItemKind, CompletionResponse, CompletionTextEdit, Position, Range,
-    TextEdit,
+    CompletionItem, CompletionItemKind, CompletionList, CompletionResponse, CompletionTextEdit,
+    Position, Range, TextEdit, Uri,
 };
 
-use crate::entity::{Dependency, DependencyEntryKind, EntryKind, NodeKind, TomlNode};
+use crate::entity::{strip_quotes, Dependency, DependencyEntryKind, EntryKind, NodeKind, TomlNode};
 
-pub async fn completion(node: &TomlNode, dep: Option<&Dependency>) -> Option<CompletionResponse> {
-    if let Some(name) = node.crate_name() {
-        //crate name completion
-        return crate_name_completion(&name).await;
-    }
+//crate names are completed by `spawn_crate_name_completion`
+pub fn completion(
+    node: &TomlNode,
+    dep: Option<&Dependency>,
+    max_items: usize,
+) -> Option<CompletionResponse> {
     let dep = dep?;
+    if let NodeKind::Entry(EntryKind::Dependency(
+        _,
+        DependencyEntryKind::TableDependency | DependencyEntryKind::VirtualTableDependency,
+    )) = &node.kind
+    {
+        return table_key_completion(dep);
+    }
     let summaries = dep.summaries.as_ref()?;
     //TODO dep is never resolved, manually create a dependency
     if summaries.is_empty() {
@@ -28,12 +42,18 @@ pub async fn completion(node: &TomlNode, dep: Option<&Dependency>) -> Option<Com
             // Sort summaries in descending order by version
             summaries.sort_by(|a, b| b.version().cmp(a.version()));
 
+            let versions: Vec<_> = summaries.iter().map(|s| s.version()).collect();
+            //after `-` or `+`, only offer the suffixes of the typed version
+            let versions = match suffix_completions(&strip_quotes(&node.text), &versions) {
+                Some(suffixed) if !suffixed.is_empty() => suffixed,
+                _ => versions.iter().map(|v| v.to_string()).collect(),
+            };
+
             // Create a vector of CompletionItems for each version
-            let versions: Vec<_> = summaries
-                .iter()
+            let versions: Vec<_> = versions
+                .into_iter()
                 .enumerate()
-                .map(|(index, s)| {
-                    let version = s.version().to_string();
+                .map(|(index, version)| {
                     CompletionItem {
                         label: version.to_string(),
                         kind: Some(CompletionItemKind::CONSTANT),
@@ -54,36 +74,313 @@ pub async fn completion(node: &TomlNode, dep: Option<&Dependency>) -> Option<Com
                     }
                 })
                 .collect();
-            Some(CompletionResponse::Array(versions))
+            Some(capped_response(versions, max_items))
         }
-        NodeKind::Entry(EntryKind::Dependency(_, DependencyEntryKind::TableDependencyFeature)) => {
-            let summary = dep.matched_summary.as_ref()?;
-            let versions: Vec<_> = summary
-                .features()
-                .keys()
-                .map(|s| CompletionItem {
+        NodeKind::Entry(EntryKind::Dependency(
+            _,
+            kind @ (DependencyEntryKind::TableDependencyFeature
+            | DependencyEntryKind::TableDependencyFeatures),
+        )) => {
+            let summary = dep.feature_summary()?;
+            let features = summary.features().iter().map(|(name, values)| {
+                //the implicit feature of an optional dependency
+                let is_dep = matches!(
+                    values.as_slice(),
+                    [FeatureValue::Dep { dep_name }] if dep_name == name
+                );
+                (name.as_str(), is_dep)
+            });
+            let in_string = *kind == DependencyEntryKind::TableDependencyFeature;
+            let items: Vec<_> = available_features(features, dep, &node.id)
+                .into_iter()
+                .map(|(s, is_dep)| CompletionItem {
                     label: s.to_string(),
-                    kind: Some(CompletionItemKind::CONSTANT),
-                    detail: Some(s.to_string()),
+                    kind: Some(if is_dep {
+                        CompletionItemKind::MODULE
+                    } else {
+                        CompletionItemKind::CONSTANT
+                    }),
+                    detail: Some(if is_dep {
+                        format!("{} (optional dependency)", s)
+                    } else {
+                        s.to_string()
+                    }),
                     documentation: None,
-                    // sort_text: Some(format!("{:04}", index)),
-                    text_edit: Some(CompletionTextEdit::Edit(TextEdit {
-                        range: Range::new(
-                            Position::new(node.range.start.line, node.range.start.character + 1),
-                            Position::new(node.range.end.line, node.range.end.character - 1),
-                        ),
-                        new_text: s.to_string(),
-                    })),
+                    //inside a feature string replace the string content,
+                    //inside the array insert a new quoted feature
+                    text_edit: in_string.then(|| {
+                        CompletionTextEdit::Edit(TextEdit {
+                            range: Range::new(
+                                Position::new(
+                                    node.range.start.line,
+                                    node.range.start.character + 1,
+                                ),
+                                Position::new(node.range.end.line, node.range.end.character - 1),
+                            ),
+                            new_text: s.to_string(),
+                        })
+                    }),
+                    insert_text: (!in_string).then(|| format!("\"{}\"", s)),
                     ..Default::default()
                 })
                 .collect();
-            Some(CompletionResponse::Array(versions))
+            if items.is_empty() {
+                return None;
+            }
+            Some(CompletionResponse::Array(items))
         }
         _ => None,
     }
 }
 
-async fn crate_name_completion(crate_name: &str) -> Option<CompletionResponse> {
+//directories containing a `Cargo.toml` for `path = "..."`, relative to the manifest directory
+//`root` keeps the suggestions inside the workspace
+pub fn path_completion(
+    node: &TomlNode,
+    manifest_dir: &Path,
+    root: Option<&Path>,
+) -> Option<CompletionResponse> {
+    let dirs = crate_dirs(&strip_quotes(&node.text), manifest_dir, root);
+    if dirs.is_empty() {
+        return None;
+    }
+    let items = dirs
+        .into_iter()
+        .map(|dir| CompletionItem {
+            label: dir.to_string(),
+            kind: Some(CompletionItemKind::FOLDER),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range: Range::new(
+                    Position::new(node.range.start.line, node.range.start.character + 1),
+                    Position::new(node.range.end.line, node.range.end.character - 1),
+                ),
+                new_text: dir,
+            })),
+            ..Default::default()
+        })
+        .collect();
+    Some(CompletionResponse::Array(items))
+}
+
+//`../cr` lists the directories of `../` starting with `cr`
+fn crate_dirs(typed: &str, manifest_dir: &Path, root: Option<&Path>) -> Vec<String> {
+    let (parent, prefix) = match typed.rfind('/') {
+        Some(i) => typed.split_at(i + 1),
+        None => ("", typed),
+    };
+    let Ok(entries) = std::fs::read_dir(manifest_dir.join(parent)) else {
+        return vec![];
+    };
+    let root = root.and_then(|r| r.canonicalize().ok());
+    let this = manifest_dir.canonicalize().ok();
+    let mut dirs: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(prefix))
+        .filter(|e| e.path().join("Cargo.toml").is_file())
+        .filter(|e| {
+            let Ok(path) = e.path().canonicalize() else {
+                return false;
+            };
+            //the manifest itself isn't a dependency
+            this.as_ref() != Some(&path)
+                && root.as_ref().map_or(true, |root| path.starts_with(root))
+        })
+        .map(|e| format!("{}{}", parent, e.file_name().to_string_lossy()))
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+//keep the first `max` items, the client queries again as the input narrows
+fn capped_response(mut items: Vec<CompletionItem>, max: usize) -> CompletionResponse {
+    if items.len() <= max {
+        return CompletionResponse::Array(items);
+    }
+    items.truncate(max);
+    CompletionResponse::List(CompletionList {
+        is_incomplete: true,
+        items,
+    })
+}
+
+//features not yet listed in the array, `editing` is the id of the feature being typed
+fn available_features<'a>(
+    features: impl Iterator<Item = (&'a str, bool)>,
+    dep: &Dependency,
+    editing: &str,
+) -> Vec<(&'a str, bool)> {
+    let present: Vec<&str> = dep
+        .features
+        .iter()
+        .flatten()
+        .filter(|f| f.id() != editing)
+        .map(|f| f.value().as_str())
+        .collect();
+    //`default` is already enabled unless `default-features = false`
+    let defaults_on = dep.default_features.as_ref().map_or(true, |v| *v.value());
+    features
+        .filter(|(name, _)| !present.contains(name))
+        .filter(|(name, _)| !(defaults_on && *name == "default"))
+        .collect()
+}
+
+const DEPENDENCY_TABLE_KEYS: [&str; 12] = [
+    "version",
+    "features",
+    "default-features",
+    "optional",
+    "git",
+    "path",
+    "branch",
+    "tag",
+    "rev",
+    "package",
+    "registry",
+    "workspace",
+];
+
+//keys which can still be added to an inline dependency table
+fn available_table_keys(dep: &Dependency) -> Vec<&'static str> {
+    let present = dep.table_keys();
+    let has = |key: &str| present.contains(&key);
+    let has_git_ref = has("branch") || has("tag") || has("rev");
+    DEPENDENCY_TABLE_KEYS
+        .into_iter()
+        .filter(|key| !has(key))
+        .filter(|key| match *key {
+            //workspace inherited dependency only allow features and optional
+            _ if has("workspace") => matches!(*key, "features" | "optional"),
+            //`[workspace.dependencies]` can't inherit or be optional
+            "workspace" | "optional" => !dep.is_virtual,
+            "branch" | "tag" | "rev" => has("git") && !has_git_ref,
+            "git" => !has("path") && !has("registry"),
+            "path" => !has("git"),
+            "registry" => !has("git"),
+            _ => true,
+        })
+        .collect()
+}
+
+fn table_key_completion(dep: &Dependency) -> Option<CompletionResponse> {
+    let items: Vec<_> = available_table_keys(dep)
+        .into_iter()
+        .enumerate()
+        .map(|(index, key)| CompletionItem {
+            label: key.to_string(),
+            kind: Some(CompletionItemKind::PROPERTY),
+            sort_text: Some(format!("{:04}", index)),
+            insert_text: Some(format!("{} = ", key)),
+            ..Default::default()
+        })
+        .collect();
+    if items.is_empty() {
+        return None;
+    }
+    Some(CompletionResponse::Array(items))
+}
+
+//an empty prefix would list arbitrary crates, don't query crates.io for it
+fn search_query(crate_name: &str) -> Option<&str> {
+    let query = crate_name.trim();
+    if query.is_empty() {
+        return None;
+    }
+    Some(query)
+}
+
+//the typed requirement ends with `-` or `+`, list the pre-release or build metadata versions
+//sharing its base version, keeping the requirement operator
+fn suffix_completions(typed: &str, versions: &[&Version]) -> Option<Vec<String>> {
+    let (base, build) = if let Some(base) = typed.strip_suffix('-') {
+        (base, false)
+    } else if let Some(base) = typed.strip_suffix('+') {
+        (base, true)
+    } else {
+        return None;
+    };
+    let trimmed = base.trim_start_matches(['^', '~', '=', '<', '>', ' ']);
+    let op = &base[..base.len() - trimmed.len()];
+    let base = Version::parse(trimmed).ok()?;
+    let suffixed = versions
+        .iter()
+        .filter(|v| v.major == base.major && v.minor == base.minor && v.patch == base.patch)
+        .filter(|v| {
+            if build {
+                v.pre == base.pre && !v.build.is_empty()
+            } else {
+                base.pre.is_empty() && !v.pre.is_empty()
+            }
+        })
+        .map(|v| format!("{}{}", op, v))
+        .collect();
+    Some(suffixed)
+}
+
+//pending crates.io searches keyed by document,
+//a newer search in the same document cancels the pending one
+#[derive(Default)]
+pub struct CrateSearches {
+    pending: HashMap<Uri, CancellationToken>,
+}
+
+impl CrateSearches {
+    pub fn start(&mut self, uri: &Uri) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Some(superseded) = self.pending.insert(uri.clone(), token.clone()) {
+            superseded.cancel();
+        }
+        token
+    }
+
+    pub fn cancel(&mut self, uri: &Uri) {
+        if let Some(pending) = self.pending.remove(uri) {
+            pending.cancel();
+        }
+    }
+}
+
+//run `fut` after the debounce, None if cancelled before it finishes
+pub async fn debounced<T>(
+    debounce: Duration,
+    token: CancellationToken,
+    fut: impl Future<Output = Option<T>>,
+) -> Option<T> {
+    tokio::select! {
+        _ = token.cancelled() => None,
+        res = async {
+            tokio::time::sleep(debounce).await;
+            fut.await
+        } => res,
+    }
+}
+
+//search crates.io outside the event loop, a cancelled search responds with None
+pub fn spawn_crate_name_completion(
+    name: String,
+    range: Range,
+    debounce: Duration,
+    max_items: usize,
+    token: CancellationToken,
+    tx: oneshot::Sender<Option<CompletionResponse>>,
+) {
+    tokio::spawn(async move {
+        let res = match search_query(&name) {
+            Some(query) => {
+                let search = crate_name_completion(query, range, max_items);
+                debounced(debounce, token, search).await
+            }
+            None => None,
+        };
+        let _ = tx.send(res);
+    });
+}
+
+//the completed name replaces the whole key
+async fn crate_name_completion(
+    crate_name: &str,
+    range: Range,
+    max_items: usize,
+) -> Option<CompletionResponse> {
     #[derive(Deserialize, Debug)]
     struct SearchCrateOutput {
         name: String,
@@ -96,8 +393,10 @@ async fn crate_name_completion(crate_name: &str) -> Option<CompletionResponse> {
         crates: Vec<SearchCrateOutput>,
     }
 
+    //one more than the cap tells whether the list is complete, crates.io allows 100 per page
     let url = format!(
-        "https://crates.io/api/v1/crates?page=1&per_page=30&q={}",
+        "https://crates.io/api/v1/crates?page=1&per_page={}&q={}",
+        (max_items + 1).min(100),
         crate_name
     );
 
@@ -115,6 +414,10 @@ async fn crate_name_completion(crate_name: &str) -> Option<CompletionResponse> {
         .crates
         .into_iter()
         .map(|crate_info| CompletionItem {
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: crate_info.name.to_string(),
+            })),
             label: crate_info.name,
             kind: Some(CompletionItemKind::MODULE),
             detail: Some(format!("v{}", crate_info.max_version)),
@@ -125,5 +428,200 @@ async fn crate_name_completion(crate_name: &str) -> Option<CompletionResponse> {
         })
         .collect();
 
-    Some(CompletionResponse::Array(completion_items))
+    Some(capped_response(completion_items, max_items))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        entity::{Dependency, Value},
+        fixture::Fixture,
+        usecase::Document,
+    };
+
+    use std::{str::FromStr, time::Duration};
+
+    use semver::Version;
+    use tower_lsp::lsp_types::Uri;
+
+    use tower_lsp::lsp_types::{CompletionItem, CompletionResponse, Position};
+
+    use super::{
+        available_features, available_table_keys, capped_response, completion, crate_dirs,
+        debounced, search_query, suffix_completions, CrateSearches,
+    };
+
+    #[test]
+    fn test_available_features() {
+        let dep = Dependency {
+            version: Some(Value::new("dependencies.a.version".to_string(), "1".to_string())),
+            features: Some(vec![
+                Value::new("dependencies.a.features.0".to_string(), "derive".to_string()),
+                Value::new("dependencies.a.features.1".to_string(), "std".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let features = [
+            ("default", false),
+            ("derive", false),
+            ("std", false),
+            ("alloc", false),
+            ("serde_json", true),
+        ];
+        let available = available_features(features.into_iter(), &dep, "dependencies.a.features");
+        assert_eq!(available, vec![("alloc", false), ("serde_json", true)]);
+
+        //the feature being edited is still suggested
+        let available = available_features(features.into_iter(), &dep, "dependencies.a.features.1");
+        assert_eq!(available, vec![("std", false), ("alloc", false), ("serde_json", true)]);
+    }
+
+    #[test]
+    fn test_search_query() {
+        assert_eq!(search_query("serd"), Some("serd"));
+        assert_eq!(search_query(" tok "), Some("tok"));
+        assert_eq!(search_query(""), None);
+        assert_eq!(search_query("   "), None);
+    }
+
+    #[test]
+    fn test_available_table_keys() {
+        let dep = Dependency {
+            version: Some(Value::new("dependencies.a.version".to_string(), "1".to_string())),
+            ..Default::default()
+        };
+        let keys = available_table_keys(&dep);
+        assert_eq!(
+            keys,
+            vec![
+                "features",
+                "default-features",
+                "optional",
+                "git",
+                "path",
+                "package",
+                "registry",
+                "workspace"
+            ]
+        );
+
+        let dep = Dependency {
+            git: Some(Value::new("dependencies.a.git".to_string(), "url".to_string())),
+            ..Default::default()
+        };
+        let keys = available_table_keys(&dep);
+        assert!(keys.contains(&"branch"));
+        assert!(!keys.contains(&"path"));
+    }
+
+    #[test]
+    fn test_prerelease_suffix_completion() {
+        let versions: Vec<_> = [
+            "1.1.0",
+            "1.1.0-beta.1",
+            "1.1.0-alpha.2",
+            "1.0.0-rc.1",
+            "1.0.0+build.5",
+        ]
+        .iter()
+        .map(|v| Version::parse(v).unwrap())
+        .collect();
+        let versions: Vec<_> = versions.iter().collect();
+        assert_eq!(
+            suffix_completions("^1.1.0-", &versions),
+            Some(vec!["^1.1.0-beta.1".to_string(), "^1.1.0-alpha.2".to_string()])
+        );
+        assert_eq!(
+            suffix_completions("1.0.0+", &versions),
+            Some(vec!["1.0.0+build.5".to_string()])
+        );
+        assert_eq!(suffix_completions("1.2.0-", &versions), Some(vec![]));
+        assert_eq!(suffix_completions("1.1", &versions), None);
+    }
+
+    #[tokio::test]
+    async fn test_debounced_search() {
+        let mut searches = CrateSearches::default();
+        let a = Uri::from_str("file:///a/Cargo.toml").unwrap();
+        let b = Uri::from_str("file:///b/Cargo.toml").unwrap();
+        let debounce = Duration::from_millis(20);
+        let first = tokio::spawn(debounced(debounce, searches.start(&a), async { Some("ser") }));
+        let latest = tokio::spawn(debounced(debounce, searches.start(&a), async { Some("serde") }));
+        //a search in another document isn't affected
+        let other = tokio::spawn(debounced(debounce, searches.start(&b), async { Some("tokio") }));
+        assert_eq!(first.await.unwrap(), None);
+        assert_eq!(latest.await.unwrap(), Some("serde"));
+        assert_eq!(other.await.unwrap(), Some("tokio"));
+
+        let closed = tokio::spawn(debounced(debounce, searches.start(&b), async { Some("tok") }));
+        searches.cancel(&b);
+        assert_eq!(closed.await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_crate_dirs() {
+        let fixture = Fixture::new(&[
+            ("app/Cargo.toml", ""),
+            ("crates/Cargo.toml", ""),
+            ("crates/core/Cargo.toml", ""),
+            ("crates/docs/index.md", ""),
+            ("cli/Cargo.toml", ""),
+        ]);
+        let root = fixture.root();
+        let app = root.join("app");
+
+        assert_eq!(crate_dirs("../", &app, None), vec!["../cli", "../crates"]);
+        assert_eq!(crate_dirs("../cr", &app, None), vec!["../crates"]);
+        //`docs` has no Cargo.toml
+        assert_eq!(crate_dirs("../crates/", &app, None), vec!["../crates/core"]);
+        //nothing outside the workspace root
+        assert!(crate_dirs("../../", &app, Some(root)).is_empty());
+        assert_eq!(crate_dirs("../", &app, Some(root)).len(), 2);
+    }
+
+    #[test]
+    fn test_capped_response() {
+        let items = |n: usize| -> Vec<CompletionItem> {
+            (0..n)
+                .map(|i| CompletionItem {
+                    label: format!("1.0.{}", i),
+                    ..Default::default()
+                })
+                .collect()
+        };
+        let CompletionResponse::List(list) = capped_response(items(500), 50) else {
+            panic!("expected an incomplete list");
+        };
+        assert!(list.is_incomplete);
+        assert_eq!(list.items.len(), 50);
+        assert_eq!(list.items[0].label, "1.0.0");
+
+        let CompletionResponse::Array(items) = capped_response(items(50), 50) else {
+            panic!("expected a complete array");
+        };
+        assert_eq!(items.len(), 50);
+    }
+
+    #[test]
+    fn test_table_key_completion_at_cursor() {
+        let labels = |text: &str, line: u32, character: u32| {
+            let doc = Document::parse(&Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(), text);
+            let node = doc.precise_match(Position::new(line, character)).unwrap();
+            let dep = node.row_id().and_then(|id| doc.dependency(&id));
+            let Some(CompletionResponse::Array(items)) = completion(&node, dep, 50) else {
+                panic!("no table key completion");
+            };
+            items.into_iter().map(|i| i.label).collect::<Vec<_>>()
+        };
+        //the cursor is right before `}`
+        let keys = labels("[dependencies]\nserde = { version = \"1\" }\n", 1, 24);
+        assert!(!keys.contains(&"version".to_string()));
+        assert!(keys.contains(&"features".to_string()));
+        assert!(keys.contains(&"optional".to_string()));
+
+        let keys = labels("[workspace.dependencies]\nserde = { version = \"1\" }\n", 1, 24);
+        assert!(keys.contains(&"features".to_string()));
+        assert!(!keys.contains(&"optional".to_string()));
+        assert!(!keys.contains(&"workspace".to_string()));
+    }
 }
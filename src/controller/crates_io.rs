@@ -0,0 +1,183 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+use semver::Version;
+use serde::Deserialize;
+
+//hover lookups share one client, a slow crates.io response gives up after the timeout
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(reqwest::Client::new);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize, Debug)]
+struct VersionMetadata {
+    created_at: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct VersionResponse {
+    version: VersionMetadata,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct CrateMeta {
+    pub downloads: u64,
+    //downloads in the last 90 days
+    pub recent_downloads: Option<u64>,
+    pub max_stable_version: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CrateResponse {
+    #[serde(rename = "crate")]
+    krate: CrateMeta,
+}
+
+//download counts of a crate, None if the crate doesn't exist on crates.io or the request failed
+pub async fn fetch_crate_meta(name: &str) -> Option<CrateMeta> {
+    let url = format!("https://crates.io/api/v1/crates/{}", name);
+    let body = tokio::time::timeout(TIMEOUT, async {
+        let resp = CLIENT
+            .get(&url)
+            .header("User-Agent", "lsp-cargo-appraiser")
+            .send()
+            .await
+            .ok()?;
+        if !resp.status().is_success() {
+            return None;
+        }
+        resp.text().await.ok()
+    })
+    .await
+    .ok()??;
+    parse_crate_meta(&body)
+}
+
+pub fn parse_crate_meta(body: &str) -> Option<CrateMeta> {
+    let resp: CrateResponse = serde_json::from_str(body).ok()?;
+    Some(resp.krate)
+}
+
+//e.g. "⬇ 1.2M downloads (120k recent), latest stable 1.0.215"
+pub fn downloads_text(meta: &CrateMeta) -> String {
+    let mut s = format!("⬇ {} downloads", compact_count(meta.downloads));
+    if let Some(recent) = meta.recent_downloads {
+        s.push_str(&format!(" ({} recent)", compact_count(recent)));
+    }
+    if let Some(version) = &meta.max_stable_version {
+        s.push_str(&format!(", latest stable {}", version));
+    }
+    s
+}
+
+fn compact_count(n: u64) -> String {
+    let (value, unit) = match n {
+        n if n >= 1_000_000_000 => (n as f64 / 1e9, "B"),
+        n if n >= 1_000_000 => (n as f64 / 1e6, "M"),
+        n if n >= 1_000 => (n as f64 / 1e3, "k"),
+        n => return n.to_string(),
+    };
+    //one decimal below 100, e.g. 1.2M and 120k
+    let value = if value < 100.0 {
+        format!("{:.1}", value)
+    } else {
+        format!("{:.0}", value)
+    };
+    format!("{}{}", value.trim_end_matches(".0"), unit)
+}
+
+//the publish date of a version, in days since the unix epoch
+pub async fn fetch_publish_date(name: &str, version: &Version) -> Option<i64> {
+    let url = format!("https://crates.io/api/v1/crates/{}/{}", name, version);
+    let body = tokio::time::timeout(TIMEOUT, async {
+        let resp = CLIENT
+            .get(&url)
+            .header("User-Agent", "lsp-cargo-appraiser")
+            .send()
+            .await
+            .ok()?;
+        resp.text().await.ok()
+    })
+    .await
+    .ok()??;
+    parse_publish_date(&body)
+}
+
+//`created_at` is a RFC 3339 timestamp, only the date is used
+pub fn parse_publish_date(body: &str) -> Option<i64> {
+    let resp: VersionResponse = serde_json::from_str(body).ok()?;
+    let mut parts = resp.version.created_at.get(..10)?.splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    Some(days_from_civil(year, month, day))
+}
+
+//days since 1970-01-01 of a proleptic gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+pub fn today() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| (d.as_secs() / 86400) as i64)
+}
+
+pub fn relative_date(published: i64, today: i64) -> String {
+    match today - published {
+        d if d <= 0 => "today".to_string(),
+        1 => "1 day ago".to_string(),
+        d => format!("{} days ago", d),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{downloads_text, parse_crate_meta, parse_publish_date, relative_date, CrateMeta};
+
+    #[test]
+    fn test_publish_date() {
+        let body = r#"{"version":{"num":"1.0.215","created_at":"2024-01-01T08:30:00+00:00"}}"#;
+        let published = parse_publish_date(body).unwrap();
+        assert_eq!(published, 19723);
+        assert_eq!(relative_date(published, 19753), "30 days ago");
+        assert_eq!(relative_date(published, 19724), "1 day ago");
+        assert_eq!(relative_date(published, published), "today");
+        assert_eq!(parse_publish_date(r#"{"errors":[]}"#), None);
+    }
+
+    #[test]
+    fn test_crate_meta() {
+        let body = r#"{"crate":{"id":"serde","name":"serde","downloads":1234567,
+            "recent_downloads":120345,"max_stable_version":"1.0.215"},"versions":null}"#;
+        let meta = parse_crate_meta(body).unwrap();
+        assert_eq!(
+            meta,
+            CrateMeta {
+                downloads: 1234567,
+                recent_downloads: Some(120345),
+                max_stable_version: Some("1.0.215".to_string()),
+            }
+        );
+        assert_eq!(
+            downloads_text(&meta),
+            "⬇ 1.2M downloads (120k recent), latest stable 1.0.215"
+        );
+        let meta = CrateMeta {
+            downloads: 999,
+            recent_downloads: None,
+            max_stable_version: None,
+        };
+        assert_eq!(downloads_text(&meta), "⬇ 999 downloads");
+        //the 404 body of a nonexistent crate
+        assert_eq!(
+            parse_crate_meta(r#"{"errors":[{"detail":"crate `nope` does not exist"}]}"#),
+            None
+        );
+    }
+}
@@ -101,6 +101,13 @@ impl DiagnosticController {
         publish(&self.client, uri, diags).await;
     }
 
+    //every diagnostic currently published for the uri, keyed by id
+    pub fn diagnostics(&self, uri: &Uri) -> Vec<(String, Diagnostic)> {
+        self.diagnostics.get(uri).map_or(Vec::new(), |m| {
+            m.iter().map(|(k, v)| (k.id.clone(), v.clone())).collect()
+        })
+    }
+
     pub async fn clear_audit_diagnostics(&mut self) {
         for (uri, diags_map) in self.diagnostics.iter_mut() {
             //retain Parse and Cargo kind
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use tower_lsp::lsp_types::{DocumentSymbol, Range, SymbolKind};
+
+use crate::{
+    entity::{CargoTable, DependencyTable, Lint, Target},
+    usecase::Document,
+};
+
+//the outline of a manifest, `[package]`, the targets, the dependency tables, `[features]`
+//and the lints
+//target specific dependencies are listed under their own `target.<platform>.<table>` section
+pub fn document_symbols(doc: &Document) -> Vec<DocumentSymbol> {
+    let mut symbols = Vec::new();
+    if let Some(section) = table_symbol(doc, "package", Vec::new()) {
+        symbols.push(section);
+    }
+
+    let mut targets: BTreeMap<&str, Vec<DocumentSymbol>> = BTreeMap::new();
+    for target in &doc.manifest.targets {
+        let (Some(table), Some(symbol)) = (target_table(target.table), target_symbol(doc, target))
+        else {
+            continue;
+        };
+        targets.entry(table).or_default().push(symbol);
+    }
+    for (table, children) in targets {
+        symbols.extend(table_symbol(doc, table, children));
+    }
+
+    let mut tables: BTreeMap<(Option<&str>, String), Vec<DocumentSymbol>> = BTreeMap::new();
+    for dep in doc.dependencies.values() {
+        if dep.is_virtual
+            || !matches!(
+                dep.table,
+                DependencyTable::Dependencies
+                    | DependencyTable::DevDependencies
+                    | DependencyTable::BuildDependencies
+            )
+        {
+            continue;
+        }
+        tables
+            .entry((dep.platform.as_deref(), dep.table.to_string()))
+            .or_default()
+            .push(new_symbol(
+                dep.name.to_string(),
+                dep.version.as_ref().map(|v| v.value().to_string()),
+                SymbolKind::CONSTANT,
+                dep.range,
+                dep.key_range,
+                None,
+            ));
+    }
+    for ((platform, table), children) in tables {
+        let section = match platform {
+            None => table_symbol(doc, &table, children),
+            Some(platform) => {
+                section_symbol(format!("target.{}.{}", platform, table), None, children)
+            }
+        };
+        symbols.extend(section);
+    }
+
+    let features = doc
+        .manifest
+        .features
+        .keys()
+        .filter_map(|name| {
+            let node = doc.entry(&format!("features.{}", name))?;
+            Some(new_symbol(
+                name.to_string(),
+                None,
+                SymbolKind::KEY,
+                node.range,
+                node.range,
+                None,
+            ))
+        })
+        .collect();
+    symbols.extend(table_symbol(doc, "features", features));
+
+    let lints = doc.manifest.lints.iter().filter_map(|l| lint_symbol(doc, l)).collect();
+    symbols.extend(table_symbol(doc, "lints", lints));
+    if let Some(workspace) = doc.manifest.workspace.as_ref() {
+        let lints = workspace.lints.iter().filter_map(|l| lint_symbol(doc, l)).collect();
+        symbols.extend(section_symbol("workspace.lints".to_string(), None, lints));
+    }
+    symbols.sort_by_key(|s| s.range.start);
+    symbols
+}
+
+//`rust.unsafe_code` with the level as detail
+fn lint_symbol(doc: &Document, lint: &Lint) -> Option<DocumentSymbol> {
+    let key = doc.tree().keys.get(&lint.id)?;
+    let entry = doc.entry(&lint.id)?;
+    let detail = match (&lint.level, &lint.priority) {
+        (Some(level), Some(priority)) => {
+            Some(format!("{}, priority {}", level.value(), priority.value()))
+        }
+        (Some(level), None) => Some(level.value().to_string()),
+        _ => None,
+    };
+    Some(new_symbol(
+        format!("{}.{}", lint.tool, lint.name),
+        detail,
+        SymbolKind::KEY,
+        Range::new(key.range.start, entry.range.end),
+        key.range,
+        None,
+    ))
+}
+
+fn target_table(table: CargoTable) -> Option<&'static str> {
+    match table {
+        CargoTable::Bin => Some("bin"),
+        CargoTable::Example => Some("example"),
+        CargoTable::Test => Some("test"),
+        CargoTable::Bench => Some("bench"),
+        _ => None,
+    }
+}
+
+//a `[[bin]]` element by its name, with the required features and `harness = false` as detail
+fn target_symbol(doc: &Document, target: &Target) -> Option<DocumentSymbol> {
+    let name = target.name.as_ref()?;
+    let entry = doc.entry(&target.id)?;
+    let key = doc.tree().keys.get(name.id())?;
+    let mut details: Vec<&str> = target
+        .required_features
+        .iter()
+        .map(|f| f.value().as_str())
+        .collect();
+    if target.harness.as_ref().is_some_and(|h| !*h.value()) {
+        details.push("harness = false");
+    }
+    let detail = (!details.is_empty()).then(|| details.join(", "));
+    Some(new_symbol(
+        name.value().to_string(),
+        detail,
+        SymbolKind::OBJECT,
+        entry.range,
+        key.range,
+        None,
+    ))
+}
+
+//a top level table, present when the header or any child is
+fn table_symbol(
+    doc: &Document,
+    name: &str,
+    children: Vec<DocumentSymbol>,
+) -> Option<DocumentSymbol> {
+    let header = doc.manifest.headers.get(name).copied();
+    if header.is_none() && children.is_empty() {
+        return None;
+    }
+    section_symbol(name.to_string(), header, children)
+}
+
+//the section range covers the header and every child
+fn section_symbol(
+    name: String,
+    header: Option<Range>,
+    mut children: Vec<DocumentSymbol>,
+) -> Option<DocumentSymbol> {
+    children.sort_by_key(|s| s.range.start);
+    let selection_range = header.or(children.first().map(|c| c.selection_range))?;
+    let range = children
+        .iter()
+        .map(|c| c.range)
+        .chain(header)
+        .fold(selection_range, |acc, r| Range {
+            start: acc.start.min(r.start),
+            end: acc.end.max(r.end),
+        });
+    let children = (!children.is_empty()).then_some(children);
+    Some(new_symbol(
+        name,
+        None,
+        SymbolKind::MODULE,
+        range,
+        selection_range,
+        children,
+    ))
+}
+
+#[allow(deprecated)]
+fn new_symbol(
+    name: String,
+    detail: Option<String>,
+    kind: SymbolKind,
+    range: Range,
+    selection_range: Range,
+    children: Option<Vec<DocumentSymbol>>,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tower_lsp::lsp_types::{SymbolKind, Uri};
+
+    use super::document_symbols;
+    use crate::usecase::Document;
+
+    #[test]
+    fn test_document_symbols() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"[package]
+name = "a"
+version = "0.1.0"
+
+[dependencies]
+serde = { version = "1", features = ["derive"] }
+tokio = "1.40"
+
+[dev-dependencies]
+insta = "1"
+
+[features]
+default = ["serde/std"]
+"#,
+        );
+        let symbols = document_symbols(&doc);
+        let names: Vec<_> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["package", "dependencies", "dev-dependencies", "features"]);
+
+        let deps = symbols[1].children.as_ref().unwrap();
+        let deps: Vec<_> = deps
+            .iter()
+            .map(|s| (s.name.as_str(), s.detail.as_deref(), s.kind))
+            .collect();
+        assert_eq!(
+            deps,
+            vec![
+                ("serde", Some("1"), SymbolKind::CONSTANT),
+                ("tokio", Some("1.40"), SymbolKind::CONSTANT)
+            ]
+        );
+        let dev_deps = symbols[2].children.as_ref().unwrap();
+        assert_eq!(dev_deps[0].name, "insta");
+        assert_eq!(symbols[3].children.as_ref().unwrap()[0].name, "default");
+        //children are within their section
+        for section in &symbols {
+            for child in section.children.iter().flatten() {
+                assert!(section.range.start <= child.range.start);
+                assert!(child.range.end <= section.range.end);
+            }
+        }
+        assert_eq!(symbols[1].range.start.line, 4);
+        assert_eq!(symbols[1].range.end.line, 6);
+    }
+}
@@ -1,7 +1,9 @@
-use tower_lsp::lsp_types::{GotoDefinitionResponse, Location};
+use tower_lsp::lsp_types::{GotoDefinitionResponse, Location, Uri};
 
 use crate::{
-    entity::{DependencyEntryKind, DependencyKeyKind, EntryKind, KeyKind, NodeKind, TomlNode},
+    entity::{
+        Dependency, DependencyEntryKind, DependencyKeyKind, EntryKind, KeyKind, NodeKind, TomlNode,
+    },
     usecase::{Document, Workspace},
 };
 
@@ -10,23 +12,105 @@ pub fn goto_definition(
     doc: &Document,
     node: &TomlNode,
 ) -> Option<GotoDefinitionResponse> {
-    if let NodeKind::Entry(EntryKind::Dependency(
-        dep_id,
-        DependencyEntryKind::TableDependencyWorkspace,
-    ))
-    | NodeKind::Key(KeyKind::Dependency(dep_id, DependencyKeyKind::Workspace)) = &node.kind
-    {
-        let dep = doc.dependency(dep_id)?;
-        let root_uri = doc.root_manifest.as_ref()?;
-        let root_doc = state.document(root_uri)?;
-        for d in root_doc.dependencies.values() {
-            if d.name == dep.name && d.is_virtual {
-                return Some(GotoDefinitionResponse::Scalar(Location {
-                    uri: root_uri.clone(),
-                    range: d.range,
-                }));
-            }
+    let (dep, root_uri) = inherited_dependency(doc, node)?;
+    let root_doc = state.document(root_uri)?;
+    let d = root_doc.workspace_dependency(&dep.name)?;
+    Some(GotoDefinitionResponse::Scalar(Location {
+        uri: root_uri.clone(),
+        range: d.range,
+    }))
+}
+
+//the root manifest to parse before a workspace dependency of the node can be located
+pub fn unparsed_workspace_root<'a>(
+    state: &Workspace,
+    doc: &'a Document,
+    node: &TomlNode,
+) -> Option<&'a Uri> {
+    let (_, root_uri) = inherited_dependency(doc, node)?;
+    match state.document(root_uri) {
+        Some(_) => None,
+        None => Some(root_uri),
+    }
+}
+
+//a `{ workspace = true }` dependency under the node, either its crate name or `workspace` key
+fn inherited_dependency<'a>(
+    doc: &'a Document,
+    node: &TomlNode,
+) -> Option<(&'a Dependency, &'a Uri)> {
+    let dep_id = match &node.kind {
+        NodeKind::Entry(EntryKind::Dependency(
+            dep_id,
+            DependencyEntryKind::TableDependencyWorkspace,
+        ))
+        | NodeKind::Key(KeyKind::Dependency(
+            dep_id,
+            DependencyKeyKind::Workspace | DependencyKeyKind::CrateName,
+        )) => dep_id,
+        _ => return None,
+    };
+    let dep = doc.dependency(dep_id)?;
+    if !dep.workspace.as_ref().is_some_and(|w| *w.value()) {
+        return None;
+    }
+    Some((dep, doc.root_manifest.as_ref()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tower_lsp::lsp_types::{GotoDefinitionResponse, Position, Uri};
+
+    use super::{goto_definition, unparsed_workspace_root};
+    use crate::usecase::{Document, Workspace};
+
+    #[test]
+    fn test_goto_workspace_dependency() {
+        let root_uri = Uri::from_str("file:///C:/Users/Cargo.toml").unwrap();
+        let root = Document::parse(
+            &root_uri,
+            r#"[workspace]
+members = ["a"]
+
+[workspace.dependencies]
+serde = { version = "1", features = ["derive"] }
+"#,
+        );
+        let mut member = Document::parse(
+            &Uri::from_str("file:///C:/Users/a/Cargo.toml").unwrap(),
+            r#"[package]
+name = "a"
+
+[dependencies]
+serde = { workspace = true }
+tokio = "1"
+"#,
+        );
+        member.root_manifest = Some(root_uri.clone());
+        let mut state = Workspace::new();
+        //the crate name and the `workspace` key both jump to the root declaration
+        for pos in [Position::new(4, 2), Position::new(4, 12)] {
+            let node = member.precise_match(pos).unwrap();
+            assert!(goto_definition(&state, &member, &node).is_none());
+            assert_eq!(unparsed_workspace_root(&state, &member, &node), Some(&root_uri));
+        }
+        state.documents.insert(root_uri.clone(), root);
+        for pos in [Position::new(4, 2), Position::new(4, 12)] {
+            let node = member.precise_match(pos).unwrap();
+            let Some(GotoDefinitionResponse::Scalar(location)) =
+                goto_definition(&state, &member, &node)
+            else {
+                panic!("no definition at {:?}", pos);
+            };
+            assert_eq!(location.uri, root_uri);
+            assert_eq!(location.range.start.line, 4);
+            assert!(unparsed_workspace_root(&state, &member, &node).is_none());
         }
+        //a dependency that doesn't inherit has no definition
+        let node = member.precise_match(Position::new(5, 1)).unwrap();
+        assert!(goto_definition(&state, &member, &node).is_none());
+        assert!(unparsed_workspace_root(&state, &member, &node).is_none());
     }
-    None
 }
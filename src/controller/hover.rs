@@ -1,19 +1,150 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
-use tower_lsp::lsp_types::{Hover, HoverContents, MarkedString};
+use cargo::core::{FeatureValue, Summary};
+use tower_lsp::lsp_types::{Hover, HoverContents, MarkedString, Range};
 use tracing::info;
 
-use crate::entity::{
-    commit_str, git_ref_str, Dependency, DependencyEntryKind, DependencyKeyKind, EntryKind,
-    KeyKind, NodeKind, TomlNode, WorkspaceKeyKind,
+use crate::{
+    controller::audit::AuditResult,
+    entity::{
+        commit_str, exceeds_rust_version, git_ref_str, Dependency, DependencyEntryKind,
+        DependencyKeyKind, EntryKind, KeyKind, Manifest, NodeKind, TomlNode, WorkspaceKeyKind,
+    },
 };
 
+//the version hover lists the available versions
+pub fn is_version_hover(node: &TomlNode) -> bool {
+    matches!(
+        node.kind,
+        NodeKind::Key(KeyKind::Dependency(_, DependencyKeyKind::Version))
+            | NodeKind::Entry(EntryKind::Dependency(
+                _,
+                DependencyEntryKind::TableDependencyVersion
+                    | DependencyEntryKind::SimpleDependency
+            ))
+    )
+}
+
+pub fn is_crate_name_hover(node: &TomlNode) -> bool {
+    matches!(
+        node.kind,
+        NodeKind::Key(KeyKind::Dependency(_, DependencyKeyKind::CrateName))
+    )
+}
+
+pub fn text_hover(text: String, range: Range) -> Hover {
+    Hover {
+        contents: HoverContents::Scalar(MarkedString::String(text)),
+        range: Some(range),
+    }
+}
+
+//put a line before the text of a plain hover
+pub fn prepend_hover_text(hover: &mut Hover, line: &str) {
+    if let HoverContents::Scalar(MarkedString::String(s)) = &mut hover.contents {
+        *s = format!("{}\n\n{}", line, s);
+    }
+}
+
+//the installed version needs a newer rust than the package's `rust-version`
+fn rust_version_warning(dep: &Dependency, manifest: &Manifest) -> Option<String> {
+    let required = dep.minimum_rust_version()?;
+    let declared = manifest.package.rust_version.as_ref()?.value();
+    if !exceeds_rust_version(&required, declared) {
+        return None;
+    }
+    Some(format!("requires Rust {} but your package declares {}", required, declared))
+}
+
+//`.cargo/config.toml` redirects the queries of the dependency's source
+fn replaced_source_line(dep: &Dependency) -> Option<String> {
+    let replaced = dep.replaced_source?;
+    Some(format!("source replaced by {}", replaced.url()))
+}
+
+//`Required by: a, b, c`
+pub fn required_by_text(parents: &[String]) -> Option<String> {
+    if parents.is_empty() {
+        return None;
+    }
+    Some(format!("Required by: {}", parents.join(", ")))
+}
+
+//e.g. `1.2.0 stable, 1.3.0-beta.1 available`
+fn prerelease_line(dep: &Dependency) -> Option<String> {
+    let prerelease = dep.newer_prerelease()?;
+    Some(match dep.latest_stable_summary.as_ref() {
+        Some(stable) => format!("{} stable, {} available", stable.version(), prerelease),
+        None => format!("{} available", prerelease),
+    })
+}
+
+//append the vulnerable crates a dependency pulls in to its crate name hover
+pub fn audit_hover(node: &TomlNode, hover: Option<Hover>, audits: &[AuditResult]) -> Option<Hover> {
+    if !is_crate_name_hover(node) {
+        return hover;
+    }
+    let Some(text) = audit_paths_text(audits.iter().map(|r| (r.label(), r.tree.as_slice()))) else {
+        return hover;
+    };
+    match hover {
+        Some(mut hover) => {
+            if let HoverContents::Scalar(MarkedString::String(s)) = &mut hover.contents {
+                s.push('\n');
+                s.push_str(&text);
+            }
+            Some(hover)
+        }
+        None => Some(Hover {
+            contents: HoverContents::Scalar(MarkedString::String(text)),
+            range: Some(node.range),
+        }),
+    }
+}
+
+//the chains from a direct dependency down to each vulnerable crate, grouped by the crate
+pub fn audit_paths_text<'a>(
+    audits: impl Iterator<Item = (String, &'a [Vec<String>])>,
+) -> Option<String> {
+    let mut groups: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (label, paths) in audits {
+        groups
+            .entry(label)
+            .or_default()
+            .extend(paths.iter().map(|path| path.join(" -> ")));
+    }
+    if groups.is_empty() {
+        return None;
+    }
+    let mut s = String::from("### Vulnerable dependencies\n");
+    for (label, paths) in groups {
+        s.push_str(&format!("\n**{}**\n", label));
+        for path in paths {
+            s.push_str(&format!("- {}\n", path));
+        }
+    }
+    Some(s)
+}
+
+const NO_FEATURES_ENABLED: &str = "no features enabled (defaults off)";
+
 pub fn hover(
     node: &TomlNode,
     dep: Option<&Dependency>,
     members: Option<&[cargo::core::package::Package]>,
+    manifest: &Manifest,
 ) -> Option<Hover> {
     match node.kind {
+        NodeKind::Key(KeyKind::Dependency(_, DependencyKeyKind::CrateName))
+        | NodeKind::Entry(EntryKind::Dependency(_, DependencyEntryKind::TableDependencyOptional)) => {
+            let dep = dep.filter(|dep| dep.is_optional())?;
+            Some(Hover {
+                contents: HoverContents::Scalar(MarkedString::String(enabling_features_text(
+                    &manifest.enabling_features(&dep.name),
+                ))),
+                range: Some(node.range),
+            })
+        }
         NodeKind::Key(KeyKind::Dependency(_, DependencyKeyKind::Version))
         | NodeKind::Entry(EntryKind::Dependency(_, DependencyEntryKind::TableDependencyVersion))
         | NodeKind::Entry(EntryKind::Dependency(_, DependencyEntryKind::SimpleDependency)) => {
@@ -26,11 +157,20 @@ pub fn hover(
 
             versions.sort_by(|a, b| b.cmp(a));
 
-            let formatted_versions = versions
+            let mut formatted_versions = versions
                 .iter()
                 .map(|v| format!("- {}", v))
                 .collect::<Vec<_>>()
                 .join("\n");
+            if let Some(line) = prerelease_line(dep) {
+                formatted_versions = line + "\n\n" + &formatted_versions;
+            }
+            if let Some(line) = replaced_source_line(dep) {
+                formatted_versions = line + "\n\n" + &formatted_versions;
+            }
+            if let Some(warning) = rust_version_warning(dep, manifest) {
+                formatted_versions = warning + "\n\n" + &formatted_versions;
+            }
 
             Some(Hover {
                 contents: HoverContents::Scalar(MarkedString::String(formatted_versions)),
@@ -39,6 +179,14 @@ pub fn hover(
         }
         NodeKind::Key(KeyKind::Dependency(_, DependencyKeyKind::Features)) => {
             let dep = dep?;
+            if dep.has_no_features_enabled() {
+                return Some(Hover {
+                    contents: HoverContents::Scalar(MarkedString::String(
+                        NO_FEATURES_ENABLED.to_string(),
+                    )),
+                    range: Some(node.range),
+                });
+            }
             let resolved = dep.resolved.as_ref()?;
 
             let features: HashMap<_, Vec<_>> = resolved
@@ -50,7 +198,9 @@ pub fn hover(
                 .collect();
             let mut feature_list = features.keys().collect::<Vec<_>>();
             feature_list.sort();
-            let mut s = String::new();
+            let mut s = enabled_features_text(dep, resolved.summary())
+                .map(|enabled| enabled + "\n\n")
+                .unwrap_or_default();
             for key in feature_list {
                 s.push_str(&format!("- {}", key));
                 if !features[key].is_empty() {
@@ -90,6 +240,27 @@ pub fn hover(
                 range: Some(node.range),
             })
         }
+        NodeKind::Entry(EntryKind::Dependency(
+            _,
+            DependencyEntryKind::TableDependencyDefaultFeatures,
+        )) => {
+            let dep = dep?;
+            let defaults = dep.resolved.as_ref().and_then(|resolved| {
+                resolved
+                    .manifest()
+                    .summary()
+                    .features()
+                    .get("default")
+                    .map(|v| v.iter().map(|fv| fv.to_string()).collect::<Vec<_>>())
+            });
+            Some(Hover {
+                contents: HoverContents::Scalar(MarkedString::String(default_features_text(
+                    dep,
+                    defaults.as_deref(),
+                ))),
+                range: Some(node.range),
+            })
+        }
         NodeKind::Key(KeyKind::Workspace(WorkspaceKeyKind::Members)) => {
             let members = members?;
             let member_list = members
@@ -102,6 +273,17 @@ pub fn hover(
                 range: Some(node.range),
             })
         }
+        NodeKind::Entry(EntryKind::Dependency(_, DependencyEntryKind::TableDependencyRegistry)) => {
+            //the index url the registry name resolves to
+            let source_id = dep?.resolved.as_ref()?.package_id().source_id();
+            Some(Hover {
+                contents: HoverContents::Scalar(MarkedString::String(format!(
+                    "- index: {}",
+                    source_id.url()
+                ))),
+                range: Some(node.range),
+            })
+        }
         NodeKind::Entry(EntryKind::Dependency(_, DependencyEntryKind::TableDependencyGit)) => {
             let source_id = dep?.resolved.as_ref()?.package_id().source_id();
             let git_ref = git_ref_str(&source_id);
@@ -126,3 +308,194 @@ pub fn hover(
         _ => None,
     }
 }
+
+//`enabled: derive, rc, std (rc via other dependents)`,
+//features the declaration doesn't request are enabled by feature unification
+fn enabled_features_text(dep: &Dependency, summary: &Summary) -> Option<String> {
+    if dep.enabled_features.is_empty() {
+        return None;
+    }
+    let requested = requested_features(dep, summary);
+    let unified: Vec<&str> = dep
+        .enabled_features
+        .iter()
+        .map(|f| f.as_str())
+        .filter(|f| !requested.contains(f))
+        .collect();
+    let mut s = format!("enabled: {}", dep.enabled_features.join(", "));
+    if !unified.is_empty() {
+        s.push_str(&format!(" ({} via other dependents)", unified.join(", ")));
+    }
+    Some(s)
+}
+
+//the features of the declaration and the features they enable in turn
+fn requested_features<'a>(dep: &'a Dependency, summary: &'a Summary) -> HashSet<&'a str> {
+    let mut stack: Vec<&str> = dep
+        .features
+        .iter()
+        .flatten()
+        .map(|f| f.value().as_str())
+        .collect();
+    if dep.default_features.as_ref().map_or(true, |v| *v.value()) {
+        stack.push("default");
+    }
+    let mut res = HashSet::new();
+    while let Some(feature) = stack.pop() {
+        if !res.insert(feature) {
+            continue;
+        }
+        for value in summary.features().get(feature).into_iter().flatten() {
+            if let FeatureValue::Feature(name) = value {
+                stack.push(name.as_str());
+            }
+        }
+    }
+    res
+}
+
+fn enabling_features_text(features: &[&str]) -> String {
+    let mut s = "enabled by features:\n".to_string();
+    for f in features {
+        s.push_str(&format!("- {}\n", f));
+    }
+    s
+}
+
+fn default_features_text(dep: &Dependency, defaults: Option<&[String]>) -> String {
+    if dep.has_no_features_enabled() {
+        return NO_FEATURES_ENABLED.to_string();
+    }
+    let defaults_off = dep
+        .default_features
+        .as_ref()
+        .is_some_and(|v| !*v.value());
+    if defaults_off {
+        return "default features off".to_string();
+    }
+    match defaults {
+        Some(d) if !d.is_empty() => format!("default: [{}]", d.join(", ")),
+        _ => "default: []".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, str::FromStr};
+
+    use cargo::{
+        core::{PackageId, SourceId, Summary},
+        util::{interning::InternedString, IntoUrl},
+    };
+    use tower_lsp::lsp_types::{HoverContents, MarkedString, Uri};
+
+    use crate::{
+        entity::{Dependency, Value},
+        usecase::Document,
+    };
+
+    use super::{
+        audit_paths_text, default_features_text, enabled_features_text, hover, NO_FEATURES_ENABLED,
+    };
+
+    #[test]
+    fn test_optional_dependency_hover() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"
+            [dependencies]
+            serde = { version = "1", optional = true }
+            bar = { version = "1", optional = true }
+
+            [features]
+            serialize = ["dep:serde", "bar?/serde"]
+            full = ["serialize", "bar/std"]
+            "#,
+        );
+        let text = |id: &str| {
+            let node = doc.tree().keys.get(id).unwrap();
+            let dep = doc.dependency(id);
+            match hover(node, dep, None, &doc.manifest).unwrap().contents {
+                HoverContents::Scalar(MarkedString::String(s)) => s,
+                _ => unreachable!(),
+            }
+        };
+        assert_eq!(text("dependencies.serde"), "enabled by features:\n- serialize\n");
+        //`bar?/serde` doesn't enable bar, the implicit `bar` feature does
+        assert_eq!(text("dependencies.bar"), "enabled by features:\n- full\n- bar\n");
+    }
+
+    #[test]
+    fn test_audit_paths_hover() {
+        let path = |p: &[&str]| p.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let channel = vec![
+            path(&["tauri", "tokio", "crossbeam-channel"]),
+            path(&["tauri", "crossbeam-channel"]),
+        ];
+        let time = vec![path(&["tauri", "chrono", "time"])];
+        let audits = vec![
+            ("crossbeam-channel 0.5.14 (RUSTSEC-2020-0001)".to_string(), channel.as_slice()),
+            ("time 0.1.45 (RUSTSEC-2020-0071)".to_string(), time.as_slice()),
+        ];
+        let text = audit_paths_text(audits.into_iter()).unwrap();
+        assert!(text.contains("**crossbeam-channel 0.5.14 (RUSTSEC-2020-0001)**"));
+        assert!(text.contains("- tauri -> tokio -> crossbeam-channel\n"));
+        assert!(text.contains("- tauri -> crossbeam-channel\n"));
+        assert!(text.contains("- tauri -> chrono -> time\n"));
+        assert_eq!(audit_paths_text(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn test_default_features_off_hover() {
+        let mut dep = Dependency {
+            default_features: Some(Value::new(
+                "dependencies.a.default-features".to_string(),
+                false,
+            )),
+            ..Default::default()
+        };
+        let defaults = vec!["std".to_string()];
+        assert_eq!(
+            default_features_text(&dep, Some(&defaults)),
+            NO_FEATURES_ENABLED
+        );
+        dep.features = Some(vec![]);
+        assert_eq!(
+            default_features_text(&dep, Some(&defaults)),
+            NO_FEATURES_ENABLED
+        );
+        dep.default_features = None;
+        assert_eq!(default_features_text(&dep, Some(&defaults)), "default: [std]");
+    }
+
+    #[test]
+    fn test_enabled_features_text() {
+        let source_id =
+            SourceId::for_registry(&"https://example.com/index".into_url().unwrap()).unwrap();
+        let id = PackageId::try_new("serde", "1.0.0", source_id).unwrap();
+        let features = BTreeMap::from([
+            (InternedString::new("default"), vec![InternedString::new("std")]),
+            (InternedString::new("std"), vec![]),
+            (InternedString::new("derive"), vec![]),
+            (InternedString::new("rc"), vec![]),
+        ]);
+        let serde = Summary::new(id, vec![], &features, None::<&str>, None).unwrap();
+        let mut dep = Dependency {
+            features: Some(vec![Value::new(
+                "dependencies.serde.features.0".to_string(),
+                "derive".to_string(),
+            )]),
+            ..Default::default()
+        };
+        assert_eq!(enabled_features_text(&dep, &serde), None);
+
+        dep.enabled_features = ["default", "derive", "rc", "std"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        assert_eq!(
+            enabled_features_text(&dep, &serde).unwrap(),
+            "enabled: default, derive, rc, std (rc via other dependents)"
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+
+use tower_lsp::lsp_types::Uri;
+
+//with `resolve.lazy`, opened documents are only parsed,
+//resolution starts on the first hover, completion, code action or save
+#[derive(Debug, Default)]
+pub struct LazyResolve {
+    pending: HashSet<Uri>,
+}
+
+impl LazyResolve {
+    //return true if the document should be resolved now
+    pub fn opened(&mut self, uri: &Uri, lazy: bool) -> bool {
+        if lazy {
+            self.pending.insert(uri.clone());
+            return false;
+        }
+        true
+    }
+
+    //return true if the document was deferred and should be resolved now
+    pub fn interacted(&mut self, uri: &Uri) -> bool {
+        self.pending.remove(uri)
+    }
+
+    pub fn closed(&mut self, uri: &Uri) {
+        self.pending.remove(uri);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tower_lsp::lsp_types::Uri;
+
+    use super::LazyResolve;
+
+    #[test]
+    fn test_lazy_resolve_on_hover() {
+        let uri = Uri::from_str("file:///C:/Users/Cargo.toml").unwrap();
+        let mut lazy = LazyResolve::default();
+        assert!(!lazy.opened(&uri, true));
+        //the first interaction resolves, the following ones don't
+        assert!(lazy.interacted(&uri));
+        assert!(!lazy.interacted(&uri));
+
+        assert!(lazy.opened(&uri, false));
+        assert!(!lazy.interacted(&uri));
+    }
+}
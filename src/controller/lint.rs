@@ -0,0 +1,950 @@
+use cargo::{
+    core::{SourceId, SourceKind, Summary},
+    util::OptVersionReq,
+};
+use semver::{Op, Version, VersionReq};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+
+use crate::{
+    config::Config,
+    entity::{match_versions, Dependency, DependencyTable, Value},
+    usecase::Document,
+};
+
+//lint diagnostics are produced after a successful cargo resolve
+//they share the lifecycle of cargo diagnostics and are cleared on the next resolve
+pub fn lint_dependencies(doc: &Document, config: &Config) -> Vec<(String, Diagnostic)> {
+    let mut diags = Vec::new();
+    for dep in doc.dependencies.values() {
+        if dep.is_virtual || dep.hidden {
+            continue;
+        }
+        git_with_version(doc, dep, &mut diags);
+        yanked_without_alternative(doc, dep, &mut diags);
+        pin_drift(doc, dep, &mut diags);
+        if let Some(allow) = config.sources.allow.as_deref() {
+            disallowed_source(doc, dep, allow, &mut diags);
+        }
+    }
+    diags
+}
+
+//a member requirement that differs from the `[workspace.dependencies]` requirement may be drift
+pub fn lint_workspace_divergence(doc: &Document, root: &Document) -> Vec<(String, Diagnostic)> {
+    let mut diags = Vec::new();
+    for dep in doc.dependencies.values() {
+        if dep.hidden {
+            continue;
+        }
+        let Some(workspace_dep) = root.workspace_dependency(&dep.name) else {
+            continue;
+        };
+        let Some(version) = dep.version.as_ref() else {
+            continue;
+        };
+        let Some(workspace_version) = workspace_dep.version.as_ref() else {
+            continue;
+        };
+        if !can_inherit_workspace(dep, workspace_dep)
+            || !diverges_from_workspace(version.value(), workspace_version.value())
+        {
+            continue;
+        }
+        let Some(node) = doc.entry(version.id()) else {
+            continue;
+        };
+        diags.push((
+            version.id().to_string() + ".workspace-divergence",
+            lint_diagnostic(
+                node.range,
+                DiagnosticSeverity::HINT,
+                format!(
+                    "`{}` diverges from the workspace requirement `{}`, inherit it with `{{ workspace = true }}`",
+                    version.value(),
+                    workspace_version.value()
+                ),
+            ),
+        ));
+    }
+    diags
+}
+
+//a root manifest that is also a package may declare a crate in `[dependencies]`
+//and `[workspace.dependencies]`, the package uses its own requirement, not the workspace one
+pub fn lint_dual_declaration(doc: &Document) -> Vec<(String, Diagnostic)> {
+    let mut diags = Vec::new();
+    for dep in doc.dependencies.values() {
+        if dep.hidden || dep.table == DependencyTable::WorkspaceDependencies {
+            continue;
+        }
+        let Some(workspace_dep) = doc.dependencies.values().find(|d| {
+            d.table == DependencyTable::WorkspaceDependencies && d.name == dep.name
+        }) else {
+            continue;
+        };
+        let (Some(version), Some(workspace_version)) =
+            (dep.version.as_ref(), workspace_dep.version.as_ref())
+        else {
+            continue;
+        };
+        if !can_inherit_workspace(dep, workspace_dep)
+            || !diverges_from_workspace(version.value(), workspace_version.value())
+        {
+            continue;
+        }
+        let Some(node) = doc.entry(version.id()) else {
+            continue;
+        };
+        diags.push((
+            version.id().to_string() + ".dual-declaration",
+            lint_diagnostic(
+                node.range,
+                DiagnosticSeverity::HINT,
+                format!(
+                    "`{}` is also in `[workspace.dependencies]` as `{}`, this package uses `{}`, \
+                     members with `{{ workspace = true }}` use `{}`",
+                    dep.name,
+                    workspace_version.value(),
+                    version.value(),
+                    workspace_version.value()
+                ),
+            ),
+        ));
+    }
+    diags
+}
+
+//the same crate required with different syntax across the workspace, e.g. "1.0" and "^1.0"
+pub fn lint_version_syntax<'a>(
+    doc: &Document,
+    others: impl Iterator<Item = &'a Document>,
+) -> Vec<(String, Diagnostic)> {
+    let mut styles: HashMap<&str, BTreeSet<VersionSyntax>> = HashMap::new();
+    let others: Vec<&Document> = others.collect();
+    for d in others.iter().copied().chain(std::iter::once(doc)) {
+        for dep in d.dependencies.values() {
+            if dep.is_virtual {
+                continue;
+            }
+            if let Some(version) = dep.version.as_ref() {
+                styles
+                    .entry(dep.package_name())
+                    .or_default()
+                    .insert(VersionSyntax::parse(version.value()));
+            }
+        }
+    }
+    let mut diags = Vec::new();
+    for dep in doc.dependencies.values() {
+        if dep.is_virtual || dep.hidden {
+            continue;
+        }
+        let Some(version) = dep.version.as_ref() else {
+            continue;
+        };
+        let Some(syntax) = styles.get(dep.package_name()).filter(|s| s.len() > 1) else {
+            continue;
+        };
+        let Some(node) = doc.entry(version.id()) else {
+            continue;
+        };
+        diags.push((
+            version.id().to_string() + ".version-syntax",
+            lint_diagnostic(
+                node.range,
+                DiagnosticSeverity::HINT,
+                format!(
+                    "`{}` is required with mixed version syntax across the workspace: {}",
+                    dep.package_name(),
+                    syntax.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(", ")
+                ),
+            ),
+        ));
+    }
+    diags
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VersionSyntax {
+    Bare,
+    Caret,
+    Tilde,
+    Exact,
+    Wildcard,
+    Range,
+}
+
+impl VersionSyntax {
+    pub fn parse(req: &str) -> Self {
+        let req = req.trim();
+        if req.contains(',') {
+            return VersionSyntax::Range;
+        }
+        match req.chars().next() {
+            Some('^') => VersionSyntax::Caret,
+            Some('~') => VersionSyntax::Tilde,
+            Some('=') => VersionSyntax::Exact,
+            Some('>' | '<') => VersionSyntax::Range,
+            _ if req.contains('*') => VersionSyntax::Wildcard,
+            _ => VersionSyntax::Bare,
+        }
+    }
+}
+
+impl std::fmt::Display for VersionSyntax {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            VersionSyntax::Bare => "bare",
+            VersionSyntax::Caret => "`^`",
+            VersionSyntax::Tilde => "`~`",
+            VersionSyntax::Exact => "`=`",
+            VersionSyntax::Wildcard => "`*`",
+            VersionSyntax::Range => "range",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+//`bar/feature` and `bar?/feature` in `[features]` must name a feature the resolved `bar` exposes
+pub fn lint_feature_refs(doc: &Document) -> Vec<(String, Diagnostic)> {
+    let mut diags = Vec::new();
+    for value in doc.manifest.features.values().flatten() {
+        let Some((dep_name, feature)) = dependency_feature_ref(value.value()) else {
+            continue;
+        };
+        let summaries: Vec<&Summary> = doc
+            .dependencies
+            .values()
+            .filter(|dep| !dep.is_virtual && !dep.hidden && dep.name == dep_name)
+            .filter_map(|dep| dep.resolved.as_ref().map(|r| r.summary()))
+            .collect();
+        //unresolved dependencies can't be checked
+        if summaries.is_empty() || summaries.iter().any(|s| exposes_feature(s, feature)) {
+            continue;
+        }
+        let Some(node) = doc.entry(value.id()) else {
+            continue;
+        };
+        diags.push((
+            value.id().to_string() + ".unknown-dependency-feature",
+            lint_diagnostic(
+                node.range,
+                DiagnosticSeverity::ERROR,
+                format!("`{}` doesn't have a feature named `{}`", dep_name, feature),
+            ),
+        ));
+    }
+    diags
+}
+
+//entries of a dependency's `features` array the resolved package doesn't expose
+pub fn lint_dependency_features(doc: &Document) -> Vec<(String, Diagnostic)> {
+    let mut diags = Vec::new();
+    for dep in doc.dependencies.values().filter(|dep| !dep.is_virtual && !dep.hidden) {
+        for feature in unknown_features(dep) {
+            let Some(node) = doc.entry(feature.id()) else {
+                continue;
+            };
+            diags.push((
+                feature.id().to_string() + ".unknown-feature",
+                lint_diagnostic(
+                    node.range,
+                    DiagnosticSeverity::WARNING,
+                    format!("`{}` doesn't have a feature named `{}`", dep.name, feature.value()),
+                ),
+            ));
+        }
+    }
+    diags
+}
+
+//empty if the features of the dependency are unknown
+pub fn unknown_features(dep: &Dependency) -> Vec<&Value<String>> {
+    let Some(summary) = dep.feature_summary() else {
+        return vec![];
+    };
+    let Some(features) = dep.features.as_ref() else {
+        return vec![];
+    };
+    features
+        .iter()
+        .filter(|f| !exposes_feature(summary, f.value()))
+        .collect()
+}
+
+//the requirement is satisfied locally, e.g. by a path or a patch, but not by the registry
+//a crate without any published version is reported by cargo as not found
+pub fn lint_unpublished_requirement(doc: &Document) -> Vec<(String, Diagnostic)> {
+    let mut diags = Vec::new();
+    for dep in doc.dependencies.values().filter(|dep| !dep.is_virtual && !dep.hidden) {
+        let (Some(version), Some(summaries)) = (dep.version.as_ref(), dep.summaries.as_ref()) else {
+            continue;
+        };
+        let Ok(req) = VersionReq::parse(version.value()) else {
+            continue;
+        };
+        if summaries.is_empty() || summaries.iter().any(|s| req.matches(s.version())) {
+            continue;
+        }
+        let Some(node) = doc.entry(version.id()) else {
+            continue;
+        };
+        diags.push((
+            version.id().to_string() + ".no-matching-version",
+            lint_diagnostic(
+                node.range,
+                DiagnosticSeverity::WARNING,
+                format!("No published version matches `{}`", version.value()),
+            ),
+        ));
+    }
+    diags
+}
+
+//an optional dependency the default features activate should be in the resolve,
+//target specific dependencies may be left out for the host
+pub fn lint_optional_not_installed(doc: &Document) -> Vec<(String, Diagnostic)> {
+    let enabled = doc.manifest.default_features();
+    let mut diags = Vec::new();
+    if enabled.is_empty() {
+        return diags;
+    }
+    for dep in doc.dependencies.values() {
+        if dep.is_virtual
+            || dep.hidden
+            || !dep.is_optional()
+            || dep.resolved.is_some()
+            || dep.platform.is_some()
+            || doc.dirty_dependencies.contains_key(&dep.id)
+        {
+            continue;
+        }
+        let Some(feature) = doc
+            .manifest
+            .enabling_features(&dep.name)
+            .into_iter()
+            .find(|f| enabled.contains(f))
+        else {
+            continue;
+        };
+        diags.push((
+            dep.id.to_string() + ".optional-not-installed",
+            lint_diagnostic(
+                dep.key_range,
+                DiagnosticSeverity::WARNING,
+                format!(
+                    "`{}` is enabled by default through feature `{}` but is not installed",
+                    dep.name, feature
+                ),
+            ),
+        ));
+    }
+    diags
+}
+
+//split `bar/feature` or `bar?/feature`, `dep:bar` and plain features are not references
+pub fn dependency_feature_ref(value: &str) -> Option<(&str, &str)> {
+    let (dep_name, feature) = value.split_once('/')?;
+    Some((dep_name.trim_end_matches('?'), feature))
+}
+
+//a feature of the package, or one of its optional dependencies
+pub fn exposes_feature(summary: &Summary, feature: &str) -> bool {
+    summary.features().keys().any(|f| f.as_str() == feature)
+        || summary
+            .dependencies()
+            .iter()
+            .any(|d| d.is_optional() && d.name_in_toml().as_str() == feature)
+}
+
+//crates resolved at more than one version, reported once on the workspace root
+pub fn lint_duplicate_versions(
+    doc: &Document,
+    duplicates: &HashMap<String, Vec<Version>>,
+) -> Option<(String, Diagnostic)> {
+    if duplicates.is_empty() {
+        return None;
+    }
+    let mut names: Vec<_> = duplicates.keys().collect();
+    names.sort();
+    let lines: Vec<String> = names
+        .into_iter()
+        .map(|name| {
+            let versions: Vec<String> = duplicates[name].iter().map(|v| v.to_string()).collect();
+            format!("- {}: {}", name, versions.join(", "))
+        })
+        .collect();
+    //the `[dependencies]` header, or the first line
+    let range = doc
+        .manifest
+        .headers
+        .get("dependencies")
+        .copied()
+        .unwrap_or(Range::new(Position::new(0, 0), Position::new(1, 0)));
+    Some((
+        "duplicate-versions".to_string(),
+        lint_diagnostic(
+            range,
+            DiagnosticSeverity::INFORMATION,
+            format!(
+                "multiple versions of the same crate in the dependency graph:\n{}",
+                lines.join("\n")
+            ),
+        ),
+    ))
+}
+
+//a stale lockfile misses dependencies added to the manifest since the last `cargo` run
+pub fn lint_unlocked(doc: &Document, unlocked: &HashSet<String>) -> Vec<(String, Diagnostic)> {
+    let mut diags = Vec::new();
+    if unlocked.is_empty() {
+        return diags;
+    }
+    for dep in doc.dependencies.values() {
+        if dep.is_virtual || dep.hidden || !unlocked.contains(dep.package_name()) {
+            continue;
+        }
+        diags.push((
+            dep.id.to_string() + ".unlocked",
+            lint_diagnostic(
+                dep.key_range,
+                DiagnosticSeverity::WARNING,
+                format!(
+                    "`{}` is missing from Cargo.lock, run `cargo update -p {}` or `cargo check`",
+                    dep.package_name(),
+                    dep.package_name()
+                ),
+            ),
+        ));
+    }
+    diags
+}
+
+//both sides must be registry dependencies declared with a version
+pub fn can_inherit_workspace(dep: &Dependency, workspace_dep: &Dependency) -> bool {
+    !dep.is_virtual
+        && dep.workspace.is_none()
+        && dep.git.is_none()
+        && dep.path.is_none()
+        && workspace_dep.git.is_none()
+        && workspace_dep.path.is_none()
+}
+
+pub fn diverges_from_workspace(member: &str, workspace: &str) -> bool {
+    let (Ok(member), Ok(workspace)) = (VersionReq::parse(member), VersionReq::parse(workspace))
+    else {
+        return false;
+    };
+    member != workspace
+}
+
+fn disallowed_source(
+    doc: &Document,
+    dep: &Dependency,
+    allow: &[String],
+    diags: &mut Vec<(String, Diagnostic)>,
+) {
+    let Some(resolved) = dep.resolved.as_ref() else {
+        return;
+    };
+    let source = source_label(&resolved.package_id().source_id());
+    if is_source_allowed(&source, allow) {
+        return;
+    }
+    //point at the git or path value if there is one
+    let range = dep
+        .git
+        .as_ref()
+        .or(dep.path.as_ref())
+        .and_then(|v| doc.entry(v.id()))
+        .map_or(dep.range, |n| n.range);
+    diags.push((
+        dep.id.to_string() + ".source",
+        lint_diagnostic(
+            range,
+            DiagnosticSeverity::WARNING,
+            format!("source `{}` is not in the allowed sources", source),
+        ),
+    ));
+}
+
+pub fn source_label(source_id: &SourceId) -> String {
+    if source_id.is_crates_io() {
+        return "crates-io".to_string();
+    }
+    match source_id.kind() {
+        SourceKind::Git(_) => "git".to_string(),
+        SourceKind::Path | SourceKind::Directory => "path".to_string(),
+        SourceKind::Registry | SourceKind::SparseRegistry | SourceKind::LocalRegistry => {
+            format!("registry:{}", source_id.display_registry_name())
+        }
+    }
+}
+
+pub fn is_source_allowed(source: &str, allow: &[String]) -> bool {
+    allow.iter().any(|a| a == source)
+}
+
+//a dependency with both `git` and `version` is fetched from git,
+//the version requirement only needs to be satisfied by the git package
+fn git_with_version(doc: &Document, dep: &Dependency, diags: &mut Vec<(String, Diagnostic)>) {
+    let (Some(_), Some(version)) = (dep.git.as_ref(), dep.version.as_ref()) else {
+        return;
+    };
+    let Some(node) = doc.entry(version.id()) else {
+        return;
+    };
+    diags.push((
+        version.id().to_string() + ".git-precedence",
+        lint_diagnostic(
+            node.range,
+            DiagnosticSeverity::HINT,
+            "`git` takes precedence, `version` is only checked against the git package's version"
+                .to_string(),
+        ),
+    ));
+    let Some(resolved) = dep.resolved.as_ref() else {
+        return;
+    };
+    if let Some(message) = git_version_violation(version.value(), resolved.version()) {
+        diags.push((
+            version.id().to_string() + ".git-version",
+            lint_diagnostic(node.range, DiagnosticSeverity::WARNING, message),
+        ));
+    }
+}
+
+pub fn git_version_violation(req: &str, installed: &Version) -> Option<String> {
+    let req = VersionReq::parse(req).ok()?;
+    if req.matches(installed) {
+        return None;
+    }
+    Some(format!(
+        "git package version {} doesn't satisfy the version requirement `{}`",
+        installed, req
+    ))
+}
+
+//an exact requirement the lockfile doesn't honor, e.g. the pin was edited before `cargo update`
+fn pin_drift(doc: &Document, dep: &Dependency, diags: &mut Vec<(String, Diagnostic)>) {
+    let (Some(version), Some(resolved)) = (dep.version.as_ref(), dep.resolved.as_ref()) else {
+        return;
+    };
+    if !resolved.package_id().source_id().is_registry() {
+        return;
+    }
+    let Some(pinned) = pinned_drift(version.value(), resolved.version()) else {
+        return;
+    };
+    let Some(node) = doc.entry(version.id()) else {
+        return;
+    };
+    diags.push((
+        version.id().to_string() + ".pin-drift",
+        lint_diagnostic(
+            node.range,
+            DiagnosticSeverity::WARNING,
+            format!(
+                "pinned to {} but {} is installed, run `cargo update {} --precise {}`",
+                pinned,
+                resolved.version(),
+                dep.package_name(),
+                pinned
+            ),
+        ),
+    ));
+}
+
+//the pinned version of an `=x.y.z` requirement when it differs from the installed version
+pub fn pinned_drift(req: &str, installed: &Version) -> Option<Version> {
+    let req = VersionReq::parse(req.trim()).ok()?;
+    let [comparator] = req.comparators.as_slice() else {
+        return None;
+    };
+    if comparator.op != Op::Exact {
+        return None;
+    }
+    let pinned = Version {
+        major: comparator.major,
+        minor: comparator.minor?,
+        patch: comparator.patch?,
+        pre: comparator.pre.clone(),
+        build: Default::default(),
+    };
+    if req.matches(installed) {
+        return None;
+    }
+    Some(pinned)
+}
+
+//summaries exclude yanked versions, the yanked list tells a yanked install from a stale index
+//if there's also no compatible version, a fresh resolve would fail
+fn yanked_without_alternative(
+    doc: &Document,
+    dep: &Dependency,
+    diags: &mut Vec<(String, Diagnostic)>,
+) {
+    let (Some(resolved), Some(unresolved), Some(summaries)) = (
+        dep.resolved.as_ref(),
+        dep.unresolved.as_ref(),
+        dep.summaries.as_ref(),
+    ) else {
+        return;
+    };
+    if !resolved.package_id().source_id().is_registry() || !dep.is_yanked() {
+        return;
+    }
+    let versions: Vec<Version> = summaries.iter().map(|s| s.version().clone()).collect();
+    if !is_yanked_without_alternative(&versions, resolved.version(), unresolved.version_req()) {
+        return;
+    }
+    let range = dep
+        .version
+        .as_ref()
+        .and_then(|v| doc.entry(v.id()))
+        .map_or(dep.range, |n| n.range);
+    diags.push((
+        dep.id.to_string() + ".yanked",
+        lint_diagnostic(
+            range,
+            DiagnosticSeverity::ERROR,
+            format!(
+                "installed version {} is yanked and no compatible version is available, a fresh resolve will fail; relax the version requirement and run `cargo update -p {}`",
+                resolved.version(),
+                dep.package_name()
+            ),
+        ),
+    ));
+}
+
+pub fn is_yanked_without_alternative(
+    versions: &[Version],
+    installed: &Version,
+    req: &OptVersionReq,
+) -> bool {
+    let matched = match_versions(versions, |v| v, installed, req);
+    matched.installed.is_none() && matched.latest_matched.is_none()
+}
+
+pub fn lint_diagnostic(range: Range, severity: DiagnosticSeverity, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: None,
+        code_description: None,
+        source: Some("cargo-appraiser".to_string()),
+        message,
+        related_information: None,
+        tags: None,
+        data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cargo::{
+        core::{GitReference, PackageId, SourceId, Summary},
+        util::{interning::InternedString, IntoUrl, OptVersionReq},
+    };
+    use std::{
+        collections::{BTreeMap, HashMap},
+        str::FromStr,
+    };
+
+    use semver::{Version, VersionReq};
+    use tower_lsp::lsp_types::{DiagnosticSeverity, Uri};
+
+    use super::{
+        dependency_feature_ref, exposes_feature, git_version_violation, is_source_allowed,
+        is_yanked_without_alternative, lint_dependency_features, lint_dual_declaration,
+        lint_duplicate_versions, lint_optional_not_installed, lint_unpublished_requirement,
+        lint_version_syntax, lint_workspace_divergence, pinned_drift, source_label, VersionSyntax,
+    };
+    use crate::{controller::completion::completion, usecase::Document};
+
+    #[test]
+    fn test_workspace_divergence() {
+        let root = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"
+            [workspace.dependencies]
+            serde = "1.0"
+            tokio = "1"
+            "#,
+        );
+        let member = Document::parse(
+            &Uri::from_str("file:///C:/Users/a/Cargo.toml").unwrap(),
+            r#"
+            [dependencies]
+            serde = "1.0.200"
+            tokio = "^1"
+            "#,
+        );
+        let diags = lint_workspace_divergence(&member, &root);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].0, "dependencies.serde.workspace-divergence");
+        assert_eq!(diags[0].1.severity, Some(DiagnosticSeverity::HINT));
+    }
+
+    #[test]
+    fn test_dual_declaration() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"
+            [package]
+            name = "app"
+
+            [workspace.dependencies]
+            serde = "1.0"
+            tokio = "1"
+
+            [dependencies]
+            serde = "1.0.200"
+            tokio = "1"
+            "#,
+        );
+        let diags = lint_dual_declaration(&doc);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].0, "dependencies.serde.dual-declaration");
+        assert_eq!(diags[0].1.severity, Some(DiagnosticSeverity::HINT));
+        assert!(diags[0].1.message.contains("this package uses `1.0.200`"));
+    }
+
+    #[test]
+    fn test_mixed_version_syntax() {
+        let a = Document::parse(
+            &Uri::from_str("file:///C:/Users/a/Cargo.toml").unwrap(),
+            r#"
+            [dependencies]
+            serde = "1.0"
+            tokio = "1"
+            "#,
+        );
+        let b = Document::parse(
+            &Uri::from_str("file:///C:/Users/b/Cargo.toml").unwrap(),
+            r#"
+            [dependencies]
+            serde = "^1.0"
+            tokio = "1.38"
+            "#,
+        );
+        let diags = lint_version_syntax(&a, std::iter::once(&b));
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].0, "dependencies.serde.version-syntax");
+        assert_eq!(diags[0].1.severity, Some(DiagnosticSeverity::HINT));
+
+        assert_eq!(VersionSyntax::parse("=1.0.0"), VersionSyntax::Exact);
+        assert_eq!(VersionSyntax::parse(">=1, <2"), VersionSyntax::Range);
+        assert_eq!(VersionSyntax::parse("1.*"), VersionSyntax::Wildcard);
+    }
+
+    #[test]
+    fn test_unknown_dependency_feature() {
+        let source_id =
+            SourceId::for_registry(&"https://example.com/index".into_url().unwrap()).unwrap();
+        let id = PackageId::try_new("bar", "1.0.0", source_id).unwrap();
+        let features = BTreeMap::from([(InternedString::new("std"), vec![])]);
+        let bar = Summary::new(id, vec![], &features, None::<&str>, None).unwrap();
+
+        let (dep_name, feature) = dependency_feature_ref("bar/nonexistent").unwrap();
+        assert_eq!(dep_name, "bar");
+        assert!(!exposes_feature(&bar, feature));
+        let (dep_name, feature) = dependency_feature_ref("bar?/std").unwrap();
+        assert_eq!(dep_name, "bar");
+        assert!(exposes_feature(&bar, feature));
+        assert_eq!(dependency_feature_ref("dep:bar"), None);
+    }
+
+    #[test]
+    fn test_unknown_feature_in_dependency() {
+        let source_id =
+            SourceId::for_registry(&"https://example.com/index".into_url().unwrap()).unwrap();
+        let id = PackageId::try_new("bar", "1.0.0", source_id).unwrap();
+        let features = BTreeMap::from([(InternedString::new("std"), vec![])]);
+        let bar = Summary::new(id, vec![], &features, None::<&str>, None).unwrap();
+
+        let mut doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"
+            [dependencies]
+            bar = { version = "1", features = ["std", "nonexistent"] }
+            "#,
+        );
+        //features can't be checked before the dependency is resolved
+        assert!(lint_dependency_features(&doc).is_empty());
+
+        let dep = doc.dependencies.get_mut("dependencies.bar").unwrap();
+        dep.matched_summary = Some(bar.clone());
+        let diags = lint_dependency_features(&doc);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].0, "dependencies.bar.features.1.unknown-feature");
+        assert_eq!(diags[0].1.severity, Some(DiagnosticSeverity::WARNING));
+        doc.dependencies.get_mut("dependencies.bar").unwrap().hidden = true;
+        assert!(lint_dependency_features(&doc).is_empty());
+
+        let mut doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"
+            [dependencies]
+            bar = { version = "1", features = ["std"] }
+            "#,
+        );
+        let dep = doc.dependencies.get_mut("dependencies.bar").unwrap();
+        dep.matched_summary = Some(bar);
+        assert!(lint_dependency_features(&doc).is_empty());
+
+        //the local index cache read when the registry is unreachable has no features
+        let mut doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"
+            [dependencies]
+            bar = { version = "1", features = ["std"] }
+            "#,
+        );
+        let cached = Summary::new(id, vec![], &BTreeMap::new(), None::<&str>, None).unwrap();
+        let dep = doc.dependencies.get_mut("dependencies.bar").unwrap();
+        dep.matched_summary = Some(cached);
+        dep.summaries_from_cache = true;
+        assert!(lint_dependency_features(&doc).is_empty());
+        let node = doc.entry("dependencies.bar.features.0").unwrap();
+        let dep = doc.dependency("dependencies.bar");
+        assert!(completion(node, dep, 10).is_none());
+    }
+
+    #[test]
+    fn test_unpublished_requirement() {
+        let source_id =
+            SourceId::for_registry(&"https://example.com/index".into_url().unwrap()).unwrap();
+        let published: Vec<Summary> = ["1.0.0", "1.2.0"]
+            .iter()
+            .map(|v| {
+                let id = PackageId::try_new("a", *v, source_id).unwrap();
+                Summary::new(id, vec![], &BTreeMap::new(), None::<&str>, None).unwrap()
+            })
+            .collect();
+        let mut doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"
+            [dependencies]
+            a = { path = "../a", version = "99.0" }
+            b = "1.1"
+            c = "1"
+            "#,
+        );
+        for (id, summaries) in [
+            ("dependencies.a", published.clone()),
+            ("dependencies.b", published),
+            //not found in the registry
+            ("dependencies.c", vec![]),
+        ] {
+            doc.dependencies.get_mut(id).unwrap().summaries = Some(summaries);
+        }
+        let diags = lint_unpublished_requirement(&doc);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].0, "dependencies.a.version.no-matching-version");
+        assert_eq!(diags[0].1.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diags[0].1.message, "No published version matches `99.0`");
+        doc.dependencies.get_mut("dependencies.a").unwrap().hidden = true;
+        assert!(lint_unpublished_requirement(&doc).is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_versions() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"
+[package]
+name = "app"
+
+[dependencies]
+syn = "2"
+"#,
+        );
+        let duplicates = HashMap::from([(
+            "syn".to_string(),
+            vec![Version::parse("2.0.87").unwrap(), Version::parse("1.0.109").unwrap()],
+        )]);
+        let (id, diag) = lint_duplicate_versions(&doc, &duplicates).unwrap();
+        assert_eq!(id, "duplicate-versions");
+        assert_eq!(diag.severity, Some(DiagnosticSeverity::INFORMATION));
+        assert!(diag.message.contains("- syn: 2.0.87, 1.0.109"));
+        //attached to the `[dependencies]` header
+        assert_eq!(diag.range.start.line, 4);
+        assert!(lint_duplicate_versions(&doc, &HashMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_yanked_without_alternative() {
+        let installed = Version::parse("0.2.3").unwrap();
+        let req = OptVersionReq::Req(VersionReq::parse("=0.2.3").unwrap());
+        //0.2.3 is yanked, only newer versions are published
+        let versions = vec![
+            Version::parse("0.3.0").unwrap(),
+            Version::parse("0.2.4").unwrap(),
+        ];
+        assert!(is_yanked_without_alternative(&versions, &installed, &req));
+
+        let req = OptVersionReq::Req(VersionReq::parse("0.2").unwrap());
+        assert!(!is_yanked_without_alternative(&versions, &installed, &req));
+
+        let versions = vec![Version::parse("0.2.3").unwrap()];
+        let req = OptVersionReq::Req(VersionReq::parse("=0.2.3").unwrap());
+        assert!(!is_yanked_without_alternative(&versions, &installed, &req));
+    }
+
+    #[test]
+    fn test_git_version_violation() {
+        let installed = Version::parse("0.3.0").unwrap();
+        assert!(git_version_violation("0.2", &installed).is_some());
+        assert!(git_version_violation("0.3", &installed).is_none());
+    }
+
+    #[test]
+    fn test_pinned_drift() {
+        let installed = Version::parse("1.0.2").unwrap();
+        assert_eq!(pinned_drift("=1.0.1", &installed), Some(Version::parse("1.0.1").unwrap()));
+        assert!(pinned_drift("=1.0.2", &installed).is_none());
+        //partial exact requirements match a range
+        assert!(pinned_drift("=1.0", &installed).is_none());
+        assert!(pinned_drift("1.0.1", &installed).is_none());
+        assert!(pinned_drift(">=1.0.1, <1.0.2", &installed).is_none());
+    }
+
+    #[test]
+    fn test_disallowed_git_source() {
+        let allow = vec!["crates-io".to_string()];
+        let git = SourceId::for_git(
+            &"https://github.com/serde-rs/serde".into_url().unwrap(),
+            GitReference::DefaultBranch,
+        )
+        .unwrap();
+        let label = source_label(&git);
+        assert_eq!(label, "git");
+        assert!(!is_source_allowed(&label, &allow));
+        assert!(is_source_allowed("crates-io", &allow));
+    }
+
+    #[test]
+    fn test_optional_not_installed() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"
+            [features]
+            default = ["std"]
+            std = ["dep:a", "b?/std"]
+            extra = ["c"]
+
+            [dependencies]
+            a = { version = "1", optional = true }
+            b = { version = "1", optional = true }
+            c = { version = "1", optional = true }
+            "#,
+        );
+        //`b?/std` doesn't activate `b`, `c` is behind a non default feature
+        let diags = lint_optional_not_installed(&doc);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].0, "dependencies.a.optional-not-installed");
+        assert!(diags[0].1.message.contains("through feature `std`"));
+    }
+}
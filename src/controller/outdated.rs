@@ -0,0 +1,206 @@
+use semver::Version;
+use serde::Serialize;
+use tower_lsp::lsp_types::{Range, Uri};
+
+use crate::{
+    decoration::{version_decoration, DecorationPayload, VersionDecorationKind},
+    entity::Dependency,
+    usecase::Workspace,
+};
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OutdatedDependency {
+    pub uri: Uri,
+    pub name: String,
+    pub table: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    pub range: Range,
+    pub installed: Version,
+    pub latest_matched: Version,
+    pub latest: Version,
+}
+
+//list every dependency with an available upgrade across the parsed documents
+pub fn outdated_dependencies(state: &Workspace) -> Vec<OutdatedDependency> {
+    let mut res = Vec::new();
+    for (uri, doc) in &state.documents {
+        for dep in doc.dependencies.values() {
+            if dep.is_virtual {
+                continue;
+            }
+            if let Some(o) = outdated(uri, dep, version_decoration(dep)) {
+                res.push(o);
+            }
+        }
+    }
+    res.sort_by(|a, b| {
+        (a.uri.as_str(), a.range.start.line).cmp(&(b.uri.as_str(), b.range.start.line))
+    });
+    res
+}
+
+#[derive(Debug, Default, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UpgradeSummary {
+    pub total: usize,
+    //a newer version matches the requirement
+    pub compatible: usize,
+    //the latest version is semver incompatible with the installed one
+    pub breaking: usize,
+}
+
+impl UpgradeSummary {
+    //a dependency can have both a compatible and a breaking upgrade
+    pub fn add(&mut self, payload: &DecorationPayload) {
+        self.total += 1;
+        let Some(installed) = payload.installed.as_ref() else {
+            return;
+        };
+        if payload.latest_matched.as_ref().is_some_and(|v| v > installed) {
+            self.compatible += 1;
+        }
+        if payload.latest.as_ref().is_some_and(|v| is_breaking(installed, v)) {
+            self.breaking += 1;
+        }
+    }
+}
+
+//count the available upgrades across the parsed documents
+pub fn upgrade_summary(state: &Workspace) -> UpgradeSummary {
+    let mut summary = UpgradeSummary::default();
+    for doc in state.documents.values() {
+        for dep in doc.dependencies.values().filter(|dep| !dep.is_virtual) {
+            summary.add(&version_decoration(dep));
+        }
+    }
+    summary
+}
+
+//`0.x` versions break on a minor bump, `0.0.x` on a patch bump
+fn is_breaking(installed: &Version, latest: &Version) -> bool {
+    if latest <= installed {
+        return false;
+    }
+    if installed.major != latest.major {
+        return true;
+    }
+    if installed.major != 0 {
+        return false;
+    }
+    installed.minor != latest.minor || (installed.minor == 0 && installed.patch != latest.patch)
+}
+
+pub fn outdated(
+    uri: &Uri,
+    dep: &Dependency,
+    payload: DecorationPayload,
+) -> Option<OutdatedDependency> {
+    match payload.kind {
+        VersionDecorationKind::MixedUpgradeable
+        | VersionDecorationKind::CompatibleLatest
+        | VersionDecorationKind::NonCompatibleLatest
+        | VersionDecorationKind::Yanked => Some(OutdatedDependency {
+            uri: uri.clone(),
+            name: dep.name.clone(),
+            table: dep.table.to_string(),
+            platform: dep.platform.clone(),
+            range: dep.range,
+            installed: payload.installed?,
+            latest_matched: payload.latest_matched?,
+            latest: payload.latest?,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use semver::Version;
+    use tower_lsp::lsp_types::Uri;
+
+    use crate::{
+        decoration::{DecorationPayload, VersionDecorationKind},
+        entity::Dependency,
+    };
+
+    use super::{outdated, UpgradeSummary};
+
+    fn payload(kind: VersionDecorationKind, installed: &str, latest: &str) -> DecorationPayload {
+        DecorationPayload {
+            kind,
+            installed: Some(Version::parse(installed).unwrap()),
+            latest_matched: Some(Version::parse(latest).unwrap()),
+            latest: Some(Version::parse(latest).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_outdated_members() {
+        let members = [
+            (
+                Uri::from_str("file:///ws/a/Cargo.toml").unwrap(),
+                "serde",
+                payload(VersionDecorationKind::CompatibleLatest, "1.0.1", "1.0.2"),
+            ),
+            (
+                Uri::from_str("file:///ws/b/Cargo.toml").unwrap(),
+                "tokio",
+                payload(VersionDecorationKind::Latest, "1.41.0", "1.41.0"),
+            ),
+            (
+                Uri::from_str("file:///ws/b/Cargo.toml").unwrap(),
+                "rand",
+                payload(VersionDecorationKind::NonCompatibleLatest, "0.7.3", "0.8.5"),
+            ),
+        ];
+        let list: Vec<_> = members
+            .into_iter()
+            .filter_map(|(uri, name, p)| {
+                let dep = Dependency {
+                    name: name.to_string(),
+                    ..Default::default()
+                };
+                outdated(&uri, &dep, p)
+            })
+            .collect();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].name, "serde");
+        assert_eq!(list[0].uri.as_str(), "file:///ws/a/Cargo.toml");
+        assert_eq!(list[1].name, "rand");
+        assert_eq!(list[1].latest, Version::parse("0.8.5").unwrap());
+    }
+
+    #[test]
+    fn test_upgrade_summary() {
+        let mut summary = UpgradeSummary::default();
+        for (installed, latest_matched, latest) in [
+            ("1.0.1", "1.0.2", "1.0.2"),
+            ("1.0.1", "1.0.2", "2.0.0"),
+            ("0.7.3", "0.7.3", "0.8.5"),
+            ("1.41.0", "1.41.0", "1.41.0"),
+            ("0.0.1", "0.0.1", "0.0.2"),
+        ] {
+            summary.add(&DecorationPayload {
+                installed: Some(Version::parse(installed).unwrap()),
+                latest_matched: Some(Version::parse(latest_matched).unwrap()),
+                latest: Some(Version::parse(latest).unwrap()),
+                ..Default::default()
+            });
+        }
+        //not installed
+        summary.add(&DecorationPayload::default());
+        assert_eq!(
+            summary,
+            UpgradeSummary {
+                total: 6,
+                compatible: 2,
+                breaking: 3,
+            }
+        );
+    }
+}
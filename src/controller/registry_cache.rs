@@ -0,0 +1,117 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use cargo::core::{PackageId, SourceId, Summary};
+use semver::Version;
+use serde::Deserialize;
+
+//a line of the registry index, only the version and the yanked flag are read
+#[derive(Deserialize, Debug)]
+struct IndexLine {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+//summaries and yanked versions from cargo's local index cache
+//used when the registry is unreachable
+//the summaries carry no dependencies or features, only versions are available offline
+pub fn cached_summaries(
+    index_root: &Path,
+    source_id: SourceId,
+    name: &str,
+) -> (Vec<Summary>, Vec<Version>) {
+    let Some(host) = source_id.url().host_str() else {
+        return (vec![], vec![]);
+    };
+    let mut summaries = Vec::new();
+    let mut yanked = Vec::new();
+    for (version, is_yanked) in cached_versions(index_root, host, name) {
+        if is_yanked {
+            yanked.push(version);
+            continue;
+        }
+        let Ok(id) = PackageId::try_new(name, version.to_string(), source_id) else {
+            continue;
+        };
+        if let Ok(summary) = Summary::new(id, vec![], &BTreeMap::new(), None::<&str>, None) {
+            summaries.push(summary);
+        }
+    }
+    (summaries, yanked)
+}
+
+//index directories are named `<host>-<hash>`, e.g. `index.crates.io-1949cf8c6b5b557f`
+pub fn cached_versions(index_root: &Path, host: &str, name: &str) -> Vec<(Version, bool)> {
+    let Ok(dirs) = fs::read_dir(index_root) else {
+        return vec![];
+    };
+    let prefix = format!("{}-", host);
+    dirs.filter_map(|d| d.ok())
+        .filter(|d| d.file_name().to_string_lossy().starts_with(&prefix))
+        .map(|d| d.path().join(".cache").join(index_path(name)))
+        .find_map(|path| fs::read(path).ok())
+        .map_or(vec![], |bytes| parse_index_cache(&bytes))
+}
+
+//the path of a crate in the index, e.g. `1/a`, `3/s/syn`, `se/rd/serde`
+pub fn index_path(name: &str) -> PathBuf {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => PathBuf::from("1").join(&name),
+        2 => PathBuf::from("2").join(&name),
+        3 => PathBuf::from("3").join(&name[..1]).join(&name),
+        _ => PathBuf::from(&name[..2]).join(&name[2..4]).join(&name),
+    }
+}
+
+//the cache file starts with a cache version byte and a u32 index format version,
+//followed by null terminated strings: the index version, then pairs of version and json line
+pub fn parse_index_cache(bytes: &[u8]) -> Vec<(Version, bool)> {
+    let Some(rest) = bytes.get(5..) else {
+        return vec![];
+    };
+    let mut parts = rest.split(|b| *b == 0).skip(1);
+    let mut versions = Vec::new();
+    while let (Some(_), Some(line)) = (parts.next(), parts.next()) {
+        let Ok(line) = serde_json::from_slice::<IndexLine>(line) else {
+            continue;
+        };
+        if let Ok(version) = Version::parse(&line.vers) {
+            versions.push((version, line.yanked));
+        }
+    }
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use semver::Version;
+
+    use super::{cached_versions, index_path};
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_cached_versions() {
+        let fixture = Fixture::new(&[]);
+        let root = fixture.root();
+        let mut bytes = vec![3, 2, 0, 0, 0];
+        bytes.extend_from_slice(b"etag: \"abc\"\0");
+        bytes.extend_from_slice(b"1.0.0\0{\"vers\":\"1.0.0\"}\0");
+        bytes.extend_from_slice(b"1.0.1\0{\"vers\":\"1.0.1\",\"yanked\":true}\0");
+        fixture.write("index.crates.io-1949cf8c6b5b557f/.cache/se/rd/serde", bytes);
+
+        let versions = cached_versions(root, "index.crates.io", "serde");
+        assert_eq!(
+            versions,
+            vec![(Version::new(1, 0, 0), false), (Version::new(1, 0, 1), true)]
+        );
+        assert!(cached_versions(root, "index.crates.io", "tokio").is_empty());
+        assert_eq!(index_path("syn"), PathBuf::from("3/s/syn"));
+    }
+}
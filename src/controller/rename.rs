@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{Range, TextEdit, Uri, WorkspaceEdit};
+
+use crate::{
+    entity::{validate_crate_name, Dependency, DependencyKeyKind, KeyKind, NodeKind, TomlNode},
+    usecase::Document,
+};
+
+//only the crate name key of a `[workspace.dependencies]` entry can be renamed
+pub fn prepare_rename(doc: &Document, node: &TomlNode) -> Option<Range> {
+    workspace_dependency(doc, node).map(|_| node.range)
+}
+
+//rename the workspace dependency and every `{ workspace = true }` inheriting it in the members
+pub fn rename(
+    root: &Document,
+    node: &TomlNode,
+    new_name: &str,
+    members: &[&Document],
+) -> Result<Option<WorkspaceEdit>, String> {
+    let Some(dep) = workspace_dependency(root, node) else {
+        return Ok(None);
+    };
+    validate_crate_name(new_name).map_err(|e| e.to_string())?;
+    let mut changes = HashMap::with_capacity(members.len() + 1);
+    changes.insert(
+        root.uri.clone(),
+        vec![TextEdit::new(node.range, new_name.to_string())],
+    );
+    for member in members {
+        let edits: Vec<TextEdit> = member
+            .dependencies
+            .values()
+            .filter(|d| !d.is_virtual && d.name == dep.name)
+            .filter(|d| d.workspace.as_ref().is_some_and(|w| *w.value()))
+            .map(|d| TextEdit::new(d.key_range, new_name.to_string()))
+            .collect();
+        if !edits.is_empty() {
+            changes.insert(member.uri.clone(), edits);
+        }
+    }
+    Ok(Some(WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    }))
+}
+
+//the manifests of the members, from the resolved workspace and the `members` paths
+pub fn member_uris(root: &Document) -> Vec<Uri> {
+    let mut uris: Vec<Uri> = root.member_versions.keys().cloned().collect();
+    for (_, _, uri) in root.member_manifest_uris() {
+        if !uris.contains(&uri) {
+            uris.push(uri);
+        }
+    }
+    uris.retain(|uri| *uri != root.uri);
+    uris
+}
+
+fn workspace_dependency<'a>(doc: &'a Document, node: &TomlNode) -> Option<&'a Dependency> {
+    let NodeKind::Key(KeyKind::Dependency(dep_id, DependencyKeyKind::CrateName)) = &node.kind
+    else {
+        return None;
+    };
+    doc.dependency(dep_id).filter(|dep| dep.is_virtual)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tower_lsp::lsp_types::{Position, Uri};
+
+    use super::{member_uris, prepare_rename, rename};
+    use crate::usecase::Document;
+
+    #[test]
+    fn test_rename_workspace_dependency() {
+        let root = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"[workspace]
+members = ["a", "b"]
+
+[workspace.dependencies]
+serde = "1"
+"#,
+        );
+        let a = Document::parse(
+            &Uri::from_str("file:///C:/Users/a/Cargo.toml").unwrap(),
+            r#"[dependencies]
+serde = { workspace = true }
+"#,
+        );
+        let b = Document::parse(
+            &Uri::from_str("file:///C:/Users/b/Cargo.toml").unwrap(),
+            r#"[dependencies]
+tokio = "1"
+
+[dev-dependencies]
+serde = { workspace = true, features = ["derive"] }
+"#,
+        );
+        assert_eq!(member_uris(&root), vec![a.uri.clone(), b.uri.clone()]);
+
+        let node = root.precise_match(Position::new(4, 2)).unwrap();
+        assert_eq!(prepare_rename(&root, &node), Some(node.range));
+        let edit = rename(&root, &node, "serde2", &[&a, &b]).unwrap().unwrap();
+        let changes = edit.changes.unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[&root.uri][0].range, node.range);
+        assert_eq!(
+            changes[&a.uri][0].range,
+            a.dependencies["dependencies.serde"].key_range
+        );
+        assert_eq!(
+            changes[&b.uri][0].range,
+            b.dependencies["dev-dependencies.serde"].key_range
+        );
+        assert!(changes.values().flatten().all(|e| e.new_text == "serde2"));
+
+        assert!(rename(&root, &node, "2serde", &[&a, &b]).is_err());
+        //the version isn't a crate name key
+        let node = root.precise_match(Position::new(4, 9)).unwrap();
+        assert_eq!(prepare_rename(&root, &node), None);
+        assert_eq!(rename(&root, &node, "serde2", &[&a, &b]), Ok(None));
+    }
+}
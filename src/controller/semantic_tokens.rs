@@ -0,0 +1,132 @@
+use tower_lsp::lsp_types::{Range, SemanticToken, SemanticTokenType, SemanticTokensLegend};
+
+use crate::{
+    entity::{DependencyEntryKind, DependencyKeyKind, EntryKind, KeyKind, NodeKind, TomlNode},
+    usecase::Document,
+};
+
+//the index of a token type is the `token_type` sent to the client
+const TOKEN_TYPES: [SemanticTokenType; 4] = [
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::ENUM_MEMBER,
+    SemanticTokenType::STRING,
+];
+
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: vec![],
+    }
+}
+
+//version requirements, crate names, features and git/path sources of dependencies
+//tokens are sorted by position and delta encoded against the previous token
+pub fn semantic_tokens(doc: &Document) -> Vec<SemanticToken> {
+    let mut tokens: Vec<(Range, u32)> = doc
+        .tree
+        .keys
+        .values()
+        .chain(doc.tree.entries.values())
+        //a token can't span lines
+        .filter(|node| node.range.start.line == node.range.end.line)
+        .filter_map(|node| token_type(node).map(|t| (node.range, t)))
+        .collect();
+    tokens.sort_by_key(|(range, _)| (range.start.line, range.start.character));
+
+    let mut res = Vec::with_capacity(tokens.len());
+    let (mut line, mut character) = (0, 0);
+    for (range, token_type) in tokens {
+        let delta_line = range.start.line - line;
+        let delta_start = if delta_line == 0 {
+            range.start.character - character
+        } else {
+            range.start.character
+        };
+        res.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: range.end.character - range.start.character,
+            token_type,
+            token_modifiers_bitset: 0,
+        });
+        line = range.start.line;
+        character = range.start.character;
+    }
+    res
+}
+
+fn token_type(node: &TomlNode) -> Option<u32> {
+    let token_type = match &node.kind {
+        NodeKind::Key(KeyKind::Dependency(_, DependencyKeyKind::CrateName)) => {
+            SemanticTokenType::NAMESPACE
+        }
+        NodeKind::Entry(EntryKind::Dependency(_, kind)) => match kind {
+            DependencyEntryKind::SimpleDependency | DependencyEntryKind::TableDependencyVersion => {
+                SemanticTokenType::NUMBER
+            }
+            DependencyEntryKind::TableDependencyFeature => SemanticTokenType::ENUM_MEMBER,
+            DependencyEntryKind::TableDependencyGit | DependencyEntryKind::TableDependencyPath => {
+                SemanticTokenType::STRING
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+    TOKEN_TYPES
+        .iter()
+        .position(|t| *t == token_type)
+        .map(|i| i as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tower_lsp::lsp_types::Uri;
+
+    use super::semantic_tokens;
+    use crate::usecase::Document;
+
+    #[test]
+    fn test_semantic_tokens() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"[dependencies]
+tokio = "1.40"
+serde = { version = "1", features = ["derive", "rc"] }
+
+[dependencies.foo]
+git = "https://github.com/a/foo"
+version = "0.1"
+"#,
+        );
+        //decode to absolute (line, char, length, type)
+        let (mut line, mut character) = (0, 0);
+        let decoded: Vec<(u32, u32, u32, u32)> = semantic_tokens(&doc)
+            .into_iter()
+            .map(|t| {
+                if t.delta_line > 0 {
+                    character = 0;
+                }
+                line += t.delta_line;
+                character += t.delta_start;
+                (line, character, t.length, t.token_type)
+            })
+            .collect();
+        assert_eq!(
+            decoded,
+            vec![
+                (1, 0, 5, 1),
+                (1, 8, 6, 0),
+                (2, 0, 5, 1),
+                (2, 20, 3, 0),
+                (2, 37, 8, 2),
+                (2, 47, 4, 2),
+                (4, 14, 3, 1),
+                (5, 6, 26, 3),
+                (6, 10, 5, 0),
+            ]
+        );
+    }
+}
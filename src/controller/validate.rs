@@ -0,0 +1,101 @@
+use serde::Serialize;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Range};
+
+use crate::{config::Config, usecase::Document};
+
+use super::lint::lint_dependencies;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    pub id: String,
+    pub range: Range,
+    pub severity: &'static str,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn new(id: String, diag: Diagnostic) -> Self {
+        Finding {
+            id,
+            range: diag.range,
+            severity: severity_label(diag.severity),
+            message: diag.message,
+        }
+    }
+}
+
+fn severity_label(severity: Option<DiagnosticSeverity>) -> &'static str {
+    match severity {
+        Some(DiagnosticSeverity::ERROR) => "error",
+        Some(DiagnosticSeverity::INFORMATION) => "info",
+        Some(DiagnosticSeverity::HINT) => "hint",
+        _ => "warning",
+    }
+}
+
+//run the checks that only need the document, cargo and audit diagnostics are merged by the caller
+pub fn validate_document(doc: &Document, config: &Config) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = doc
+        .parsing_errors
+        .iter()
+        .cloned()
+        .filter_map(|e| e.diagnostic())
+        .chain(lint_dependencies(doc, config))
+        .map(|(id, diag)| Finding::new(id, diag))
+        .collect();
+    sort_findings(&mut findings);
+    findings
+}
+
+//merge diagnostics already published for the document, skipping duplicates
+pub fn merge_findings(findings: &mut Vec<Finding>, diags: Vec<(String, Diagnostic)>) {
+    for (id, diag) in diags {
+        let finding = Finding::new(id, diag);
+        if !findings.contains(&finding) {
+            findings.push(finding);
+        }
+    }
+    sort_findings(findings);
+}
+
+fn sort_findings(findings: &mut [Finding]) {
+    findings.sort_by(|a, b| {
+        (a.range.start.line, a.range.start.character, &a.id).cmp(&(
+            b.range.start.line,
+            b.range.start.character,
+            &b.id,
+        ))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tower_lsp::lsp_types::Uri;
+
+    use super::validate_document;
+    use crate::{config::Config, usecase::Document};
+
+    #[test]
+    fn test_validate_problematic_manifest() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [package]
+            name = "a"
+            rust-version = ">=1.70"
+
+            [dependencies]
+            1serde = "1"
+            "to$kio" = "1"
+            "#,
+        );
+        let findings = validate_document(&doc, &Config::default());
+        assert!(findings.len() >= 3);
+        assert!(findings.iter().all(|f| f.severity == "error"));
+        assert_eq!(findings[0].id, "package.rust-version");
+        assert!(findings[0].range.start.line < findings[1].range.start.line);
+    }
+}
@@ -8,7 +8,10 @@ use tower_lsp::{
 };
 mod vscode;
 
-use crate::entity::{commit_str_short, git_ref_str, Dependency};
+use crate::{
+    config::DecorationConfig,
+    entity::{commit_str_short, git_ref_str, Dependency},
+};
 
 pub mod inlay_hint;
 
@@ -62,6 +65,8 @@ pub enum DecorationEvent {
     DependencyRemove(Uri, String),
     DependencyWaiting(Uri, String, Range),
     Dependency(Uri, String, Range, Dependency),
+    //the package version of a `[workspace] members` path
+    Member(Uri, String, Range, String),
 }
 
 #[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -81,6 +86,8 @@ pub enum VersionDecorationKind {
     NonCompatibleLatest,
     Yanked,
     Git,
+    //the package version of a `[workspace] members` path
+    Member,
 }
 
 #[derive(Debug, Default, Serialize, Clone)]
@@ -93,30 +100,41 @@ pub struct DecorationPayload {
     pub latest_matched: Option<Version>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub latest: Option<Version>,
+    //a pre-release newer than the latest stable version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_prerelease: Option<Version>,
     //(ref,commit)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub git: Option<(String, String)>,
+    //number of versions of the package in the resolve graph
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dup_count: Option<usize>,
+    //a local dependency with `publish = false`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub unpublishable: bool,
+    //`optional = true`, an optional dependency is not installed until a feature enables it
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+}
+
+//a hidden dependency removes its decoration instead
+pub fn dependency_event(uri: Uri, dep: &Dependency) -> DecorationEvent {
+    if dep.hidden {
+        return DecorationEvent::DependencyRemove(uri, dep.id.clone());
+    }
+    DecorationEvent::Dependency(uri, dep.id.clone(), dep.range, dep.clone())
 }
 
 pub fn formatted_string(
     dep: &Dependency,
     formatter: &CompiledFormatter,
 ) -> Option<(VersionDecorationKind, String)> {
-    let version = version_decoration(dep);
-
-    let template = match &version.kind {
-        VersionDecorationKind::Git => &formatter.git,
-        VersionDecorationKind::Latest => &formatter.latest,
-        VersionDecorationKind::Local => &formatter.local,
-        VersionDecorationKind::NotInstalled => &formatter.not_installed,
-        VersionDecorationKind::MixedUpgradeable => &formatter.mixed_upgradeable,
-        VersionDecorationKind::CompatibleLatest => &formatter.compatible_latest,
-        VersionDecorationKind::NonCompatibleLatest => &formatter.noncompatible_latest,
-        VersionDecorationKind::Yanked => &formatter.yanked,
-        VersionDecorationKind::NotParsed => return None,
-    };
+    let mut version = version_decoration(dep);
+    version.dup_count = dep.dup_count;
+    version.optional = dep.is_optional();
 
-    Some((version.kind.clone(), template.format(&version)))
+    let text = formatter.format(&version)?;
+    Some((version.kind.clone(), text))
 }
 
 pub fn version_decoration(dep: &Dependency) -> DecorationPayload {
@@ -135,6 +153,7 @@ pub fn version_decoration(dep: &Dependency) -> DecorationPayload {
     match unresolved.source_id().kind() {
         SourceKind::Path => DecorationPayload {
             kind: VersionDecorationKind::Local,
+            unpublishable: dep.unpublishable,
             ..Default::default()
         },
         //TODO idk what's this
@@ -153,6 +172,8 @@ pub fn version_decoration(dep: &Dependency) -> DecorationPayload {
             };
             DecorationPayload {
                 kind: VersionDecorationKind::Git,
+                //the version of the checked out manifest, usually the one the tag names
+                installed: Some(resolved.version().clone()),
                 git,
                 ..Default::default()
             }
@@ -184,15 +205,22 @@ pub fn version_decoration(dep: &Dependency) -> DecorationPayload {
                     p.installed = Some(matched.version().clone());
                     p.latest = Some(latest.version().clone());
                     p.latest_matched = Some(latest_matched.version().clone());
+                    p.latest_prerelease = dep.newer_prerelease().cloned();
                     p
                 }
-                (None, Some(latest_matched), Some(latest)) => DecorationPayload {
+                (None, latest_matched, Some(latest)) if dep.is_yanked() => DecorationPayload {
                     kind: VersionDecorationKind::Yanked,
                     installed: Some(resolved.version().clone()),
-                    latest_matched: Some(latest_matched.version().clone()),
+                    //no compatible alternative when the yanked version is pinned
+                    latest_matched: latest_matched.map(|s| s.version().clone()),
                     latest: Some(latest.version().clone()),
                     ..Default::default()
                 },
+                //the installed version is missing from the index but not yanked, e.g. a stale index
+                (None, _, _) => DecorationPayload {
+                    kind: VersionDecorationKind::NotParsed,
+                    ..Default::default()
+                },
                 //TODO any other match arm?
                 _ => unreachable!(),
             }
@@ -205,6 +233,8 @@ pub fn version_decoration(dep: &Dependency) -> DecorationPayload {
 /// - installed: the installed version
 /// - latest_matched: the latest compatible version
 /// - latest: the latest version, the latest version may or may not be compatilbe with the version requirement
+/// - latest_prerelease: a pre-release newer than the latest stable version, "none" if there isn't
+/// - unstable: "(0.x)" if the installed version is pre-1.0, where a minor bump is breaking
 /// - git: if the dependency source is git
 ///
 /// the formatter has 7 fields:
@@ -216,63 +246,137 @@ pub fn version_decoration(dep: &Dependency) -> DecorationPayload {
 /// compatible_latest: the installed version can update to latest version
 /// noncompatible_latest: the installed version can't upate to latest version
 /// yanked: the installed version is yanked
-// git: the dependency is a git dependency, support {{ref}}, {{commit}}
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// git: the dependency is a git dependency, support {{installed}}, {{ref}}, {{commit}}
+/// unset fields fall back to the default template of the configured icon set
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct DecorationFormatter {
-    #[serde(default = "default_latest")]
-    pub latest: String,
-    #[serde(default = "default_local")]
-    pub local: String,
-    #[serde(default = "default_not_installed")]
-    pub not_installed: String,
-    #[serde(default = "default_waiting")]
-    pub waiting: String,
-    #[serde(default = "default_mixed_upgradeable")]
-    pub mixed_upgradeable: String,
-    #[serde(default = "default_compatible_latest")]
-    pub compatible_latest: String,
-    #[serde(default = "default_noncompatible_latest")]
-    pub noncompatible_latest: String,
-    #[serde(default = "default_yanked")]
-    pub yanked: String,
-    #[serde(default = "default_git")]
-    pub git: String,
+    pub latest: Option<String>,
+    pub local: Option<String>,
+    pub not_installed: Option<String>,
+    pub waiting: Option<String>,
+    pub mixed_upgradeable: Option<String>,
+    pub compatible_latest: Option<String>,
+    pub noncompatible_latest: Option<String>,
+    pub yanked: Option<String>,
+    pub git: Option<String>,
 }
 
 impl DecorationFormatter {
-    pub fn compile(&self) -> CompiledFormatter {
+    //templates set here win, the rest come from `base`
+    pub fn or(&self, base: &DecorationFormatter) -> DecorationFormatter {
+        let or = |t: &Option<String>, base: &Option<String>| t.clone().or_else(|| base.clone());
+        DecorationFormatter {
+            latest: or(&self.latest, &base.latest),
+            local: or(&self.local, &base.local),
+            not_installed: or(&self.not_installed, &base.not_installed),
+            waiting: or(&self.waiting, &base.waiting),
+            mixed_upgradeable: or(&self.mixed_upgradeable, &base.mixed_upgradeable),
+            compatible_latest: or(&self.compatible_latest, &base.compatible_latest),
+            noncompatible_latest: or(&self.noncompatible_latest, &base.noncompatible_latest),
+            yanked: or(&self.yanked, &base.yanked),
+            git: or(&self.git, &base.git),
+        }
+    }
+
+    pub fn compile(&self, config: &DecorationConfig) -> CompiledFormatter {
+        let defaults = DefaultTemplates::new(config.icon_set);
+        let template = |t: &Option<String>, default: &str| {
+            CompiledTemplate::new(t.clone().unwrap_or_else(|| default.to_string()))
+        };
         CompiledFormatter {
-            waiting: CompiledTemplate::new(self.waiting.clone()),
-            latest: CompiledTemplate::new(self.latest.clone()),
-            local: CompiledTemplate::new(self.local.clone()),
-            not_installed: CompiledTemplate::new(self.not_installed.clone()),
-            mixed_upgradeable: CompiledTemplate::new(self.mixed_upgradeable.clone()),
-            compatible_latest: CompiledTemplate::new(self.compatible_latest.clone()),
-            noncompatible_latest: CompiledTemplate::new(self.noncompatible_latest.clone()),
-            yanked: CompiledTemplate::new(self.yanked.clone()),
-            git: CompiledTemplate::new(self.git.clone()),
+            waiting: template(&self.waiting, defaults.waiting),
+            latest: template(&self.latest, defaults.latest),
+            local: template(&self.local, defaults.local),
+            not_installed: template(&self.not_installed, defaults.not_installed),
+            mixed_upgradeable: template(&self.mixed_upgradeable, defaults.mixed_upgradeable),
+            compatible_latest: template(&self.compatible_latest, defaults.compatible_latest),
+            noncompatible_latest: template(
+                &self.noncompatible_latest,
+                defaults.noncompatible_latest,
+            ),
+            yanked: template(&self.yanked, defaults.yanked),
+            git: template(&self.git, defaults.git),
+            show_duplicates: config.show_duplicates,
+            show_publish_status: config.show_publish_status,
+            show_member_versions: config.show_member_versions,
         }
     }
 }
 
-impl Default for DecorationFormatter {
-    fn default() -> Self {
+/// icons used by the default templates
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IconSet {
+    #[default]
+    Emoji,
+    Nerdfont,
+    Ascii,
+}
+
+struct DefaultTemplates {
+    latest: &'static str,
+    local: &'static str,
+    not_installed: &'static str,
+    waiting: &'static str,
+    mixed_upgradeable: &'static str,
+    compatible_latest: &'static str,
+    noncompatible_latest: &'static str,
+    yanked: &'static str,
+    git: &'static str,
+}
+
+impl DefaultTemplates {
+    fn new(icon_set: IconSet) -> Self {
+        match icon_set {
+            IconSet::Emoji => Self {
+                latest: "✅ {{installed}}",
+                mixed_upgradeable: "🚀🔒 {{installed}} -> {{latest_matched}},  {{latest}}",
+                compatible_latest: "🚀 {{installed}} -> {{latest}}",
+                noncompatible_latest: "🔒 {{installed}}, {{latest}}",
+                yanked: "❌ yanked {{installed}}, {{latest_matched}}",
+                git: "🐙 {{installed}} {{commit}}",
+                ..Self::plain()
+            },
+            IconSet::Nerdfont => Self {
+                latest: "\u{f00c} {{installed}}",
+                mixed_upgradeable: "\u{f135}\u{f023} {{installed}} -> {{latest_matched}},  {{latest}}",
+                compatible_latest: "\u{f135} {{installed}} -> {{latest}}",
+                noncompatible_latest: "\u{f023} {{installed}}, {{latest}}",
+                yanked: "\u{f00d} yanked {{installed}}, {{latest_matched}}",
+                git: "\u{e725} {{installed}} {{commit}}",
+                ..Self::plain()
+            },
+            IconSet::Ascii => Self {
+                latest: "[ok] {{installed}}",
+                mixed_upgradeable: "[^=] {{installed}} -> {{latest_matched}},  {{latest}}",
+                compatible_latest: "[^] {{installed}} -> {{latest}}",
+                noncompatible_latest: "[=] {{installed}}, {{latest}}",
+                yanked: "[x] yanked {{installed}}, {{latest_matched}}",
+                git: "[git] {{installed}} {{commit}}",
+                ..Self::plain()
+            },
+        }
+    }
+
+    //templates without icons
+    fn plain() -> Self {
         Self {
-            latest: default_latest(),
-            compatible_latest: default_compatible_latest(),
-            local: default_local(),
-            noncompatible_latest: default_noncompatible_latest(),
-            not_installed: default_not_installed(),
-            waiting: default_waiting(),
-            mixed_upgradeable: default_mixed_upgradeable(),
-            yanked: default_yanked(),
-            git: default_git(),
+            latest: "{{installed}}",
+            local: "Local",
+            not_installed: "Not installed",
+            waiting: "Waiting...",
+            mixed_upgradeable: "{{installed}} -> {{latest_matched}},  {{latest}}",
+            compatible_latest: "{{installed}} -> {{latest}}",
+            noncompatible_latest: "{{installed}}, {{latest}}",
+            yanked: "yanked {{installed}}, {{latest_matched}}",
+            git: "{{installed}} {{commit}}",
         }
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CompiledFormatter {
     waiting: CompiledTemplate,
     latest: CompiledTemplate,
@@ -283,6 +387,45 @@ pub struct CompiledFormatter {
     noncompatible_latest: CompiledTemplate,
     yanked: CompiledTemplate,
     git: CompiledTemplate,
+    show_duplicates: bool,
+    show_publish_status: bool,
+    show_member_versions: bool,
+}
+
+impl CompiledFormatter {
+    //the version of a workspace member, None when `decoration.showMemberVersions` is off
+    pub fn format_member(&self, version: &str) -> Option<String> {
+        self.show_member_versions.then(|| version.to_string())
+    }
+
+    fn format(&self, version: &DecorationPayload) -> Option<String> {
+        let template = match &version.kind {
+            VersionDecorationKind::Git => &self.git,
+            VersionDecorationKind::Latest => &self.latest,
+            VersionDecorationKind::Local => &self.local,
+            VersionDecorationKind::NotInstalled => &self.not_installed,
+            VersionDecorationKind::MixedUpgradeable => &self.mixed_upgradeable,
+            VersionDecorationKind::CompatibleLatest => &self.compatible_latest,
+            VersionDecorationKind::NonCompatibleLatest => &self.noncompatible_latest,
+            VersionDecorationKind::Yanked => &self.yanked,
+            //member versions are formatted by `format_member`
+            VersionDecorationKind::NotParsed | VersionDecorationKind::Member => return None,
+        };
+        let mut text = template.format(version);
+        if version.optional && version.kind == VersionDecorationKind::NotInstalled {
+            text.push_str(" (optional)");
+        }
+        if self.show_publish_status && version.unpublishable {
+            text.push_str(" (unpublished)");
+        }
+        if let Some(n) = version.dup_count.filter(|n| *n > 1) {
+            //templates with {{dup_count}} place the count themselves
+            if self.show_duplicates && !template.needs_dup_count {
+                text.push_str(&format!(" ({} versions in tree)", n));
+            }
+        }
+        Some(text)
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -291,8 +434,18 @@ struct CompiledTemplate {
     needs_installed: bool,
     needs_latest_matched: bool,
     needs_latest: bool,
+    needs_latest_prerelease: bool,
     needs_git_ref: bool,
     needs_git_commit: bool,
+    needs_dup_count: bool,
+    needs_unstable: bool,
+}
+
+//only the template is meaningful to users
+impl Serialize for CompiledTemplate {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.template)
+    }
 }
 
 impl CompiledTemplate {
@@ -301,8 +454,11 @@ impl CompiledTemplate {
             needs_installed: template.contains("{{installed}}"),
             needs_latest_matched: template.contains("{{latest_matched}}"),
             needs_latest: template.contains("{{latest}}"),
+            needs_latest_prerelease: template.contains("{{latest_prerelease}}"),
             needs_git_ref: template.contains("{{ref}}"),
             needs_git_commit: template.contains("{{commit}}"),
+            needs_dup_count: template.contains("{{dup_count}}"),
+            needs_unstable: template.contains("{{unstable}}"),
             template,
         }
     }
@@ -320,15 +476,41 @@ impl CompiledTemplate {
                 &version.installed.as_ref().unwrap().to_string(),
             );
         }
-        if self.needs_latest_matched && version.latest_matched.is_some() {
+        if self.needs_latest_matched {
+            //a yanked version may have no compatible alternative
             result = result.replace(
                 "{{latest_matched}}",
-                &version.latest_matched.as_ref().unwrap().to_string(),
+                &version
+                    .latest_matched
+                    .as_ref()
+                    .map_or("none".to_string(), |v| v.to_string()),
             );
         }
         if self.needs_latest && version.latest.is_some() {
             result = result.replace("{{latest}}", &version.latest.as_ref().unwrap().to_string());
         }
+        if self.needs_latest_prerelease {
+            //most crates have no pre-release newer than the latest stable version
+            result = result.replace(
+                "{{latest_prerelease}}",
+                &version
+                    .latest_prerelease
+                    .as_ref()
+                    .map_or("none".to_string(), |v| v.to_string()),
+            );
+        }
+        if self.needs_dup_count {
+            result = result.replace(
+                "{{dup_count}}",
+                &version.dup_count.unwrap_or(1).to_string(),
+            );
+        }
+        if self.needs_unstable {
+            let unstable = version.installed.as_ref().is_some_and(|v| v.major == 0);
+            result = result.replace("{{unstable}}", if unstable { "(0.x)" } else { "" });
+            //an empty marker at the end leaves a trailing space
+            result.truncate(result.trim_end().len());
+        }
         if let Some((ref_str, commit)) = version.git.as_ref() {
             if self.needs_git_ref {
                 result = result.replace("{{ref}}", ref_str);
@@ -342,38 +524,167 @@ impl CompiledTemplate {
     }
 }
 
-fn default_latest() -> String {
-    "✅ {{installed}}".to_string()
-}
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
 
-fn default_mixed_upgradeable() -> String {
-    "🚀🔒 {{installed}} -> {{latest_matched}},  {{latest}}".to_string()
-}
+    use tower_lsp::lsp_types::Uri;
 
-fn default_compatible_latest() -> String {
-    "🚀 {{installed}} -> {{latest}}".to_string()
-}
+    use super::{
+        dependency_event, DecorationEvent, DecorationFormatter, DecorationPayload, IconSet,
+        VersionDecorationKind,
+    };
+    use crate::{config::DecorationConfig, usecase::Document};
 
-fn default_noncompatible_latest() -> String {
-    "🔒 {{installed}}, {{latest}}".to_string()
-}
+    fn config(icon_set: IconSet, show_duplicates: bool) -> DecorationConfig {
+        DecorationConfig {
+            icon_set,
+            show_duplicates,
+            ..Default::default()
+        }
+    }
 
-fn default_not_installed() -> String {
-    "Not installed".to_string()
-}
+    #[test]
+    fn test_ascii_icon_set() {
+        let formatter = DecorationFormatter::default().compile(&config(IconSet::Ascii, false));
+        let kinds = [
+            VersionDecorationKind::Latest,
+            VersionDecorationKind::Local,
+            VersionDecorationKind::NotInstalled,
+            VersionDecorationKind::MixedUpgradeable,
+            VersionDecorationKind::CompatibleLatest,
+            VersionDecorationKind::NonCompatibleLatest,
+            VersionDecorationKind::Yanked,
+            VersionDecorationKind::Git,
+        ];
+        for kind in kinds {
+            let payload = DecorationPayload {
+                kind: kind.clone(),
+                ..Default::default()
+            };
+            let text = formatter.format(&payload).unwrap();
+            assert!(text.is_ascii(), "{:?} renders non-ascii text {}", kind, text);
+        }
+    }
 
-fn default_waiting() -> String {
-    "Waiting...".to_string()
-}
+    #[test]
+    fn test_user_template_overrides_icon_set() {
+        let formatter = DecorationFormatter {
+            latest: Some("✅ {{installed}}".to_string()),
+            ..Default::default()
+        }
+        .compile(&config(IconSet::Ascii, false));
+        let payload = DecorationPayload {
+            kind: VersionDecorationKind::Latest,
+            installed: Some(semver::Version::new(1, 0, 0)),
+            ..Default::default()
+        };
+        assert_eq!(formatter.format(&payload).unwrap(), "✅ 1.0.0");
+    }
 
-fn default_local() -> String {
-    "Local".to_string()
-}
+    #[test]
+    fn test_latest_prerelease_token() {
+        let formatter = DecorationFormatter {
+            latest: Some("{{installed}}, {{latest_prerelease}} available".to_string()),
+            ..Default::default()
+        }
+        .compile(&config(IconSet::Ascii, false));
+        let mut payload = DecorationPayload {
+            kind: VersionDecorationKind::Latest,
+            installed: Some(semver::Version::new(1, 2, 0)),
+            latest_prerelease: Some(semver::Version::parse("1.3.0-beta.1").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(formatter.format(&payload).unwrap(), "1.2.0, 1.3.0-beta.1 available");
+        payload.latest_prerelease = None;
+        assert_eq!(formatter.format(&payload).unwrap(), "1.2.0, none available");
+    }
 
-fn default_yanked() -> String {
-    "❌ yanked {{installed}}, {{latest_matched}}".to_string()
-}
+    #[test]
+    fn test_unstable_token() {
+        let formatter = DecorationFormatter {
+            compatible_latest: Some("{{installed}} -> {{latest}} {{unstable}}".to_string()),
+            ..Default::default()
+        }
+        .compile(&config(IconSet::Ascii, false));
+        let mut payload = DecorationPayload {
+            kind: VersionDecorationKind::CompatibleLatest,
+            installed: Some(semver::Version::new(0, 4, 1)),
+            latest: Some(semver::Version::new(0, 4, 3)),
+            ..Default::default()
+        };
+        assert_eq!(formatter.format(&payload).unwrap(), "0.4.1 -> 0.4.3 (0.x)");
+        payload.installed = Some(semver::Version::new(1, 0, 0));
+        payload.latest = Some(semver::Version::new(1, 0, 2));
+        assert_eq!(formatter.format(&payload).unwrap(), "1.0.0 -> 1.0.2");
+    }
+
+    #[test]
+    fn test_duplicate_note() {
+        let payload = DecorationPayload {
+            kind: VersionDecorationKind::Latest,
+            installed: Some(semver::Version::new(2, 0, 0)),
+            dup_count: Some(2),
+            ..Default::default()
+        };
+        let formatter = DecorationFormatter::default().compile(&config(IconSet::Ascii, true));
+        assert_eq!(
+            formatter.format(&payload).unwrap(),
+            "[ok] 2.0.0 (2 versions in tree)"
+        );
+        let formatter = DecorationFormatter::default().compile(&config(IconSet::Ascii, false));
+        assert_eq!(formatter.format(&payload).unwrap(), "[ok] 2.0.0");
+        let formatter = DecorationFormatter {
+            latest: Some("{{installed}} x{{dup_count}}".to_string()),
+            ..Default::default()
+        }
+        .compile(&config(IconSet::Ascii, true));
+        assert_eq!(formatter.format(&payload).unwrap(), "2.0.0 x2");
+    }
+
+    #[test]
+    fn test_optional_not_installed() {
+        let formatter = DecorationFormatter::default().compile(&config(IconSet::Ascii, false));
+        let payload = DecorationPayload {
+            kind: VersionDecorationKind::NotInstalled,
+            optional: true,
+            ..Default::default()
+        };
+        assert!(formatter.format(&payload).unwrap().ends_with(" (optional)"));
+    }
 
-fn default_git() -> String {
-    "🐙 {{commit}}".to_string()
+    #[test]
+    fn test_hidden_dependency_event() {
+        let uri = Uri::from_str("file:///C:/Users/Cargo.toml").unwrap();
+        let doc = Document::parse(
+            &uri,
+            r#"
+            [dependencies]
+            serde = "1" # appraiser: hide
+            tokio = "1"
+            "#,
+        );
+        let serde = doc.dependency("dependencies.serde").unwrap();
+        assert!(matches!(
+            dependency_event(uri.clone(), serde),
+            DecorationEvent::DependencyRemove(_, id) if id == "dependencies.serde"
+        ));
+        let tokio = doc.dependency("dependencies.tokio").unwrap();
+        assert!(matches!(
+            dependency_event(uri, tokio),
+            DecorationEvent::Dependency(..)
+        ));
+    }
+
+    #[test]
+    fn test_git_version() {
+        let formatter = DecorationFormatter::default().compile(&config(IconSet::Ascii, false));
+        let payload = DecorationPayload {
+            kind: VersionDecorationKind::Git,
+            installed: Some(semver::Version::new(1, 2, 3)),
+            git: Some(("v1.2.3".to_string(), "abcdef1".to_string())),
+            ..Default::default()
+        };
+        assert_eq!(formatter.format(&payload).unwrap(), "[git] 1.2.3 abcdef1");
+    }
 }
@@ -8,22 +8,34 @@ use tower_lsp::{
 };
 use tracing::error;
 
-use crate::config::GLOBAL_CONFIG;
+use crate::{
+    config::GLOBAL_CONFIG,
+    entity::{canonical_uri, Dependency},
+};
 
 use super::{formatted_string, DecorationEvent, InlayHintDecorationRenderer};
 
-type InlayHintDecorationState = HashMap<Uri, HashMap<String, InlayHint>>;
+#[derive(Debug, Default)]
+struct InlayHintDecorationState {
+    hints: HashMap<Uri, HashMap<String, InlayHint>>,
+    //hints listed for the editor, built on the first request after the uri's hints change
+    listed: HashMap<Uri, Vec<InlayHint>>,
+}
 
 mod inlay_hint_decoration_state {
+    use parking_lot::RwLockUpgradableReadGuard;
+
     use super::*;
 
     pub fn new() -> Arc<RwLock<InlayHintDecorationState>> {
-        Arc::new(RwLock::new(HashMap::new()))
+        Arc::new(RwLock::new(InlayHintDecorationState::default()))
     }
 
     pub fn upsert(state: &RwLock<InlayHintDecorationState>, uri: &Uri, id: &str, hint: InlayHint) {
+        let uri = &canonical_uri(uri);
         let mut state = state.write();
-        let path_map = state.entry(uri.clone()).or_default();
+        state.listed.remove(uri);
+        let path_map = state.hints.entry(uri.clone()).or_default();
         path_map.insert(id.to_string(), hint);
     }
 
@@ -33,38 +45,88 @@ mod inlay_hint_decoration_state {
         id: &str,
         range: tower_lsp::lsp_types::Range,
     ) {
+        let uri = &canonical_uri(uri);
         let mut state = state.write();
-        if let Some(path_map) = state.get_mut(uri) {
-            if let Some(hint) = path_map.get_mut(id) {
-                hint.position = Position::new(range.end.line, range.end.character);
+        state.listed.remove(uri);
+        if let Some(path_map) = state.hints.get_mut(uri) {
+            let Some(hint) = path_map.get_mut(id) else {
+                return;
+            };
+            let line_delta = range.end.line as i64 - hint.position.line as i64;
+            hint.position = Position::new(range.end.line, range.end.character);
+            //the feature count hint sits on the crate name, move it by the same lines
+            if let Some(hint) = path_map.get_mut(&feature_count_id(id)) {
+                hint.position.line = (hint.position.line as i64 + line_delta).max(0) as u32;
             }
         }
     }
 
     pub fn remove(state: &RwLock<InlayHintDecorationState>, uri: &Uri, id: &str) {
+        let uri = &canonical_uri(uri);
+        let mut state = state.write();
+        state.listed.remove(uri);
+        if let Some(path_map) = state.hints.get_mut(uri) {
+            path_map.remove(id);
+            path_map.remove(&feature_count_id(id));
+        }
+    }
+
+    pub fn remove_one(state: &RwLock<InlayHintDecorationState>, uri: &Uri, id: &str) {
+        let uri = &canonical_uri(uri);
         let mut state = state.write();
-        if let Some(path_map) = state.get_mut(uri) {
+        state.listed.remove(uri);
+        if let Some(path_map) = state.hints.get_mut(uri) {
             path_map.remove(id);
         }
     }
 
     pub fn reset(state: &RwLock<InlayHintDecorationState>, uri: &Uri) {
+        let uri = &canonical_uri(uri);
         let mut state = state.write();
-        state.remove(uri);
+        state.hints.remove(uri);
+        state.listed.remove(uri);
     }
 
+    //editors request hints on every scroll, reuse the list until the hints change
     pub fn list(state: &RwLock<InlayHintDecorationState>, uri: &Uri) -> Vec<InlayHint> {
-        let state = state.read();
-        state
-            .get(uri)
-            .cloned()
-            .unwrap_or_default()
-            .values()
-            .cloned()
-            .collect()
+        let uri = &canonical_uri(uri);
+        let state = state.upgradable_read();
+        if let Some(hints) = state.listed.get(uri) {
+            return hints.clone();
+        }
+        let Some(path_map) = state.hints.get(uri) else {
+            return Vec::new();
+        };
+        let hints: Vec<InlayHint> = path_map.values().cloned().collect();
+        let mut state = RwLockUpgradableReadGuard::upgrade(state);
+        state.listed.insert(uri.clone(), hints.clone());
+        hints
     }
 }
 
+fn feature_count_id(id: &str) -> String {
+    format!("{}.features-count", id)
+}
+
+//`(n features)` after the crate name of a dependency with a features array
+fn feature_count_hint(dep: &Dependency) -> Option<InlayHint> {
+    let count = dep.features.as_ref()?.len();
+    let label = match count {
+        1 => "(1 feature)".to_string(),
+        _ => format!("({} features)", count),
+    };
+    Some(InlayHint {
+        position: Position::new(dep.key_range.end.line, dep.key_range.end.character),
+        label: InlayHintLabel::String(label),
+        kind: None,
+        text_edits: None,
+        tooltip: None,
+        padding_left: Some(true),
+        padding_right: None,
+        data: None,
+    })
+}
+
 #[derive(Debug, Clone)]
 pub struct InlayHintDecoration {
     client: Client,
@@ -101,7 +163,7 @@ impl InlayHintDecoration {
                                 value: GLOBAL_CONFIG
                                     .read()
                                     .unwrap()
-                                    .decoration_formatter
+                                    .inlay_hint_formatter
                                     .waiting
                                     .template()
                                     .to_string(),
@@ -126,7 +188,7 @@ impl InlayHintDecoration {
                     }
                     DecorationEvent::Dependency(path, id, range, p) => {
                         let config = GLOBAL_CONFIG.read().unwrap();
-                        let Some((_, text)) = formatted_string(&p, &config.decoration_formatter)
+                        let Some((_, text)) = formatted_string(&p, &config.inlay_hint_formatter)
                         else {
                             continue;
                         };
@@ -142,10 +204,41 @@ impl InlayHintDecoration {
                             data: None,
                         };
                         inlay_hint_decoration_state::upsert(&state, &path, &id, hint);
+                        let count_id = feature_count_id(&id);
+                        match feature_count_hint(&p) {
+                            Some(hint) if config.inlay_hint.show_feature_count => {
+                                inlay_hint_decoration_state::upsert(&state, &path, &count_id, hint);
+                            }
+                            _ => {
+                                inlay_hint_decoration_state::remove_one(&state, &path, &count_id);
+                            }
+                        }
                     }
                     DecorationEvent::DependencyRangeUpdate(uri, id, range) => {
                         inlay_hint_decoration_state::update_range(&state, &uri, &id, range);
                     }
+                    DecorationEvent::Member(uri, id, range, version) => {
+                        let text = GLOBAL_CONFIG
+                            .read()
+                            .unwrap()
+                            .inlay_hint_formatter
+                            .format_member(&version);
+                        let Some(text) = text else {
+                            inlay_hint_decoration_state::remove_one(&state, &uri, &id);
+                            continue;
+                        };
+                        let hint = InlayHint {
+                            position: Position::new(range.end.line, range.end.character),
+                            label: InlayHintLabel::String(text),
+                            kind: None,
+                            text_edits: None,
+                            tooltip: None,
+                            padding_left: Some(true),
+                            padding_right: None,
+                            data: None,
+                        };
+                        inlay_hint_decoration_state::upsert(&state, &uri, &id, hint);
+                    }
                 }
                 if let Err(e) = client.inlay_hint_refresh().await {
                     error!("inlay hint refresh error: {}", e);
@@ -173,3 +266,89 @@ impl InlayHintDecorationRenderer for InlayHintDecoration {
         inlay_hint_decoration_state::list(&self.hints, uri)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tower_lsp::lsp_types::{InlayHint, InlayHintLabel, Position, Range, Uri};
+
+    use crate::{
+        entity::{into_file_uri, Dependency, Value},
+        fixture::Fixture,
+    };
+
+    use super::{feature_count_hint, inlay_hint_decoration_state};
+
+    #[test]
+    fn test_feature_count_hint() {
+        let features = ["derive", "std", "rc"]
+            .iter()
+            .enumerate()
+            .map(|(i, f)| Value::new(format!("dependencies.serde.features.{}", i), f.to_string()))
+            .collect();
+        let dep = Dependency {
+            key_range: Range::new(Position::new(3, 0), Position::new(3, 5)),
+            features: Some(features),
+            ..Default::default()
+        };
+        let hint = feature_count_hint(&dep).unwrap();
+        assert_eq!(hint.position, Position::new(3, 5));
+        assert_eq!(
+            hint.label,
+            InlayHintLabel::String("(3 features)".to_string())
+        );
+        assert!(feature_count_hint(&Dependency::default()).is_none());
+    }
+
+    #[test]
+    fn test_list_cache() {
+        let state = inlay_hint_decoration_state::new();
+        let uri = Uri::from_str("file:///C:/Users/Cargo.toml").unwrap();
+        let dep = Dependency {
+            key_range: Range::new(Position::new(3, 0), Position::new(3, 5)),
+            features: Some(vec![Value::new(
+                "dependencies.serde.features.0".to_string(),
+                "derive".to_string(),
+            )]),
+            ..Default::default()
+        };
+        let hint = feature_count_hint(&dep).unwrap();
+        inlay_hint_decoration_state::upsert(&state, &uri, "dependencies.serde", hint.clone());
+        for _ in 0..1000 {
+            assert_eq!(inlay_hint_decoration_state::list(&state, &uri).len(), 1);
+        }
+
+        //a change invalidates the list of the uri
+        inlay_hint_decoration_state::upsert(&state, &uri, "dependencies.tokio", hint);
+        assert_eq!(inlay_hint_decoration_state::list(&state, &uri).len(), 2);
+        inlay_hint_decoration_state::remove(&state, &uri, "dependencies.serde");
+        inlay_hint_decoration_state::remove(&state, &uri, "dependencies.tokio");
+        assert!(inlay_hint_decoration_state::list(&state, &uri).is_empty());
+        inlay_hint_decoration_state::reset(&state, &uri);
+        assert!(inlay_hint_decoration_state::list(&state, &uri).is_empty());
+        assert!(state.read().listed.is_empty());
+    }
+
+    #[test]
+    fn test_canonical_uri() {
+        let fixture = Fixture::new(&[("Cargo.toml", "")]);
+        let uri = into_file_uri(&fixture.root().join("Cargo.toml"));
+        let spelled = into_file_uri(&fixture.root().join(".").join("Cargo.toml"));
+        let state = inlay_hint_decoration_state::new();
+        let hint = InlayHint {
+            position: Position::new(0, 0),
+            label: InlayHintLabel::String("1.0.0".to_string()),
+            kind: None,
+            text_edits: None,
+            tooltip: None,
+            padding_left: None,
+            padding_right: None,
+            data: None,
+        };
+        inlay_hint_decoration_state::upsert(&state, &spelled, "dependencies.serde", hint);
+        assert_eq!(inlay_hint_decoration_state::list(&state, &uri).len(), 1);
+        inlay_hint_decoration_state::reset(&state, &uri);
+        assert!(inlay_hint_decoration_state::list(&state, &spelled).is_empty());
+    }
+}
@@ -81,7 +81,7 @@ impl VSCodeDecoration {
                         let text = GLOBAL_CONFIG
                             .read()
                             .unwrap()
-                            .decoration_formatter
+                            .vscode_formatter
                             .waiting
                             .template()
                             .to_string();
@@ -130,7 +130,7 @@ impl VSCodeDecoration {
                     DecorationEvent::Dependency(uri, id, range, p) => {
                         let decoration = {
                             let config = GLOBAL_CONFIG.read().unwrap();
-                            formatted_string(&p, &config.decoration_formatter)
+                            formatted_string(&p, &config.vscode_formatter)
                         };
                         let Some((kind, text)) = decoration else {
                             continue;
@@ -148,6 +148,37 @@ impl VSCodeDecoration {
                             error!("reset decoration error: {}", err);
                         }
                     }
+                    DecorationEvent::Member(uri, id, range, version) => {
+                        let text = GLOBAL_CONFIG
+                            .read()
+                            .unwrap()
+                            .vscode_formatter
+                            .format_member(&version);
+                        let Some(text) = text else {
+                            if let Err(err) = client
+                                .send_request::<DeleteDecorationRequest>(DeleteDecorationRequest {
+                                    uri,
+                                    id,
+                                })
+                                .await
+                            {
+                                error!("delete decoration error: {}", err);
+                            }
+                            continue;
+                        };
+                        if let Err(err) = client
+                            .send_request::<CreateDecorationRequest>(CreateDecorationRequest {
+                                uri,
+                                id,
+                                text,
+                                kind: VersionDecorationKind::Member,
+                                range,
+                            })
+                            .await
+                        {
+                            error!("create decoration error: {}", err);
+                        }
+                    }
                 }
             }
         });
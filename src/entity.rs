@@ -3,11 +3,13 @@ mod command;
 mod dependency;
 mod entry;
 mod key;
+mod lints;
 mod manifest;
 mod node;
 mod package;
 mod profile;
 mod table;
+mod target;
 mod toml_error;
 mod tree;
 mod uri;
@@ -19,10 +21,12 @@ pub use command::*;
 pub use dependency::*;
 pub use entry::*;
 pub use key::*;
+pub use lints::*;
 pub use manifest::*;
 pub use node::*;
 pub use package::*;
 pub use table::*;
+pub use target::*;
 pub use toml_error::*;
 pub use tree::*;
 pub use uri::*;
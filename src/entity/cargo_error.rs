@@ -36,6 +36,7 @@ impl CargoError {
             CargoErrorKind::NoMatchingPackage(name) => Some(name),
             CargoErrorKind::VersionNotFound(name, _) => Some(name),
             CargoErrorKind::FailedToSelectVersion(name) => Some(name),
+            CargoErrorKind::AuthFailed(name, _) => Some(name),
             CargoErrorKind::CyclicDependency => None,
             CargoErrorKind::WorkspaceError => None,
             CargoErrorKind::ResolveError => None,
@@ -51,6 +52,11 @@ pub enum CargoErrorKind {
     VersionNotFound(String, String),
     #[error("failed to select a version for `{0}`")]
     FailedToSelectVersion(String),
+    #[error(
+        "authentication failed for registry `{1}`, set a token with `cargo login --registry {1}` \
+         or the `CARGO_REGISTRIES_<NAME>_TOKEN` environment variable"
+    )]
+    AuthFailed(String, String),
     #[error("cyclic dependency detected")]
     CyclicDependency,
     #[error("unparsed workspace error")]
@@ -62,6 +68,21 @@ pub enum CargoErrorKind {
 pub fn from_resolve_error(e: anyhow::Error) -> CargoError {
     let error_message = e.to_string();
 
+    // failed to get `private-crate` as a dependency of package `hello-rust v0.1.0 (...)`
+    // Caused by:
+    //   token rejected for `mycorp`, please run `cargo login --registry mycorp`
+    if let Some(registry) = auth_failed_registry(&e) {
+        if let Some(package_name) = error_message
+            .strip_prefix("failed to get")
+            .and_then(|s| s.split('`').nth(1))
+        {
+            return CargoError {
+                kind: CargoErrorKind::AuthFailed(package_name.to_string(), registry),
+                source: e,
+            };
+        }
+    }
+
     // no matching package named `aserde` found
     // location searched: registry `crates-io`
     // required by package `hello-rust v0.1.0 (/Users/jingyu/tmp/hello-rust)`
@@ -140,3 +161,56 @@ pub fn from_resolve_error(e: anyhow::Error) -> CargoError {
         source: e,
     }
 }
+
+//the registry name, or the index url if only the http status is known,
+//of a registry rejecting the request for missing or invalid credentials
+fn auth_failed_registry(e: &anyhow::Error) -> Option<String> {
+    e.chain().find_map(|cause| {
+        let message = cause.to_string();
+        if message.starts_with("token rejected for")
+            || message.starts_with("no token found for")
+            || message.ends_with("got 401")
+            || message.ends_with("got 403")
+        {
+            return message.split('`').nth(1).map(|s| s.to_string());
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_resolve_error, CargoErrorKind};
+
+    #[test]
+    fn test_auth_failed() {
+        //the error chain cargo reports for an alternative registry without credentials
+        let e = anyhow::anyhow!(
+            "no token found for `mycorp`, please run `cargo login --registry mycorp`"
+        )
+        .context("failed to query replaced source registry `mycorp`")
+        .context("failed to get `private-crate` as a dependency of package `hello v0.1.0`");
+        let err = from_resolve_error(e);
+        assert!(matches!(
+            &err.kind,
+            CargoErrorKind::AuthFailed(name, registry)
+                if name == "private-crate" && registry == "mycorp"
+        ));
+        assert_eq!(err.crate_name(), Some("private-crate"));
+        assert!(err.to_string().contains("cargo login --registry mycorp"));
+
+        let e = anyhow::anyhow!(
+            "failed to get successful HTTP response from `https://mycorp.example/config.json`, \
+             got 401"
+        )
+        .context("failed to get `private-crate` as a dependency of package `hello v0.1.0`");
+        assert!(matches!(
+            from_resolve_error(e).kind,
+            CargoErrorKind::AuthFailed(_, registry)
+                if registry == "https://mycorp.example/config.json"
+        ));
+
+        let e = anyhow::anyhow!("failed to get `serde` as a dependency of package `hello v0.1.0`");
+        assert!(matches!(from_resolve_error(e).kind, CargoErrorKind::ResolveError));
+    }
+}
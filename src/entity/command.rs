@@ -1,5 +1,21 @@
 pub const CARGO: &str = "cargo";
+pub const OUTDATED: &str = "cargo-appraiser/outdated";
+pub const UPGRADE_SUMMARY: &str = "cargo-appraiser/upgradeSummary";
+pub const FEATURE_IMPACT: &str = "cargo-appraiser/featureImpact";
+pub const VALIDATE: &str = "cargo-appraiser/validate";
+pub const CONFIG: &str = "cargo-appraiser/config";
+pub const RESOLVED_MANIFEST: &str = "cargo-appraiser/resolvedManifest";
+pub const AUDIT_FIX: &str = "cargo-appraiser/auditFix";
 
 pub fn supported_commands() -> Vec<String> {
-    vec![CARGO.to_string()]
+    vec![
+        CARGO.to_string(),
+        OUTDATED.to_string(),
+        UPGRADE_SUMMARY.to_string(),
+        FEATURE_IMPACT.to_string(),
+        VALIDATE.to_string(),
+        CONFIG.to_string(),
+        RESOLVED_MANIFEST.to_string(),
+        AUDIT_FIX.to_string(),
+    ]
 }
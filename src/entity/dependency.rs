@@ -1,4 +1,5 @@
-use cargo::core::Summary;
+use cargo::{core::Summary, util::OptVersionReq};
+use semver::Version;
 use tower_lsp::lsp_types::Range;
 
 use super::{DependencyTable, Value};
@@ -7,6 +8,8 @@ use super::{DependencyTable, Value};
 pub struct Dependency {
     pub id: String,
     pub range: Range,
+    //the crate name key range
+    pub key_range: Range,
     pub name: String,
     pub table: DependencyTable,
     pub version: Option<Value<String>>,
@@ -19,7 +22,16 @@ pub struct Dependency {
     pub rev: Option<Value<String>>,
     pub package: Option<Value<String>>,
     pub workspace: Option<Value<bool>>,
+    pub default_features: Option<Value<bool>>,
+    pub optional: Option<Value<bool>>,
+    //artifact dependency, `artifact = "bin"` or `artifact = ["bin", "cdylib"]`
+    pub artifact: Option<Vec<Value<String>>>,
+    pub lib: Option<Value<bool>>,
+    //the artifact target triple
+    pub target: Option<Value<String>>,
     pub platform: Option<String>,
+    //the registry of a `[patch.<registry>]` dependency
+    pub patch_registry: Option<String>,
     pub unresolved: Option<cargo::core::Dependency>,
     pub resolved: Option<cargo::core::package::Package>,
     pub summaries: Option<Vec<Summary>>,
@@ -29,6 +41,24 @@ pub struct Dependency {
     pub latest_summary: Option<Summary>,
     //the latest summary that satisify the version requirement
     pub latest_matched_summary: Option<Summary>,
+    //the latest stable summary, whatever the installed version is
+    pub latest_stable_summary: Option<Summary>,
+    //the latest pre-release summary, whatever the installed version is
+    pub latest_prerelease_summary: Option<Summary>,
+    //the summaries are read from the local index cache, only the versions are known
+    pub summaries_from_cache: bool,
+    //yanked versions known to the registry, None if not queried
+    pub yanked_versions: Option<Vec<Version>>,
+    //features activated after unification, including the ones other dependents enable
+    pub enabled_features: Vec<String>,
+    //the source `[source] replace-with` in `.cargo/config.toml` queries instead, e.g. a vendor dir
+    pub replaced_source: Option<cargo::core::SourceId>,
+    //number of versions of the package in the resolve graph, set when more than one
+    pub dup_count: Option<usize>,
+    //a path dependency to a crate with `publish = false`
+    pub unpublishable: bool,
+    //`# appraiser: hide`, no decoration or diagnostic for this dependency
+    pub hidden: bool,
     pub is_virtual: bool,
 }
 
@@ -41,12 +71,28 @@ impl Dependency {
     }
 
     pub fn toml_key(&self) -> String {
-        let platform = self.platform.as_deref().unwrap_or_default();
+        let platform = self
+            .platform
+            .as_deref()
+            .map(normalize_platform)
+            .unwrap_or_default();
         format!("{}:{}:{}", self.table, self.name, platform)
     }
 
+    //the summary listing the features of the installed version,
+    //the summaries from the local index cache have no features
+    pub fn feature_summary(&self) -> Option<&Summary> {
+        if let Some(resolved) = self.resolved.as_ref() {
+            return Some(resolved.summary());
+        }
+        self.matched_summary
+            .as_ref()
+            .filter(|_| !self.summaries_from_cache)
+    }
+
     pub fn merge_range(&mut self, dep: Dependency) {
         self.range = dep.range;
+        self.key_range = dep.key_range;
         self.version = dep.version;
         self.features = dep.features;
         self.registry = dep.registry;
@@ -57,8 +103,84 @@ impl Dependency {
         self.rev = dep.rev;
         self.package = dep.package;
         self.workspace = dep.workspace;
+        self.default_features = dep.default_features;
+        self.optional = dep.optional;
+        self.artifact = dep.artifact;
+        self.lib = dep.lib;
+        self.target = dep.target;
+        self.hidden = dep.hidden;
         self.platform = dep.platform;
     }
+
+    //a pre-release newer than every stable version, e.g. 1.3.0-beta.1 over 1.2.0
+    pub fn newer_prerelease(&self) -> Option<&Version> {
+        let prerelease = self.latest_prerelease_summary.as_ref()?.version();
+        match self.latest_stable_summary.as_ref() {
+            Some(stable) if stable.version() > prerelease => None,
+            _ => Some(prerelease),
+        }
+    }
+
+    //the installed version is yanked, fall back to its absence from the summaries
+    pub fn is_yanked(&self) -> bool {
+        let Some(resolved) = self.resolved.as_ref() else {
+            return false;
+        };
+        match self.yanked_versions.as_ref() {
+            Some(yanked) => yanked.contains(resolved.version()),
+            None => self.summaries.is_some() && self.matched_summary.is_none(),
+        }
+    }
+
+    //the `rust-version` of the resolved package
+    pub fn minimum_rust_version(&self) -> Option<String> {
+        summary_rust_version(self.resolved.as_ref()?.summary())
+    }
+
+    pub fn is_optional(&self) -> bool {
+        self.optional.as_ref().is_some_and(|v| *v.value())
+    }
+
+    //`default-features = false` and no explicit features, the crate is built with zero features
+    pub fn has_no_features_enabled(&self) -> bool {
+        let defaults_off = self
+            .default_features
+            .as_ref()
+            .is_some_and(|v| !*v.value());
+        let no_features = self.features.as_ref().map_or(true, |f| f.is_empty());
+        defaults_off && no_features
+    }
+
+    //keys written in the dependency table
+    pub fn table_keys(&self) -> Vec<&'static str> {
+        let mut keys = Vec::with_capacity(12);
+        let fields = [
+            ("version", self.version.is_some()),
+            ("features", self.features.is_some()),
+            ("default-features", self.default_features.is_some()),
+            ("optional", self.optional.is_some()),
+            ("registry", self.registry.is_some()),
+            ("git", self.git.is_some()),
+            ("branch", self.branch.is_some()),
+            ("tag", self.tag.is_some()),
+            ("path", self.path.is_some()),
+            ("rev", self.rev.is_some()),
+            ("package", self.package.is_some()),
+            ("workspace", self.workspace.is_some()),
+        ];
+        for (key, present) in fields {
+            if present {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+}
+
+//a local package with `publish = false` or `publish = []`
+pub fn is_unpublishable(package: &cargo::core::package::Package) -> bool {
+    package.package_id().source_id().is_path()
+        && package.publish().as_ref().is_some_and(|r| r.is_empty())
 }
 
 pub fn cargo_dependency_to_toml_key(dep: &cargo::core::Dependency) -> String {
@@ -73,3 +195,250 @@ pub fn cargo_dependency_to_toml_key(dep: &cargo::core::Dependency) -> String {
         platform
     )
 }
+
+//format a target platform the way cargo displays `dep.platform()`
+//so `cfg(any(unix,target_arch="wasm32"))` in Cargo.toml matches `cfg(any(unix, target_arch = "wasm32"))`
+pub fn normalize_platform(platform: &str) -> String {
+    let platform = platform.trim();
+    if !platform.starts_with("cfg(") {
+        return platform.to_string();
+    }
+    let mut tokens = Vec::new();
+    let mut chars = platform.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' | ')' | ',' | '=' => tokens.push(c.to_string()),
+            '"' => {
+                let mut s = String::from('"');
+                for c in chars.by_ref() {
+                    s.push(c);
+                    if c == '"' {
+                        break;
+                    }
+                }
+                tokens.push(s);
+            }
+            c => {
+                let mut s = String::from(c);
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | ',' | '=' | '"') {
+                        break;
+                    }
+                    s.push(c);
+                    chars.next();
+                }
+                tokens.push(s);
+            }
+        }
+    }
+    let mut res = String::with_capacity(platform.len());
+    for (i, token) in tokens.iter().enumerate() {
+        match token.as_str() {
+            //cargo drops trailing commas
+            "," if tokens.get(i + 1).map(|t| t.as_str()) == Some(")") => {}
+            "," => res.push_str(", "),
+            "=" => res.push_str(" = "),
+            t => res.push_str(t),
+        }
+    }
+    res
+}
+
+pub struct MatchedVersions<T> {
+    //the installed version
+    pub installed: Option<T>,
+    //the latest version with the same pre-release-ness as the installed version
+    pub latest: Option<T>,
+    //the latest version that satisfies every comparator of the requirement
+    pub latest_matched: Option<T>,
+    pub latest_stable: Option<T>,
+    pub latest_prerelease: Option<T>,
+}
+
+//candidates must be sorted in descending order
+pub fn match_versions<T: Clone>(
+    candidates: &[T],
+    version: impl Fn(&T) -> &Version,
+    installed: &Version,
+    req: &OptVersionReq,
+) -> MatchedVersions<T> {
+    let mut matched = MatchedVersions {
+        installed: None,
+        latest: None,
+        latest_matched: None,
+        latest_stable: None,
+        latest_prerelease: None,
+    };
+    for c in candidates {
+        if matched.installed.is_some()
+            && matched.latest_matched.is_some()
+            && matched.latest_stable.is_some()
+            && matched.latest_prerelease.is_some()
+        {
+            break;
+        }
+        let v = version(c);
+        if installed == v {
+            matched.installed = Some(c.clone());
+        }
+        if matched.latest_matched.is_none() && req.matches(v) {
+            matched.latest_matched = Some(c.clone());
+        }
+        match v.is_prerelease() {
+            true if matched.latest_prerelease.is_none() => {
+                matched.latest_prerelease = Some(c.clone())
+            }
+            false if matched.latest_stable.is_none() => matched.latest_stable = Some(c.clone()),
+            _ => {}
+        }
+    }
+    //the latest version keeps the pre-release-ness of the installed version
+    matched.latest = match installed.is_prerelease() {
+        true => matched.latest_prerelease.clone(),
+        false => matched.latest_stable.clone(),
+    };
+    matched
+}
+
+pub fn summary_rust_version(summary: &Summary) -> Option<String> {
+    summary.rust_version().map(|v| v.to_string())
+}
+
+//`rust-version` is a bare version with optional minor and patch, e.g. "1.70"
+pub fn exceeds_rust_version(required: &str, declared: &str) -> bool {
+    let parse = |v: &str| -> Option<Version> {
+        let mut parts = v.trim().split('.').map(|p| p.parse::<u64>().ok());
+        Some(Version::new(
+            parts.next()??,
+            parts.next().unwrap_or(Some(0))?,
+            parts.next().unwrap_or(Some(0))?,
+        ))
+    };
+    match (parse(required), parse(declared)) {
+        (Some(required), Some(declared)) => required > declared,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use cargo::{
+        core::{PackageId, SourceId, Summary},
+        util::{IntoUrl, OptVersionReq},
+    };
+    use semver::{Version, VersionReq};
+
+    use super::{
+        exceeds_rust_version, match_versions, normalize_platform, summary_rust_version,
+        Dependency,
+    };
+    use crate::entity::DependencyTable;
+
+    #[test]
+    fn test_minimum_rust_version() {
+        let source_id =
+            SourceId::for_registry(&"https://example.com/index".into_url().unwrap()).unwrap();
+        let id = PackageId::try_new("foo", "1.0.0", source_id).unwrap();
+        let summary = Summary::new(
+            id,
+            vec![],
+            &BTreeMap::new(),
+            None::<&str>,
+            Some("1.75".parse().unwrap()),
+        )
+        .unwrap();
+        let required = summary_rust_version(&summary).unwrap();
+        assert_eq!(required, "1.75");
+        assert!(exceeds_rust_version(&required, "1.70"));
+        assert!(!exceeds_rust_version(&required, "1.75.0"));
+        assert!(!exceeds_rust_version(&required, "1.80"));
+    }
+
+    #[test]
+    fn test_normalize_platform() {
+        assert_eq!(
+            normalize_platform(r#"cfg(any(unix,target_arch="wasm32"))"#),
+            r#"cfg(any(unix, target_arch = "wasm32"))"#
+        );
+        assert_eq!(
+            normalize_platform(r#"cfg( all( windows , not(target_env = "msvc"), ) )"#),
+            r#"cfg(all(windows, not(target_env = "msvc")))"#
+        );
+        assert_eq!(
+            normalize_platform("x86_64-pc-windows-gnu"),
+            "x86_64-pc-windows-gnu"
+        );
+    }
+
+    #[test]
+    fn test_target_dependency_toml_key() {
+        let dep = Dependency {
+            name: "a".to_string(),
+            table: DependencyTable::Dependencies,
+            platform: Some(r#"cfg(any(unix,target_arch="wasm32"))"#.to_string()),
+            ..Default::default()
+        };
+        //cargo_dependency_to_toml_key uses cargo's platform display
+        assert_eq!(
+            dep.toml_key(),
+            r#"dependencies:a:cfg(any(unix, target_arch = "wasm32"))"#
+        );
+    }
+
+    fn versions(vs: &[&str]) -> Vec<Version> {
+        vs.iter().map(|v| Version::parse(v).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_match_versions_multi_comparator() {
+        let candidates = versions(&["2.1.0", "2.0.0", "1.9.3", "1.2.0", "1.1.0"]);
+        let installed = Version::parse("1.2.0").unwrap();
+        let req = OptVersionReq::Req(VersionReq::parse(">=1.2, <2").unwrap());
+        let matched = match_versions(&candidates, |v| v, &installed, &req);
+        assert_eq!(matched.installed, Some(Version::parse("1.2.0").unwrap()));
+        assert_eq!(matched.latest, Some(Version::parse("2.1.0").unwrap()));
+        assert_eq!(
+            matched.latest_matched,
+            Some(Version::parse("1.9.3").unwrap())
+        );
+
+        let req = OptVersionReq::Req(VersionReq::parse(">1.1, <1.9").unwrap());
+        let matched = match_versions(&candidates, |v| v, &installed, &req);
+        assert_eq!(
+            matched.latest_matched,
+            Some(Version::parse("1.2.0").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_match_versions_stable_and_prerelease() {
+        let candidates = versions(&["1.3.0-beta.2", "1.3.0-beta.1", "1.2.0", "1.1.0"]);
+        let req = OptVersionReq::Req(VersionReq::parse("1.1").unwrap());
+        let installed = Version::parse("1.1.0").unwrap();
+        let matched = match_versions(&candidates, |v| v, &installed, &req);
+        assert_eq!(matched.latest_stable, Some(Version::parse("1.2.0").unwrap()));
+        assert_eq!(
+            matched.latest_prerelease,
+            Some(Version::parse("1.3.0-beta.2").unwrap())
+        );
+        //`latest` is the latest stable version for a stable install
+        assert_eq!(matched.latest, matched.latest_stable);
+
+        let installed = Version::parse("1.3.0-beta.1").unwrap();
+        let matched = match_versions(&candidates, |v| v, &installed, &req);
+        assert_eq!(matched.latest, matched.latest_prerelease);
+        assert_eq!(matched.latest_stable, Some(Version::parse("1.2.0").unwrap()));
+
+        let candidates = versions(&["1.2.0", "1.2.0-rc.1", "1.1.0"]);
+        let matched = match_versions(&candidates, |v| v, &installed, &req);
+        assert_eq!(
+            matched.latest_prerelease,
+            Some(Version::parse("1.2.0-rc.1").unwrap())
+        );
+        let matched = match_versions(&versions(&["1.2.0"]), |v| v, &installed, &req);
+        assert!(matched.latest_prerelease.is_none());
+    }
+}
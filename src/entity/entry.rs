@@ -35,6 +35,8 @@ impl TomlEntry {}
 #[derive(Debug, Serialize, Clone, PartialEq, Eq)]
 pub enum EntryKind {
     Workspace(WorkspaceEntryKind),
+    Package(PackageEntryKind),
+    Lint(LintEntryKind),
     Table(CargoTable),
     Dependency(String, DependencyEntryKind),
     Value(String),
@@ -69,12 +71,30 @@ pub enum DependencyEntryKind {
     TableDependencyWorkspace,
     TableDependencyDefaultFeatures,
     TableDependencyOptional,
+    TableDependencyArtifact,
+    TableDependencyLib,
+    TableDependencyTarget,
     TableDependencyUnknownBool,
 }
 
 #[derive(Debug, Serialize, Clone, PartialEq, Eq)]
 pub enum WorkspaceEntryKind {
     Members,
+    Resolver,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub enum PackageEntryKind {
+    RustVersion,
+    Resolver,
+    Publish,
+}
+
+//the level is the whole entry of `unsafe_code = "forbid"`
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub enum LintEntryKind {
+    Level,
+    Priority,
 }
 
 pub fn strip_quotes(s: &str) -> String {
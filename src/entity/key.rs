@@ -15,6 +15,9 @@ pub struct TomlKey {
 pub enum KeyKind {
     Workspace(WorkspaceKeyKind),
     Dependency(String, DependencyKeyKind),
+    Package(PackageKeyKind),
+    Lint(LintKeyKind),
+    Target(TargetKeyKind),
 }
 
 impl KeyKind {
@@ -22,6 +25,9 @@ impl KeyKind {
         match self {
             KeyKind::Dependency(id, _) => Some(id.to_string()),
             KeyKind::Workspace(_) => None,
+            KeyKind::Package(_) => None,
+            KeyKind::Lint(_) => None,
+            KeyKind::Target(_) => None,
         }
     }
 }
@@ -37,4 +43,29 @@ pub enum DependencyKeyKind {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum WorkspaceKeyKind {
     Members,
+    Resolver,
+}
+
+//`[lints.<tool>]` keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintKeyKind {
+    Tool,
+    Lint,
+}
+
+//keys of a `[[bin]]`, `[[example]]`, `[[test]]` or `[[bench]]` element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetKeyKind {
+    Name,
+    RequiredFeatures,
+    Harness,
+    //`path`, `test`, `doc` and the other target settings
+    Setting,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageKeyKind {
+    RustVersion,
+    Resolver,
+    Publish,
 }
@@ -0,0 +1,13 @@
+use super::Value;
+
+//a lint in `[lints.<tool>]` or `[workspace.lints.<tool>]`,
+//either `unsafe_code = "forbid"` or `all = { level = "warn", priority = -1 }`
+#[derive(Default, Debug, Clone)]
+pub struct Lint {
+    //e.g. `lints.rust.unsafe_code`
+    pub id: String,
+    pub tool: String,
+    pub name: String,
+    pub level: Option<Value<String>>,
+    pub priority: Option<Value<i64>>,
+}
@@ -1,5 +1,10 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use tower_lsp::lsp_types::Range;
+
 use super::{
-    package::Package, profile::Profile, workspace::Workspace, TomlEntry, TomlKey, TomlNode,
+    package::Package, profile::Profile, workspace::Workspace, Lint, Target, TomlEntry, TomlKey,
+    TomlNode, Value,
 };
 
 #[derive(Default, Debug, Clone)]
@@ -7,4 +12,101 @@ pub struct Manifest {
     pub package: Package,
     pub profile: Option<Vec<Profile>>,
     pub workspace: Option<Workspace>,
+    //the `[features]` table, feature name to its values
+    pub features: BTreeMap<String, Vec<Value<String>>>,
+    //the `[lints]` table
+    pub lints: Vec<Lint>,
+    //`[[bin]]`, `[[example]]`, `[[test]]` and `[[bench]]` in document order
+    pub targets: Vec<Target>,
+    //the key range of top level tables, e.g. the `[dependencies]` header
+    pub headers: HashMap<String, Range>,
+}
+
+impl Manifest {
+    //the workspace resolver takes precedence over the package resolver
+    pub fn resolver(&self) -> Option<&str> {
+        self.workspace
+            .as_ref()
+            .and_then(|w| w.resolver.as_ref())
+            .or(self.package.resolver.as_ref())
+            .map(|v| v.value().as_str())
+    }
+
+    pub fn is_publishable(&self) -> bool {
+        self.package.publish.as_ref().map_or(true, |v| *v.value())
+    }
+
+    //features which enable the optional dependency, `dep:name` or `name/feature`
+    //`name?/feature` only enables a feature if the dependency is already enabled
+    //without any `dep:name`, the implicit feature `name` enables it
+    pub fn enabling_features(&self, dep_name: &str) -> Vec<&str> {
+        let dep_prefix = format!("dep:{}", dep_name);
+        let feature_prefix = format!("{}/", dep_name);
+        let mut res: Vec<&str> = self
+            .features
+            .iter()
+            .filter(|(_, values)| {
+                values.iter().any(|v| {
+                    let v = v.value();
+                    *v == dep_prefix || *v == dep_name || v.starts_with(&feature_prefix)
+                })
+            })
+            .map(|(name, _)| name.as_str())
+            .collect();
+        let explicit = self
+            .features
+            .values()
+            .flatten()
+            .any(|v| *v.value() == dep_prefix);
+        if !explicit && !res.contains(&dep_name) {
+            res.push(dep_name);
+        }
+        res
+    }
+
+    //features that enable themselves through other features, e.g. `a = ["b"]`, `b = ["a"]`
+    pub fn cyclic_features(&self) -> BTreeSet<&str> {
+        let mut cyclic = BTreeSet::new();
+        for start in self.features.keys() {
+            let mut visited = HashSet::new();
+            let mut stack = vec![start.as_str()];
+            'search: while let Some(name) = stack.pop() {
+                for next in self.enabled_features_of(name) {
+                    if next == start {
+                        cyclic.insert(start.as_str());
+                        break 'search;
+                    }
+                    if visited.insert(next) {
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        cyclic
+    }
+
+    //`default` and the features it enables, directly or through other features
+    pub fn default_features(&self) -> BTreeSet<&str> {
+        let mut enabled = BTreeSet::new();
+        if !self.features.contains_key("default") {
+            return enabled;
+        }
+        let mut stack = vec!["default"];
+        while let Some(name) = stack.pop() {
+            if enabled.insert(name) {
+                stack.extend(self.enabled_features_of(name));
+            }
+        }
+        enabled
+    }
+
+    //the features of this package enabled by `name`, `dep:x` and `x/y` are not features
+    fn enabled_features_of(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.features
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|v| v.value().as_str())
+            .filter(|v| self.features.contains_key(*v))
+    }
 }
@@ -40,6 +40,27 @@ impl TomlNode {
         }
     }
 
+    pub fn is_path_entry(&self) -> bool {
+        matches!(
+            self.kind,
+            NodeKind::Entry(EntryKind::Dependency(
+                _,
+                DependencyEntryKind::TableDependencyPath
+            ))
+        )
+    }
+
+    //the version requirement of a simple or table dependency
+    pub fn is_version_entry(&self) -> bool {
+        matches!(
+            self.kind,
+            NodeKind::Entry(EntryKind::Dependency(
+                _,
+                DependencyEntryKind::SimpleDependency | DependencyEntryKind::TableDependencyVersion
+            ))
+        )
+    }
+
     pub fn crate_name(&self) -> Option<String> {
         let NodeKind::Key(KeyKind::Dependency(_, DependencyKeyKind::CrateName)) = self.kind else {
             return None;
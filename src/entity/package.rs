@@ -16,6 +16,10 @@ pub struct Package {
     documentation: Option<Value<String>>,
     readme: Option<Value<String>>,
     workspace: Option<Value<String>>,
+    pub rust_version: Option<Value<String>>,
+    pub resolver: Option<Value<String>>,
+    //`publish = false` or `publish = []`
+    pub publish: Option<Value<bool>>,
 }
 
 pub fn git_ref_str(source_id: &SourceId) -> Option<String> {
@@ -34,6 +34,10 @@ pub enum DependencyTable {
     DevDependencies,
     BuildDependencies,
     WorkspaceDependencies,
+    //`[patch.<registry>]`
+    Patch,
+    //`[replace]`
+    Replace,
 }
 
 impl Display for DependencyTable {
@@ -46,6 +50,8 @@ impl Display for DependencyTable {
                 DependencyTable::DevDependencies => "dev-dependencies",
                 DependencyTable::BuildDependencies => "build-dependencies",
                 DependencyTable::WorkspaceDependencies => "workspace-dependencies",
+                DependencyTable::Patch => "patch",
+                DependencyTable::Replace => "replace",
             }
         )
     }
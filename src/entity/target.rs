@@ -0,0 +1,24 @@
+use super::{CargoTable, Value};
+
+//an element of `[[bin]]`, `[[example]]`, `[[test]]` or `[[bench]]`
+#[derive(Debug, Clone)]
+pub struct Target {
+    //e.g. `bin.0`
+    pub id: String,
+    pub table: CargoTable,
+    pub name: Option<Value<String>>,
+    pub required_features: Vec<Value<String>>,
+    pub harness: Option<Value<bool>>,
+}
+
+impl Target {
+    pub fn new(id: String, table: CargoTable) -> Self {
+        Self {
+            id,
+            table,
+            name: None,
+            required_features: Vec::new(),
+            harness: None,
+        }
+    }
+}
@@ -22,51 +22,28 @@ impl TomlParsingError {
         TomlParsingError { id, source, range }
     }
 
+    //only errors block the document from being reconsiled,
+    //a padded requirement is kept so the trim quickfix has a document to work on
+    pub fn is_fatal(&self) -> bool {
+        self.source.severity() == DiagnosticSeverity::ERROR
+            && !matches!(self.source, TomlError::PaddedRequirement(_))
+    }
+
     pub fn diagnostic(self) -> Option<(String, Diagnostic)> {
-        match self.source {
-            TomlError::InvalidCrateName(e) => Some((
-                self.id,
-                Diagnostic {
-                    range: self.range,
-                    severity: Some(DiagnosticSeverity::ERROR),
-                    code: None,
-                    code_description: None,
-                    source: Some("cargo-appraiser".to_string()),
-                    message: e.to_string(),
-                    related_information: None,
-                    tags: None,
-                    data: None,
-                },
-            )),
-            TomlError::InvalidFeatureName(e) => Some((
-                self.id,
-                Diagnostic {
-                    range: self.range,
-                    severity: Some(DiagnosticSeverity::ERROR),
-                    code: None,
-                    code_description: None,
-                    source: Some("cargo-appraiser".to_string()),
-                    message: e.to_string(),
-                    related_information: None,
-                    tags: None,
-                    data: None,
-                },
-            )),
-            TomlError::InvalidProfileName(e) => Some((
-                self.id,
-                Diagnostic {
-                    range: self.range,
-                    severity: Some(DiagnosticSeverity::ERROR),
-                    code: None,
-                    code_description: None,
-                    source: Some("cargo-appraiser".to_string()),
-                    message: e.to_string(),
-                    related_information: None,
-                    tags: None,
-                    data: None,
-                },
-            )),
-        }
+        Some((
+            self.id,
+            Diagnostic {
+                range: self.range,
+                severity: Some(self.source.severity()),
+                code: None,
+                code_description: None,
+                source: Some("cargo-appraiser".to_string()),
+                message: self.source.to_string(),
+                related_information: None,
+                tags: None,
+                data: None,
+            },
+        ))
     }
 }
 
@@ -78,6 +55,41 @@ pub enum TomlError {
     InvalidFeatureName(InvalidFeatureName),
     #[error(transparent)]
     InvalidProfileName(InvalidProfileName),
+    #[error(transparent)]
+    InvalidRustVersion(InvalidRustVersion),
+    #[error("empty `features` array is redundant")]
+    EmptyFeatures,
+    #[error("absolute path `{0}` is specific to this machine, use a path relative to Cargo.toml")]
+    AbsolutePath(String),
+    #[error("unknown resolver `{0}`, expected \"1\", \"2\" or \"3\"")]
+    UnknownResolver(String),
+    #[error("unknown lint level `{0}`, expected \"allow\", \"warn\", \"deny\" or \"forbid\"")]
+    UnknownLintLevel(String),
+    #[error("feature `{0}` depends on itself")]
+    CyclicFeature(String),
+    #[error("version requirement `{0}` has leading or trailing whitespace")]
+    PaddedRequirement(String),
+    #[error("`{0}` has no upper bound and may pull in a breaking major version, consider `{1}`")]
+    OpenEndedRequirement(String, String),
+}
+
+impl TomlError {
+    pub fn severity(&self) -> DiagnosticSeverity {
+        match self {
+            TomlError::InvalidCrateName(_)
+            | TomlError::InvalidFeatureName(_)
+            | TomlError::InvalidProfileName(_)
+            | TomlError::InvalidRustVersion(_)
+            | TomlError::CyclicFeature(_)
+            | TomlError::PaddedRequirement(_) => DiagnosticSeverity::ERROR,
+            TomlError::EmptyFeatures | TomlError::OpenEndedRequirement(_, _) => {
+                DiagnosticSeverity::HINT
+            }
+            TomlError::AbsolutePath(_)
+            | TomlError::UnknownResolver(_)
+            | TomlError::UnknownLintLevel(_) => DiagnosticSeverity::WARNING,
+        }
+    }
 }
 
 impl From<InvalidCrateName> for TomlError {
@@ -221,3 +233,111 @@ impl From<InvalidProfileName> for TomlError {
         TomlError::InvalidProfileName(value)
     }
 }
+
+#[derive(Debug, thiserror::Error, Clone)]
+pub enum InvalidRustVersion {
+    #[error("`rust-version` {0} can't have an operator, use a bare version like \"1.70\"")]
+    Operator(String),
+    #[error("`rust-version` {0} must be a bare version like \"1.70\" or \"1.70.0\"")]
+    InvalidVersion(String),
+}
+
+pub fn validate_rust_version(version: &str) -> Result<(), TomlError> {
+    let version = version.trim();
+    if version.starts_with(['=', '>', '<', '~', '^', '*']) {
+        return Err(InvalidRustVersion::Operator(version.to_string()).into());
+    }
+    let parts: Vec<_> = version.split('.').collect();
+    if parts.len() > 3 || parts.iter().any(|p| p.is_empty() || p.parse::<u64>().is_err()) {
+        return Err(InvalidRustVersion::InvalidVersion(version.to_string()).into());
+    }
+    Ok(())
+}
+
+//an absolute path dependency is usually a local override that shouldn't be committed
+pub fn validate_path(path: &str) -> Result<(), TomlError> {
+    let bytes = path.as_bytes();
+    let is_unix = path.starts_with('/');
+    let is_unc = path.starts_with("\\\\");
+    let is_windows_drive = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'/' | b'\\');
+    if is_unix || is_unc || is_windows_drive {
+        return Err(TomlError::AbsolutePath(path.to_string()));
+    }
+    Ok(())
+}
+
+pub fn validate_requirement_padding(req: &str) -> Result<(), TomlError> {
+    if req.trim() != req {
+        return Err(TomlError::PaddedRequirement(req.to_string()));
+    }
+    Ok(())
+}
+
+pub fn validate_requirement_bound(req: &str) -> Result<(), TomlError> {
+    match bounded_requirement(req) {
+        Some(bounded) => Err(TomlError::OpenEndedRequirement(req.to_string(), bounded)),
+        None => Ok(()),
+    }
+}
+
+//suggest a caret requirement on the lower bound as written when every comparator is `>` or `>=`
+//`>=1.5` -> `1.5`, `>=0.3.1` -> `0.3.1`, dropping a component would widen the range
+pub fn bounded_requirement(req: &str) -> Option<String> {
+    let req = semver::VersionReq::parse(req).ok()?;
+    if req.comparators.is_empty()
+        || !req
+            .comparators
+            .iter()
+            .all(|c| matches!(c.op, semver::Op::Greater | semver::Op::GreaterEq))
+    {
+        return None;
+    }
+    let lower = req
+        .comparators
+        .iter()
+        .max_by_key(|c| (c.major, c.minor, c.patch))?;
+    let mut bounded = lower.major.to_string();
+    for n in [lower.minor, lower.patch].into_iter().flatten() {
+        bounded.push_str(&format!(".{}", n));
+    }
+    if !lower.pre.is_empty() {
+        bounded.push_str(&format!("-{}", lower.pre));
+    }
+    Some(bounded)
+}
+
+pub fn validate_resolver(resolver: &str) -> Result<(), TomlError> {
+    match resolver {
+        "1" | "2" | "3" => Ok(()),
+        _ => Err(TomlError::UnknownResolver(resolver.to_string())),
+    }
+}
+
+pub fn validate_lint_level(level: &str) -> Result<(), TomlError> {
+    match level {
+        "allow" | "warn" | "deny" | "forbid" => Ok(()),
+        _ => Err(TomlError::UnknownLintLevel(level.to_string())),
+    }
+}
+
+impl From<InvalidRustVersion> for TomlError {
+    fn from(value: InvalidRustVersion) -> Self {
+        TomlError::InvalidRustVersion(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bounded_requirement;
+
+    #[test]
+    fn test_bounded_requirement() {
+        assert_eq!(bounded_requirement(">=0.3.1").as_deref(), Some("0.3.1"));
+        assert_eq!(bounded_requirement(">=1.5").as_deref(), Some("1.5"));
+        assert_eq!(bounded_requirement(">=0.0.3").as_deref(), Some("0.0.3"));
+        assert_eq!(bounded_requirement(">=1, <2"), None);
+    }
+}
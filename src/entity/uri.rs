@@ -9,3 +9,9 @@ pub fn into_file_uri(path: &Path) -> Uri {
 pub fn into_file_uri_str(path: &str) -> Uri {
     Uri::from_str(&format!("file://{}", path)).unwrap()
 }
+
+//resolve `.`, `..` and symlinks so every spelling of a manifest path shares one key,
+//the uri is kept as is when the file doesn't exist
+pub fn canonical_uri(uri: &Uri) -> Uri {
+    std::fs::canonicalize(uri.path().as_str()).map_or_else(|_| uri.clone(), |p| into_file_uri(&p))
+}
@@ -1,16 +1,20 @@
 use tower_lsp::lsp_types::Range;
 
-use super::Value;
+use super::{Lint, Value};
 
 #[derive(Default, Debug, Clone)]
 pub struct Workspace {
     pub members: Members,
+    pub resolver: Option<Value<String>>,
+    //the `[workspace.lints]` table
+    pub lints: Vec<Lint>,
 }
 
 #[derive(Default, Debug, Clone)]
-struct Members {
+pub struct Members {
     pub id: String,
     pub text: String,
     pub range: Range,
-    pub members: Vec<Value<String>>,
+    //the member paths or globs with the range of each array item
+    pub members: Vec<(Value<String>, Range)>,
 }
@@ -0,0 +1,32 @@
+use std::path::Path;
+
+use tempfile::TempDir;
+
+//a temporary directory for tests which read files or run cargo,
+//unique to each test and removed on drop, even when the test panics
+pub struct Fixture {
+    dir: TempDir,
+}
+
+impl Fixture {
+    //paths relative to the root and their content, parent directories are created
+    pub fn new(files: &[(&str, &str)]) -> Self {
+        let fixture = Self {
+            dir: tempfile::tempdir().unwrap(),
+        };
+        for (path, content) in files {
+            fixture.write(path, content);
+        }
+        fixture
+    }
+
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn write(&self, path: &str, content: impl AsRef<[u8]>) {
+        let path = self.root().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+}
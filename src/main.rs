@@ -1,19 +1,35 @@
-use clap::{arg, command, Parser};
-use config::{initialize_config, UserConfig};
-use controller::{Appraiser, CargoDocumentEvent, CargoTomlPayload, ClientCapability};
+use clap::{arg, command, Parser, Subcommand};
+use config::{effective_config, initialize_config, settings_config, UserConfig, GLOBAL_CONFIG};
+use controller::{
+    audit_fix_args, cargo_net_offline, check, feature_impact, semantic_tokens_legend, Appraiser,
+    AppraiserSignal, CargoDocumentEvent, CargoTomlPayload, ClientCapability,
+};
 use decoration::{DecorationRenderer, Renderer};
-use entity::{supported_commands, CARGO};
+use entity::{
+    supported_commands, AUDIT_FIX, CARGO, CONFIG, FEATURE_IMPACT, OUTDATED, RESOLVED_MANIFEST,
+    UPGRADE_SUMMARY, VALIDATE,
+};
 use serde_json::Value;
-use tokio::sync::{mpsc::Sender, oneshot};
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use tokio::{
+    net::TcpListener,
+    sync::{broadcast, mpsc::Sender, oneshot},
+};
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
-use tower_lsp::{Client, LanguageServer, LspService, Server};
+use tower_lsp::{Client, ClientSocket, LanguageServer, LspService, Server};
 use tracing::{error, info};
 
 mod config;
 mod controller;
 mod decoration;
 mod entity;
+#[cfg(test)]
+mod fixture;
 mod usecase;
 
 #[derive(Debug)]
@@ -22,17 +38,20 @@ struct CargoAppraiser {
     tx: Sender<CargoDocumentEvent>,
     render: DecorationRenderer,
     cargo_path: Option<String>,
+    offline: bool,
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for CargoAppraiser {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
         //init config
-        let config: UserConfig = params
+        let mut config: UserConfig = params
             .initialization_options
             .map(serde_json::from_value)
             .and_then(|v| v.ok())
             .unwrap_or_default();
+        //`--offline` and cargo's own offline mode win over the client configuration
+        config.resolve.offline |= self.offline;
         initialize_config(config);
 
         Ok(InitializeResult {
@@ -42,10 +61,16 @@ impl LanguageServer for CargoAppraiser {
                     ..Default::default()
                 }),
                 definition_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                })),
+                document_symbol_provider: Some(OneOf::Left(true)),
                 code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 completion_provider: Some(CompletionOptions {
                     trigger_characters: Some(vec![
+                        "{".to_string(),
                         ".".to_string(),
                         "+".to_string(),
                         "-".to_string(),
@@ -67,7 +92,7 @@ impl LanguageServer for CargoAppraiser {
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
                     TextDocumentSyncOptions {
                         open_close: Some(true),
-                        change: Some(TextDocumentSyncKind::FULL),
+                        change: Some(TextDocumentSyncKind::INCREMENTAL),
                         will_save: None,
                         will_save_wait_until: None,
                         save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
@@ -80,6 +105,15 @@ impl LanguageServer for CargoAppraiser {
                     file_operations: None,
                 }),
                 inlay_hint_provider: Some(OneOf::Left(true)),
+                semantic_tokens_provider: Some(
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        SemanticTokensOptions {
+                            legend: semantic_tokens_legend(),
+                            full: Some(SemanticTokensFullOptions::Bool(true)),
+                            ..Default::default()
+                        },
+                    ),
+                ),
                 ..Default::default()
             },
             ..Default::default()
@@ -95,7 +129,7 @@ impl LanguageServer for CargoAppraiser {
         params: GotoDefinitionParams,
     ) -> Result<Option<GotoDefinitionResponse>> {
         let uri = params.text_document_position_params.text_document.uri;
-        if !uri.path().as_str().ends_with("Cargo.toml") {
+        if !is_manifest(&uri) {
             return Ok(None);
         };
         //create a once channel with payload Hover
@@ -121,11 +155,11 @@ impl LanguageServer for CargoAppraiser {
     }
 
     async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
-        let Some(cargo_path) = self.cargo_path.as_deref() else {
-            return Ok(None);
-        };
         match params.command.as_str() {
             CARGO => {
+                let Some(cargo_path) = self.cargo_path.as_deref() else {
+                    return Ok(None);
+                };
                 let cargo_path = cargo_path.to_string();
                 let args = params
                     .arguments
@@ -145,13 +179,154 @@ impl LanguageServer for CargoAppraiser {
                 }
                 Ok(None)
             }
+            OUTDATED => {
+                let (tx, rx) = oneshot::channel();
+                if let Err(e) = self.tx.send(CargoDocumentEvent::Outdated(tx)).await {
+                    error!("error sending outdated event: {}", e);
+                    return Ok(None);
+                }
+                match rx.await {
+                    Ok(outdated) => Ok(serde_json::to_value(outdated).ok()),
+                    Err(_) => Ok(None),
+                }
+            }
+            UPGRADE_SUMMARY => {
+                let (tx, rx) = oneshot::channel();
+                if let Err(e) = self.tx.send(CargoDocumentEvent::UpgradeSummary(tx)).await {
+                    error!("error sending upgrade summary event: {}", e);
+                    return Ok(None);
+                }
+                match rx.await {
+                    Ok(summary) => Ok(serde_json::to_value(summary).ok()),
+                    Err(_) => Ok(None),
+                }
+            }
+            VALIDATE => {
+                //arguments: the Cargo.toml uri
+                let Some(uri) = params.arguments.first().and_then(|v| v.as_str()) else {
+                    return Ok(None);
+                };
+                let Ok(uri) = Uri::from_str(uri) else {
+                    return Ok(None);
+                };
+                let (tx, rx) = oneshot::channel();
+                if let Err(e) = self.tx.send(CargoDocumentEvent::Validate(uri, tx)).await {
+                    error!("error sending validate event: {}", e);
+                    return Ok(None);
+                }
+                match rx.await {
+                    Ok(findings) => Ok(serde_json::to_value(findings).ok()),
+                    Err(_) => Ok(None),
+                }
+            }
+            RESOLVED_MANIFEST => {
+                //arguments: the Cargo.toml uri
+                let Some(uri) = params.arguments.first().and_then(|v| v.as_str()) else {
+                    return Ok(None);
+                };
+                let Ok(uri) = Uri::from_str(uri) else {
+                    return Ok(None);
+                };
+                let (tx, rx) = oneshot::channel();
+                if let Err(e) = self.tx.send(CargoDocumentEvent::ResolvedManifest(uri, tx)).await {
+                    error!("error sending resolved manifest event: {}", e);
+                    return Ok(None);
+                }
+                match rx.await {
+                    Ok(manifest) => Ok(manifest.map(Value::String)),
+                    Err(_) => Ok(None),
+                }
+            }
+            AUDIT_FIX => {
+                //arguments: the root Cargo.toml uri, optionally a crate and its safe version
+                let args: Vec<&str> = params.arguments.iter().filter_map(|v| v.as_str()).collect();
+                let Some(uri) = args.first().and_then(|uri| Uri::from_str(uri).ok()) else {
+                    return Ok(None);
+                };
+                let Some(cargo_path) = self.cargo_path.as_deref() else {
+                    return Ok(None);
+                };
+                let fix = match args[1..] {
+                    [package, version] => Some((package, version)),
+                    _ => None,
+                };
+                if fix.is_none() && !GLOBAL_CONFIG.read().unwrap().audit.enable_fix {
+                    self.client
+                        .show_message(
+                            MessageType::WARNING,
+                            "`cargo audit fix` is experimental, enable it with `audit.enableFix`",
+                        )
+                        .await;
+                    return Ok(None);
+                }
+                let manifest = Path::new(uri.path().as_str());
+                let output = tokio::process::Command::new(cargo_path)
+                    .args(audit_fix_args(fix))
+                    .current_dir(manifest.parent().unwrap_or(manifest))
+                    .output()
+                    .await;
+                match output {
+                    Ok(output) if output.status.success() => {
+                        self.client.show_message(MessageType::INFO, "audit fix applied").await;
+                    }
+                    Ok(output) => {
+                        let stderr = String::from_utf8_lossy(&output.stderr);
+                        let message = format!("audit fix failed: {}", stderr);
+                        self.client.show_message(MessageType::ERROR, message).await;
+                        return Ok(None);
+                    }
+                    Err(e) => {
+                        let message = format!("audit fix failed: {}", e);
+                        self.client.show_message(MessageType::ERROR, message).await;
+                        return Ok(None);
+                    }
+                }
+                //re-resolve, the audit runs after the resolve
+                if let Err(e) = self.tx.send(CargoDocumentEvent::CargoLockChanged).await {
+                    error!("error sending cargo lock changed event from audit fix: {}", e);
+                }
+                Ok(None)
+            }
+            //the effective config after defaults and initialization options are merged
+            CONFIG => Ok(Some(effective_config())),
+            FEATURE_IMPACT => {
+                //arguments: the Cargo.toml uri, then the features to enable
+                let mut args = params
+                    .arguments
+                    .iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()));
+                let Some(uri) = args.next() else {
+                    return Ok(None);
+                };
+                let Ok(uri) = Uri::from_str(&uri) else {
+                    return Ok(None);
+                };
+                let features: Vec<String> = args.collect();
+                let path = uri.path().as_str().to_string();
+                let offline = GLOBAL_CONFIG.read().unwrap().resolve.offline;
+                let impact = tokio::task::spawn_blocking(move || {
+                    feature_impact(Path::new(&path), &features, offline)
+                })
+                .await;
+                match impact {
+                    Ok(Ok(impact)) => Ok(serde_json::to_value(impact).ok()),
+                    Ok(Err(e)) => {
+                        error!("feature impact error: {}", e);
+                        Ok(None)
+                    }
+                    Err(e) => {
+                        error!("feature impact task error: {}", e);
+                        Ok(None)
+                    }
+                }
+            }
             _ => Ok(None),
         }
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let uri = params.text_document.uri;
-        if !uri.path().as_str().ends_with("Cargo.toml") {
+        if !is_manifest(&uri) {
             return;
         };
         if let Err(e) = self
@@ -167,23 +342,22 @@ impl LanguageServer for CargoAppraiser {
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
-        for change in params.content_changes {
-            if let Err(e) = self
-                .tx
-                .send(CargoDocumentEvent::Changed(CargoTomlPayload {
-                    uri: params.text_document.uri.clone(),
-                    text: change.text,
-                }))
-                .await
-            {
-                error!("error sending changed event: {}", e);
-            };
-        }
+        //the changes of one notification are applied together
+        if let Err(e) = self
+            .tx
+            .send(CargoDocumentEvent::Changed(
+                params.text_document.uri,
+                params.content_changes,
+            ))
+            .await
+        {
+            error!("error sending changed event: {}", e);
+        };
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri = params.text_document.uri;
-        if !uri.path().as_str().ends_with("Cargo.toml") {
+        if !is_manifest(&uri) {
             return;
         };
         if let Err(e) = self.tx.send(CargoDocumentEvent::Closed(uri)).await {
@@ -193,7 +367,7 @@ impl LanguageServer for CargoAppraiser {
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
         let uri = params.text_document.uri;
-        if !uri.path().as_str().ends_with("Cargo.toml") {
+        if !is_manifest(&uri) {
             return;
         };
 
@@ -210,7 +384,7 @@ impl LanguageServer for CargoAppraiser {
 
     async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
         let uri = params.text_document.uri;
-        if !uri.path().as_str().ends_with("Cargo.toml") {
+        if !is_manifest(&uri) {
             return Ok(None);
         };
 
@@ -228,7 +402,7 @@ impl LanguageServer for CargoAppraiser {
 
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         let uri = params.text_document_position.text_document.uri;
-        if !uri.path().as_str().ends_with("Cargo.toml") {
+        if !is_manifest(&uri) {
             return Ok(None);
         };
         let (tx, rx) = oneshot::channel();
@@ -254,7 +428,7 @@ impl LanguageServer for CargoAppraiser {
 
     async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
         let uri = params.text_document.uri;
-        if !uri.path().as_str().ends_with("Cargo.toml") {
+        if !is_manifest(&uri) {
             return Ok(None);
         };
         let (tx, rx) = oneshot::channel();
@@ -274,9 +448,107 @@ impl LanguageServer for CargoAppraiser {
         }
     }
 
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let uri = params.text_document.uri;
+        if !is_manifest(&uri) {
+            return Ok(None);
+        };
+        let (tx, rx) = oneshot::channel();
+        if let Err(e) = self
+            .tx
+            .send(CargoDocumentEvent::PrepareRename(uri, params.position, tx))
+            .await
+        {
+            error!("error sending prepare rename event: {}", e);
+            return Ok(None);
+        };
+        match rx.await {
+            Ok(range) => Ok(range.map(PrepareRenameResponse::Range)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = params.text_document_position.text_document.uri;
+        if !is_manifest(&uri) {
+            return Ok(None);
+        };
+        let (tx, rx) = oneshot::channel();
+        if let Err(e) = self
+            .tx
+            .send(CargoDocumentEvent::Rename(
+                uri,
+                params.text_document_position.position,
+                params.new_name,
+                tx,
+            ))
+            .await
+        {
+            error!("error sending rename event: {}", e);
+            return Ok(None);
+        };
+        match rx.await {
+            Ok(Ok(edit)) => Ok(edit),
+            Ok(Err(msg)) => Err(tower_lsp::jsonrpc::Error::invalid_params(msg)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let uri = params.text_document.uri;
+        if !is_manifest(&uri) {
+            return Ok(None);
+        };
+        let (tx, rx) = oneshot::channel();
+        if let Err(e) = self
+            .tx
+            .send(CargoDocumentEvent::DocumentSymbols(uri, tx))
+            .await
+        {
+            error!("error sending document symbols event: {}", e);
+            return Ok(None);
+        };
+        match rx.await {
+            Ok(symbols) => Ok(symbols),
+            Err(_) => Ok(None),
+        }
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        let uri = params.text_document.uri;
+        if !is_manifest(&uri) {
+            return Ok(None);
+        };
+        let (tx, rx) = oneshot::channel();
+        if let Err(e) = self
+            .tx
+            .send(CargoDocumentEvent::SemanticTokens(uri, tx))
+            .await
+        {
+            error!("error sending semantic tokens event: {}", e);
+            return Ok(None);
+        };
+        match rx.await {
+            Ok(Some(tokens)) => Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+                result_id: None,
+                data: tokens,
+            }))),
+            _ => Ok(None),
+        }
+    }
+
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
         let uri = params.text_document_position_params.text_document.uri;
-        if !uri.path().as_str().ends_with("Cargo.toml") {
+        if !is_manifest(&uri) {
             return Ok(None);
         };
         //create a once channel with payload Hover
@@ -315,31 +587,66 @@ impl LanguageServer for CargoAppraiser {
 
     async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
         info!("did change configuration: {}", params.settings);
+        let Some(mut config) = settings_config(params.settings) else {
+            error!("invalid configuration, keep the current one");
+            return;
+        };
+        config.resolve.offline |= self.offline;
+        initialize_config(config);
+        if let Err(e) = self.tx.send(CargoDocumentEvent::ConfigChanged).await {
+            error!("error sending config changed event: {}", e);
+        }
     }
 }
 
+//documents matching `files.patterns`
+fn is_manifest(uri: &Uri) -> bool {
+    GLOBAL_CONFIG
+        .read()
+        .unwrap()
+        .files
+        .is_manifest(uri.path().as_str())
+}
+
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
     ///"inlayHint" or "vscode". "inlayHint" is for lsp inlay hints and "vscode" is for vscode decorations
-    #[arg(short, long, value_enum)]
-    renderer: Renderer,
-    ///stdio transport. now only work with stdio transport
-    #[arg(short, long, default_value = "true")]
+    #[arg(short, long, value_enum, required = true)]
+    renderer: Option<Renderer>,
+    ///stdio transport, the default transport
+    #[arg(short, long, default_value = "true", conflicts_with = "listen")]
     stdio: bool,
+    ///tcp transport, listen on the address (e.g., "127.0.0.1:9257") and serve one client
+    #[arg(short, long, value_name = "ADDR")]
+    listen: Option<SocketAddr>,
+    ///resolve without network access, registry versions are not queried
+    #[arg(long)]
+    offline: bool,
     ///list of supported client capabilities (e.g., "readFile")
     #[arg(short = 'c', long, value_delimiter = ',')]
     client_capabilities: Option<Vec<ClientCapability>>,
 }
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    ///print outdated and vulnerable dependencies as json, exit with 1 if there is any
+    Check {
+        ///path to the Cargo.toml
+        manifest: PathBuf,
+        ///skip registry queries and the audit
+        #[arg(long)]
+        offline: bool,
+    },
+}
+
 #[tokio::main]
 async fn main() {
     // Parse command-line arguments
     let args = Args::parse();
 
-    let stdin = tokio::io::stdin();
-    let stdout = tokio::io::stdout();
-
     //logging
     let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
@@ -350,15 +657,54 @@ async fn main() {
         .with_ansi(false)
         .init();
 
-    let (service, socket) = LspService::new(|client| {
-        let render = DecorationRenderer::new(client.clone(), args.renderer);
+    if let Some(Command::Check { manifest, offline }) = args.command {
+        let (code, output) = run_check(&manifest, offline || cargo_net_offline()).await;
+        println!("{}", output);
+        std::process::exit(code);
+    }
+    let renderer = args.renderer.expect("renderer is required without a subcommand");
+
+    let (service, socket) = new_service(
+        renderer,
+        args.client_capabilities.as_deref(),
+        args.offline || cargo_net_offline(),
+    );
+
+    match args.listen {
+        Some(addr) => {
+            let listener = match TcpListener::bind(addr).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("failed to listen on {}: {}", addr, e);
+                    std::process::exit(1);
+                }
+            };
+            info!("listening on {}", addr);
+            if let Err(e) = serve_tcp(listener, service, socket).await {
+                error!("failed to accept a client: {}", e);
+            }
+        }
+        None => {
+            let stdin = tokio::io::stdin();
+            let stdout = tokio::io::stdout();
+            Server::new(stdin, stdout, socket).serve(service).await;
+        }
+    }
+    //the client is gone, don't wait for background resolve and audit tasks
+    std::process::exit(0);
+}
+
+fn new_service(
+    renderer: Renderer,
+    client_capabilities: Option<&[ClientCapability]>,
+    offline: bool,
+) -> (LspService<CargoAppraiser>, ClientSocket) {
+    LspService::new(|client| {
+        let render = DecorationRenderer::new(client.clone(), renderer);
         let render_tx = render.init();
 
-        let state = Appraiser::new(
-            client.clone(),
-            render_tx.clone(),
-            args.client_capabilities.as_deref(),
-        );
+        let state = Appraiser::new(client.clone(), render_tx.clone(), client_capabilities);
+        tokio::spawn(log_signals(state.subscribe()));
         let tx = state.initialize();
         let cargo_path =
             executable_path_finder::find_with_cargo_home("cargo").map(|p| p.to_string());
@@ -368,8 +714,155 @@ async fn main() {
             tx,
             render,
             cargo_path,
+            offline,
+        }
+    })
+}
+
+async fn log_signals(mut signals: broadcast::Receiver<AppraiserSignal>) {
+    loop {
+        match signals.recv().await {
+            Ok(AppraiserSignal::Resolved(ctx)) => {
+                info!("resolved {} at rev {}", ctx.uri.as_str(), ctx.rev)
+            }
+            Ok(AppraiserSignal::Audited(root)) => info!("audited {}", root.as_str()),
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+//0 when nothing is found, 1 when there are findings, 2 when the check failed
+async fn run_check(manifest: &Path, offline: bool) -> (i32, String) {
+    match check(manifest, offline).await {
+        Ok(report) => (
+            if report.is_empty() { 0 } else { 1 },
+            serde_json::to_string_pretty(&report).unwrap_or_default(),
+        ),
+        Err(e) => (2, format!("failed to check {}: {}", manifest.display(), e)),
+    }
+}
+
+//accept one connection and serve it until the client disconnects
+async fn serve_tcp(
+    listener: TcpListener,
+    service: LspService<CargoAppraiser>,
+    socket: ClientSocket,
+) -> std::io::Result<()> {
+    let (stream, peer) = listener.accept().await?;
+    info!("client connected from {}", peer);
+    let (read, write) = tokio::io::split(stream);
+    Server::new(read, write, socket).serve(service).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+
+    use tower_lsp::LspService;
+
+    use super::{new_service, run_check, serve_tcp, CargoAppraiser};
+    use crate::{
+        controller::{Appraiser, AppraiserSignal, CargoDocumentEvent, CargoTomlPayload},
+        decoration::{DecorationRenderer, Renderer},
+        entity::into_file_uri,
+        fixture::Fixture,
+    };
+
+    #[tokio::test]
+    async fn test_check_offline() {
+        let fixture = Fixture::new(&[
+            (
+                "Cargo.toml",
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            ),
+            ("src/lib.rs", ""),
+        ]);
+        let root = fixture.root();
+
+        let (code, output) = run_check(&root.join("Cargo.toml"), true).await;
+        assert_eq!(code, 0);
+        let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(report["outdated"], serde_json::json!([]));
+        assert_eq!(report["vulnerable"], serde_json::json!([]));
+
+        let (code, _) = run_check(&root.join("missing/Cargo.toml"), true).await;
+        assert_eq!(code, 2);
+    }
+
+    #[tokio::test]
+    async fn test_resolved_signal() {
+        let text = "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n";
+        let fixture = Fixture::new(&[("Cargo.toml", text), ("src/lib.rs", "")]);
+        let root = fixture.root();
+
+        let mut signals = None;
+        let (service, _socket) = LspService::new(|client| {
+            let render = DecorationRenderer::new(client.clone(), Renderer::InlayHint);
+            let state = Appraiser::new(client.clone(), render.init(), None);
+            signals = Some(state.subscribe());
+            CargoAppraiser {
+                client,
+                tx: state.initialize(),
+                render,
+                cargo_path: None,
+                offline: true,
+            }
+        });
+        let mut signals = signals.unwrap();
+
+        let uri = into_file_uri(&root.join("Cargo.toml"));
+        let payload = CargoTomlPayload {
+            uri: uri.clone(),
+            text: text.to_string(),
+        };
+        service
+            .inner()
+            .tx
+            .send(CargoDocumentEvent::Opened(payload))
+            .await
+            .unwrap();
+        //resolved once the debouncer and cargo are done, no sleeping
+        tokio::time::timeout(std::time::Duration::from_secs(60), async {
+            loop {
+                if let AppraiserSignal::Resolved(ctx) = signals.recv().await.unwrap() {
+                    if ctx.uri == uri {
+                        return;
+                    }
+                }
+            }
+        })
+        .await
+        .expect("no resolved signal");
+    }
+
+    #[tokio::test]
+    async fn test_tcp_initialize() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (service, socket) = new_service(Renderer::InlayHint, None, false);
+        let server = tokio::spawn(serve_tcp(listener, service, socket));
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{}}}"#;
+        let request = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+        client.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = Vec::new();
+        let mut buf = [0u8; 4096];
+        while !String::from_utf8_lossy(&response).contains("\"id\":1") {
+            let n = client.read(&mut buf).await.unwrap();
+            assert!(n > 0, "server closed the connection");
+            response.extend_from_slice(&buf[..n]);
         }
-    });
+        assert!(String::from_utf8_lossy(&response).contains("\"capabilities\""));
 
-    Server::new(stdin, stdout, socket).serve(service).await;
+        //the server returns once the client disconnects
+        drop(client);
+        server.await.unwrap().unwrap();
+    }
 }
@@ -3,7 +3,7 @@ mod reverse_symbol_tree;
 mod symbol_tree;
 mod workspace;
 
-pub use document::Document;
+pub use document::{Document, ResolvedContext};
 pub use reverse_symbol_tree::ReverseSymbolTree;
-pub use symbol_tree::{diff_dependency_entries, Walker};
+pub use symbol_tree::{capacity_hint, diff_dependency_entries, Walker};
 pub use workspace::Workspace;
@@ -1,13 +1,16 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::Path,
+};
 
-use tower_lsp::lsp_types::{Position, Uri};
+use tower_lsp::lsp_types::{Position, Range, Uri};
 
 use crate::entity::{
     cargo_dependency_to_toml_key, into_file_uri, Dependency, EntryDiff, Manifest, SymbolTree,
     TomlNode, TomlParsingError,
 };
 
-use super::{diff_dependency_entries, ReverseSymbolTree, Walker};
+use super::{capacity_hint, diff_dependency_entries, ReverseSymbolTree, Walker};
 
 #[derive(Debug, Clone)]
 pub struct Document {
@@ -22,6 +25,19 @@ pub struct Document {
     pub manifest: Manifest,
     pub members: Option<Vec<cargo::core::package::Package>>,
     pub root_manifest: Option<Uri>,
+    //member manifest uri -> package version, only for the workspace root
+    pub member_versions: HashMap<Uri, String>,
+    //package name -> the packages depending on it in the resolve graph, see `hover.showRequiredBy`
+    pub reverse_deps: HashMap<String, BTreeSet<String>>,
+}
+
+//the node under a position and the dependency it belongs to,
+//the dependency carries the resolved package and registry summaries once resolved
+pub struct ResolvedContext<'a> {
+    pub node: TomlNode,
+    //the row id of the node, audit results are keyed by it
+    pub id: Option<String>,
+    pub dependency: Option<&'a Dependency>,
 }
 
 impl Document {
@@ -40,12 +56,13 @@ impl Document {
         let table = dom.as_table().unwrap();
         let entries = table.entries().read();
 
-        let mut walker = Walker::new(text, entries.len());
+        let mut walker = Walker::new(text, capacity_hint(text));
 
         for (key, entry) in entries.iter() {
             if key.value().is_empty() {
                 continue;
             }
+            walker.walk_header(key);
             walker.walk_root(key.value(), key.value(), entry)
         }
 
@@ -63,6 +80,8 @@ impl Document {
             parsing_errors: errs,
             root_manifest: None,
             members: None,
+            member_versions: HashMap::new(),
+            reverse_deps: HashMap::new(),
         }
     }
 
@@ -76,6 +95,8 @@ impl Document {
         self.tree.entries = new.tree.entries;
         self.tree.keys = new.tree.keys;
         self.reverse_tree = new.reverse_tree;
+        self.parsing_errors = new.parsing_errors;
+        self.manifest = new.manifest;
         self.rev += 1;
         //merge dependencies
         for v in &diff.created {
@@ -100,12 +121,21 @@ impl Document {
                         dep.rev = new_dep.rev.clone();
                         dep.package = new_dep.package.clone();
                         dep.workspace = new_dep.workspace.clone();
+                        dep.default_features = new_dep.default_features.clone();
+                        dep.optional = new_dep.optional.clone();
+                        dep.artifact = new_dep.artifact.clone();
+                        dep.lib = new_dep.lib.clone();
+                        dep.target = new_dep.target.clone();
+                        dep.hidden = new_dep.hidden;
                         dep.platform = new_dep.platform.clone();
                         dep.unresolved = None;
                         dep.resolved = None;
                         dep.latest_summary = None;
                         dep.latest_matched_summary = None;
+                        dep.latest_stable_summary = None;
+                        dep.latest_prerelease_summary = None;
                         dep.range = new_dep.range;
+                        dep.key_range = new_dep.key_range;
                         //dep.matched_summary not reset
                         //dep.summaries not reset
                     })
@@ -147,6 +177,12 @@ impl Document {
             return;
         };
         self.root_manifest = Some(into_file_uri(&workspace.root().join("Cargo.toml")));
+        if self.root_manifest.as_ref() == Some(&self.uri) {
+            self.member_versions = workspace
+                .members()
+                .map(|m| (into_file_uri(m.manifest_path()), m.version().to_string()))
+                .collect();
+        }
         let Ok(current) = workspace.current() else {
             //virtual workspaces
             self.members = Some(workspace.members().cloned().collect());
@@ -173,6 +209,76 @@ impl Document {
         }
     }
 
+    //the manifest of each `[workspace] members` path, globs are skipped
+    pub fn member_manifest_uris(&self) -> Vec<(&str, Range, Uri)> {
+        let Some(workspace) = self.manifest.workspace.as_ref() else {
+            return Vec::new();
+        };
+        let Some(root) = Path::new(self.uri.path().as_str()).parent() else {
+            return Vec::new();
+        };
+        workspace
+            .members
+            .members
+            .iter()
+            .filter(|(path, _)| !path.value().contains(['*', '?', '[']))
+            .map(|(path, range)| {
+                let manifest = root
+                    .join(path.value().trim_start_matches("./"))
+                    .join("Cargo.toml");
+                (path.id(), *range, into_file_uri(&manifest))
+            })
+            .collect()
+    }
+
+    //id, range and version of each member with a known version
+    pub fn member_version_hints(&self) -> Vec<(String, Range, String)> {
+        self.member_manifest_uris()
+            .into_iter()
+            .filter_map(|(id, range, uri)| {
+                let version = self.member_versions.get(&uri)?;
+                Some((id.to_string(), range, version.to_string()))
+            })
+            .collect()
+    }
+
+    //the installed version and source of every resolved direct dependency, as toml tables
+    //sorted by table and name, so the output is stable for diffing
+    pub fn to_resolved_manifest(&self) -> String {
+        let mut tables: BTreeMap<String, BTreeMap<&str, String>> = BTreeMap::new();
+        for dep in self.dependencies.values() {
+            if dep.is_virtual {
+                continue;
+            }
+            let Some(resolved) = dep.resolved.as_ref() else {
+                continue;
+            };
+            let table = match dep.platform.as_deref() {
+                Some(platform) => format!("target.'{}'.{}", platform, dep.table),
+                None => dep.table.to_string(),
+            };
+            tables.entry(table).or_default().insert(
+                &dep.name,
+                format!(
+                    "{{ version = \"{}\", source = \"{}\" }}",
+                    resolved.version(),
+                    resolved.package_id().source_id().as_url()
+                ),
+            );
+        }
+        tables
+            .into_iter()
+            .map(|(table, deps)| {
+                let lines: Vec<_> = deps
+                    .into_iter()
+                    .map(|(name, value)| format!("{} = {}\n", name, value))
+                    .collect();
+                format!("[{}]\n{}", table, lines.concat())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     pub fn is_dependencies_dirty(&self) -> bool {
         !self.dirty_dependencies.is_empty()
     }
@@ -184,6 +290,17 @@ impl Document {
         }
     }
 
+    pub fn resolve_at(&self, pos: Position) -> Option<ResolvedContext<'_>> {
+        let node = self.precise_match(pos)?;
+        let id = node.row_id();
+        let dependency = id.as_deref().and_then(|id| self.dependency(id));
+        Some(ResolvedContext {
+            node,
+            id,
+            dependency,
+        })
+    }
+
     pub fn precise_match_entry(&self, pos: Position) -> Option<TomlNode> {
         self.reverse_tree
             .precise_match_entry(pos, &self.tree.entries)
@@ -200,6 +317,26 @@ impl Document {
         self.dependencies.get(id)
     }
 
+    //key and entry nodes inside the dependency's value
+    pub fn dependency_nodes<'a>(&'a self, dep: &'a Dependency) -> impl Iterator<Item = &'a TomlNode> {
+        self.tree
+            .keys
+            .values()
+            .chain(self.tree.entries.values())
+            .filter(move |n| {
+                n.range.start >= dep.range.start
+                    && n.range.end <= dep.range.end
+                    && n.row_id().as_deref() == Some(dep.id.as_str())
+            })
+    }
+
+    //the `[workspace.dependencies]` entry with the given name
+    pub fn workspace_dependency(&self, name: &str) -> Option<&Dependency> {
+        self.dependencies
+            .values()
+            .find(|d| d.is_virtual && d.name == name)
+    }
+
     pub fn entry(&self, id: &str) -> Option<&TomlNode> {
         self.tree.entries.get(id)
     }
@@ -229,15 +366,16 @@ impl Document {
     }
 }
 
+#[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
-    use tower_lsp::lsp_types::Uri;
+    use tower_lsp::lsp_types::{Position, Uri};
 
     use crate::{
         entity::{
             CargoTable, DependencyEntryKind, DependencyKeyKind, DependencyTable, EntryKind,
-            KeyKind, NodeKind,
+            KeyKind, LintEntryKind, LintKeyKind, NodeKind, TargetKeyKind,
         },
         usecase::document::Document,
     };
@@ -281,4 +419,502 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_parse_rust_version() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [package]
+            name = "a"
+            rust-version = "1.70"
+            "#,
+        );
+        assert!(doc.parsing_errors.is_empty());
+        let key = doc.tree.keys.get("package.rust-version").unwrap();
+        assert_eq!(
+            key.kind,
+            NodeKind::Key(KeyKind::Package(crate::entity::PackageKeyKind::RustVersion))
+        );
+        assert_eq!(
+            doc.manifest.package.rust_version.as_ref().unwrap().value(),
+            "1.70"
+        );
+
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [package]
+            name = "a"
+            rust-version = ">=1.70"
+            "#,
+        );
+        assert_eq!(doc.parsing_errors.len(), 1);
+        assert_eq!(doc.parsing_errors[0].id, "package.rust-version");
+    }
+
+    #[test]
+    fn test_parse_complex_cfg_target() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [target.'cfg(any(unix,target_arch="wasm32"))'.dependencies]
+            a = "0.1.0"
+            "#,
+        );
+        assert!(doc.parsing_errors.is_empty());
+        assert_eq!(doc.dependencies.len(), 1);
+        let dep = doc.dependencies.values().next().unwrap();
+        assert_eq!(
+            dep.toml_key(),
+            r#"dependencies:a:cfg(any(unix, target_arch = "wasm32"))"#
+        );
+    }
+
+    #[test]
+    fn test_parse_large_manifest() {
+        let mut text = String::from("[dependencies]\n");
+        for i in 0..2000 {
+            text.push_str(&format!("crate{} = {{ version = \"0.{}\" }}\n", i, i));
+        }
+        let doc = Document::parse(&Uri::from_str("file:///C:/Users/test.toml").unwrap(), &text);
+        assert!(doc.parsing_errors.is_empty());
+        assert_eq!(doc.dependencies.len(), 2000);
+        let dep = doc.dependency("dependencies.crate1999").unwrap();
+        assert_eq!(dep.version.as_ref().unwrap().value(), "0.1999");
+    }
+
+    #[test]
+    fn test_parse_empty_features() {
+        let uri = Uri::from_str("file:///C:/Users/test.toml").unwrap();
+        let text = r#"
+            [dependencies]
+            a = { version = "0.1.0", features = [] }
+            "#;
+        let doc = Document::parse(&uri, text);
+        assert_eq!(doc.parsing_errors.len(), 1);
+        assert_eq!(doc.parsing_errors[0].id, "dependencies.a.features");
+        assert!(!doc.parsing_errors[0].is_fatal());
+        //hints don't block the document
+        let mut workspace = crate::usecase::Workspace::new();
+        let (doc, _) = workspace.reconsile(&uri, text).unwrap();
+        assert_eq!(doc.parsing_errors.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_absolute_path() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [dependencies]
+            a = { path = "/Users/name/a" }
+            b = { path = "C:\\Users\\name\\b" }
+            c = { path = "../c" }
+            "#,
+        );
+        let mut ids: Vec<_> = doc.parsing_errors.iter().map(|e| e.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["dependencies.a.path", "dependencies.b.path"]);
+        assert!(doc.parsing_errors.iter().all(|e| !e.is_fatal()));
+    }
+
+    #[test]
+    fn test_parse_resolver() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [workspace]
+            resolver = "2"
+            "#,
+        );
+        assert!(doc.parsing_errors.is_empty());
+        assert_eq!(doc.manifest.resolver(), Some("2"));
+        let key = doc.tree.keys.get("workspace.resolver").unwrap();
+        assert_eq!(
+            key.kind,
+            NodeKind::Key(KeyKind::Workspace(crate::entity::WorkspaceKeyKind::Resolver))
+        );
+
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [package]
+            name = "a"
+            resolver = "4"
+            "#,
+        );
+        assert_eq!(doc.manifest.resolver(), Some("4"));
+        assert_eq!(doc.parsing_errors.len(), 1);
+        assert!(!doc.parsing_errors[0].is_fatal());
+    }
+
+    #[test]
+    fn test_parse_open_ended_requirement() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [dependencies]
+            serde = ">=1.0"
+            log = { version = ">=0.4.1" }
+            tokio = ">=1, <2"
+            "#,
+        );
+        let mut errs: Vec<_> = doc
+            .parsing_errors
+            .iter()
+            .map(|e| (e.id.as_str(), e.to_string()))
+            .collect();
+        errs.sort();
+        assert_eq!(errs.len(), 2);
+        assert_eq!(errs[0].0, "dependencies.log.version");
+        assert!(errs[0].1.ends_with("consider `0.4.1`"));
+        assert_eq!(errs[1].0, "dependencies.serde");
+        assert!(errs[1].1.ends_with("consider `1.0`"));
+        assert!(doc.parsing_errors.iter().all(|e| !e.is_fatal()));
+    }
+
+    #[test]
+    fn test_parse_publish() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [package]
+            name = "a"
+            publish = false
+            "#,
+        );
+        assert!(!doc.manifest.is_publishable());
+        assert!(doc.tree.keys.contains_key("package.publish"));
+
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [package]
+            name = "a"
+            publish = []
+            "#,
+        );
+        assert!(!doc.manifest.is_publishable());
+
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [package]
+            name = "a"
+            publish = ["my-registry"]
+            "#,
+        );
+        assert!(doc.manifest.is_publishable());
+    }
+
+    #[test]
+    fn test_parse_artifact_dependency() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [dependencies]
+            bar = { version = "1.0", artifact = "bin", target = "wasm32-unknown-unknown" }
+            baz = { path = "../baz", artifact = ["bin", "cdylib"], lib = true }
+            "#,
+        );
+        assert!(doc.parsing_errors.is_empty());
+        let bar = doc.dependency("dependencies.bar").unwrap();
+        assert_eq!(bar.version.as_ref().unwrap().value(), "1.0");
+        assert_eq!(bar.artifact.as_ref().unwrap()[0].value(), "bin");
+        assert_eq!(bar.target.as_ref().unwrap().value(), "wasm32-unknown-unknown");
+        let baz = doc.dependency("dependencies.baz").unwrap();
+        assert!(baz.version.is_none());
+        assert_eq!(baz.artifact.as_ref().unwrap().len(), 2);
+        assert_eq!(baz.lib.as_ref().map(|v| *v.value()), Some(true));
+    }
+
+    #[test]
+    fn test_parse_workspace_optional() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [dependencies]
+            serde = { workspace = true, optional = true }
+            "#,
+        );
+        assert!(doc.parsing_errors.is_empty());
+        let dep = doc.dependency("dependencies.serde").unwrap();
+        assert_eq!(dep.workspace.as_ref().map(|v| *v.value()), Some(true));
+        assert!(dep.is_optional());
+        assert!(dep.version.is_none());
+        assert_eq!(dep.table_keys(), vec!["optional", "workspace"]);
+
+        //the flags survive a reconsile
+        let mut doc = doc;
+        let new_doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [dependencies]
+            serde = { workspace = true, optional = true, features = ["derive"] }
+            "#,
+        );
+        let diff = Document::diff(Some(&doc), &new_doc);
+        assert_eq!(diff.value_updated, vec!["dependencies.serde"]);
+        doc.reconsile(new_doc, &diff);
+        let dep = doc.dependency("dependencies.serde").unwrap();
+        assert_eq!(dep.workspace.as_ref().map(|v| *v.value()), Some(true));
+        assert!(dep.is_optional());
+    }
+
+    #[test]
+    fn test_parse_hide_directive() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [dependencies]
+            serde = "1" # appraiser: hide
+            tokio = { version = "1", features = ["full"] } #appraiser:hide
+            anyhow = "1" # pinned for msrv
+            "#,
+        );
+        assert!(doc.dependency("dependencies.serde").unwrap().hidden);
+        assert!(doc.dependency("dependencies.tokio").unwrap().hidden);
+        assert!(!doc.dependency("dependencies.anyhow").unwrap().hidden);
+    }
+
+    #[test]
+    fn test_parse_patch_and_replace() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+[patch.crates-io]
+serde = { git = "https://github.com/serde-rs/serde", branch = "master" }
+
+[replace]
+"foo:0.1.0" = { path = "../foo" }
+"#,
+        );
+        assert!(doc.parsing_errors.is_empty());
+        let serde = doc.dependency("patch.crates-io.serde").unwrap();
+        assert_eq!(serde.table, DependencyTable::Patch);
+        assert_eq!(serde.patch_registry.as_deref(), Some("crates-io"));
+        assert_eq!(serde.branch.as_ref().unwrap().value(), "master");
+        let node = doc.precise_match(Position::new(2, 20)).unwrap();
+        assert_eq!(node.row_id().as_deref(), Some("patch.crates-io.serde"));
+
+        let foo = doc.dependency("replace.foo:0.1.0").unwrap();
+        assert_eq!(foo.table, DependencyTable::Replace);
+        assert_eq!(foo.name, "foo");
+        assert_eq!(foo.version.as_ref().unwrap().value(), "=0.1.0");
+        assert_eq!(foo.path.as_ref().unwrap().value(), "../foo");
+    }
+
+    #[test]
+    fn test_parse_dependency_sub_table() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"
+            [dependencies.serde]
+            version = "1.0"
+            features = ["derive"]
+
+            [dependencies]
+            tokio = { version = "1", features = ["full"] }
+            "#,
+        );
+        assert!(doc.parsing_errors.is_empty());
+        let serde = doc.dependency("dependencies.serde").unwrap();
+        let version = serde.version.as_ref().unwrap();
+        assert_eq!(version.value(), "1.0");
+        assert_eq!(version.id(), "dependencies.serde.version");
+        assert_eq!(serde.features.as_ref().unwrap()[0].value(), "derive");
+        //the key is in the table header, the entries follow it
+        assert_eq!(serde.key_range.start.line, 1);
+        let node = doc.entry(version.id()).unwrap();
+        assert_eq!(node.range.start.line, 2);
+        assert_eq!(
+            node.kind,
+            NodeKind::Entry(EntryKind::Dependency(
+                "dependencies.serde".to_string(),
+                DependencyEntryKind::TableDependencyVersion
+            ))
+        );
+        //a sub-table dependency has the same structure as an inline table one
+        let tokio = doc.dependency("dependencies.tokio").unwrap();
+        assert_eq!(tokio.version.as_ref().unwrap().id(), "dependencies.tokio.version");
+        assert_eq!(tokio.table, serde.table);
+    }
+
+    #[test]
+    fn test_parse_lints() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"[lints.rust]
+unsafe_code = "forbid"
+
+[lints.clippy]
+all = { level = "warn", priority = -1 }
+"#,
+        );
+        assert!(doc.parsing_errors.is_empty());
+        let lints = &doc.manifest.lints;
+        assert_eq!(lints.len(), 2);
+        let unsafe_code = lints.iter().find(|l| l.name == "unsafe_code").unwrap();
+        assert_eq!(unsafe_code.id, "lints.rust.unsafe_code");
+        assert_eq!(unsafe_code.tool, "rust");
+        assert_eq!(unsafe_code.level.as_ref().unwrap().value(), "forbid");
+        assert!(unsafe_code.priority.is_none());
+        let node = doc.precise_match(Position::new(1, 3)).unwrap();
+        assert_eq!(node.id, "lints.rust.unsafe_code");
+        assert_eq!(node.kind, NodeKind::Key(KeyKind::Lint(LintKeyKind::Lint)));
+        let node = doc.precise_match(Position::new(1, 17)).unwrap();
+        assert_eq!(node.kind, NodeKind::Entry(EntryKind::Lint(LintEntryKind::Level)));
+
+        let all = lints.iter().find(|l| l.name == "all").unwrap();
+        assert_eq!(all.id, "lints.clippy.all");
+        let level = all.level.as_ref().unwrap();
+        assert_eq!(level.value(), "warn");
+        assert_eq!(level.id(), "lints.clippy.all.level");
+        assert_eq!(*all.priority.as_ref().unwrap().value(), -1);
+        let node = doc.precise_match(Position::new(4, 36)).unwrap();
+        assert_eq!(node.id, "lints.clippy.all.priority");
+        assert_eq!(node.kind, NodeKind::Entry(EntryKind::Lint(LintEntryKind::Priority)));
+        let node = doc.precise_match(Position::new(3, 9)).unwrap();
+        assert_eq!(node.kind, NodeKind::Key(KeyKind::Lint(LintKeyKind::Tool)));
+
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"[workspace.lints.rust]
+unsafe_code = "never"
+"#,
+        );
+        let lints = &doc.manifest.workspace.as_ref().unwrap().lints;
+        assert_eq!(lints[0].id, "workspace.lints.rust.unsafe_code");
+        //an unknown level is a warning
+        assert_eq!(doc.parsing_errors.len(), 1);
+        assert!(!doc.parsing_errors[0].is_fatal());
+    }
+
+    #[test]
+    fn test_member_version_hints() {
+        let mut doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"[workspace]
+members = ["crates/a", "./crates/b", "tools/*"]
+"#,
+        );
+        let uris: Vec<_> = doc
+            .member_manifest_uris()
+            .into_iter()
+            .map(|(id, _, uri)| (id.to_string(), uri.to_string()))
+            .collect();
+        //globs are skipped
+        assert_eq!(
+            uris,
+            vec![
+                (
+                    "workspace.members.0".to_string(),
+                    "file:///C:/Users/crates/a/Cargo.toml".to_string()
+                ),
+                (
+                    "workspace.members.1".to_string(),
+                    "file:///C:/Users/crates/b/Cargo.toml".to_string()
+                ),
+            ]
+        );
+        doc.member_versions.insert(
+            Uri::from_str("file:///C:/Users/crates/a/Cargo.toml").unwrap(),
+            "0.2.1".to_string(),
+        );
+        let hints = doc.member_version_hints();
+        assert_eq!(hints.len(), 1);
+        let (id, range, version) = &hints[0];
+        assert_eq!(id, "workspace.members.0");
+        assert_eq!(version, "0.2.1");
+        //the hint sits after `"crates/a"`
+        assert_eq!(range.start, Position::new(1, 11));
+        assert_eq!(range.end, Position::new(1, 21));
+    }
+
+    #[test]
+    fn test_parse_cyclic_features() {
+        let uri = Uri::from_str("file:///C:/Users/test.toml").unwrap();
+        let doc = Document::parse(
+            &uri,
+            r#"
+            [features]
+            default = ["a"]
+            a = ["b", "serde/std"]
+            b = ["a"]
+            "#,
+        );
+        let mut ids: Vec<&str> = doc.parsing_errors.iter().map(|e| e.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["features.a", "features.b"]);
+        assert!(doc.parsing_errors[0].is_fatal());
+        assert_eq!(doc.parsing_errors[0].to_string(), "feature `a` depends on itself");
+    }
+
+    #[test]
+    fn test_resolve_at() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/test.toml").unwrap(),
+            r#"[package]
+name = "a"
+
+[dependencies]
+serde = { version = "1", features = ["derive"] }
+"#,
+        );
+        //the crate name, the version and a feature all belong to the dependency
+        for pos in [Position::new(4, 2), Position::new(4, 21), Position::new(4, 40)] {
+            let ctx = doc.resolve_at(pos).unwrap();
+            assert_eq!(ctx.node.id, doc.precise_match(pos).unwrap().id);
+            assert_eq!(ctx.id.as_deref(), Some("dependencies.serde"));
+            assert_eq!(ctx.dependency.unwrap().name, "serde");
+            //not resolved yet
+            assert!(ctx.dependency.unwrap().resolved.is_none());
+        }
+        let ctx = doc.resolve_at(Position::new(1, 1)).unwrap();
+        assert!(ctx.dependency.is_none());
+        assert!(doc.resolve_at(Position::new(2, 0)).is_none());
+    }
+
+    #[test]
+    fn test_parse_targets() {
+        let doc = Document::parse(
+            &Uri::from_str("file:///C:/Users/Cargo.toml").unwrap(),
+            r#"[[bin]]
+name = "cli"
+required-features = ["cli", "color"]
+
+[[bench]]
+name = "parse"
+harness = false
+"#,
+        );
+        assert!(doc.parsing_errors.is_empty());
+        let targets = &doc.manifest.targets;
+        assert_eq!(targets.len(), 2);
+        let bin = &targets[0];
+        assert_eq!(bin.id, "bin.0");
+        assert_eq!(bin.table, CargoTable::Bin);
+        assert_eq!(bin.name.as_ref().unwrap().value(), "cli");
+        let features: Vec<_> = bin.required_features.iter().map(|f| f.value()).collect();
+        assert_eq!(features, vec!["cli", "color"]);
+        assert_eq!(bin.required_features[1].id(), "bin.0.required-features.1");
+        assert!(bin.harness.is_none());
+        let node = doc.precise_match(Position::new(2, 3)).unwrap();
+        assert_eq!(node.id, "bin.0.required-features");
+        assert_eq!(
+            node.kind,
+            NodeKind::Key(KeyKind::Target(TargetKeyKind::RequiredFeatures))
+        );
+        let node = doc.precise_match(Position::new(2, 29)).unwrap();
+        assert_eq!(node.id, "bin.0.required-features.1");
+
+        let bench = &targets[1];
+        assert_eq!(bench.id, "bench.0");
+        assert_eq!(bench.table, CargoTable::Bench);
+        assert_eq!(bench.name.as_ref().unwrap().value(), "parse");
+        assert!(!*bench.harness.as_ref().unwrap().value());
+        let node = doc.precise_match(Position::new(6, 2)).unwrap();
+        assert_eq!(node.id, "bench.0.harness");
+        assert_eq!(node.kind, NodeKind::Key(KeyKind::Target(TargetKeyKind::Harness)));
+    }
 }
@@ -5,17 +5,57 @@ use std::{
 
 use lsp_async_stub::util::Mapper;
 use taplo::{
-    dom::{node::Key, Node},
+    dom::{
+        node::{IntegerValue, Key},
+        Node,
+    },
     util::join_ranges,
 };
 use tower_lsp::lsp_types::{Position, Range};
 
 use crate::entity::{
-    validate_crate_name, validate_feature_name, validate_profile_name, CargoTable, Dependency,
-    DependencyEntryKind, DependencyKeyKind, EntryDiff, EntryKind, KeyKind, Manifest, SymbolTree,
-    TomlNode, TomlParsingError, Value, WorkspaceEntryKind, WorkspaceKeyKind,
+    validate_crate_name, validate_feature_name, validate_lint_level, validate_path,
+    validate_profile_name, validate_requirement_bound, validate_requirement_padding,
+    validate_resolver, validate_rust_version,
+    CargoTable, Dependency, DependencyEntryKind, DependencyKeyKind, DependencyTable, EntryDiff,
+    EntryKind, KeyKind, Lint, LintEntryKind, LintKeyKind, Manifest, PackageEntryKind,
+    PackageKeyKind, SymbolTree, Target, TargetKeyKind, TomlError, TomlNode, TomlParsingError, Value,
+    WorkspaceEntryKind, WorkspaceKeyKind,
 };
 
+//estimate the node count from the document byte length,
+//a key value pair like `serde = "1"` takes about 24 bytes
+pub fn capacity_hint(text: &str) -> usize {
+    (text.len() / 24).max(8)
+}
+
+//`[replace]` keys are package id specs like `foo:0.1.0`
+fn spec_name(table: CargoTable, key: &str) -> &str {
+    match table {
+        CargoTable::Replace => key.split_once(':').map_or(key, |(name, _)| name),
+        _ => key,
+    }
+}
+
+fn hidden_lines(text: &str) -> HashSet<u32> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| is_hide_directive(line))
+        .map(|(i, _)| i as u32)
+        .collect()
+}
+
+//`serde = "1" # appraiser: hide`
+pub fn is_hide_directive(line: &str) -> bool {
+    let Some((_, comment)) = line.rsplit_once('#') else {
+        return false;
+    };
+    let Some((tool, directive)) = comment.split_once(':') else {
+        return false;
+    };
+    tool.trim() == "appraiser" && directive.trim() == "hide"
+}
+
 pub struct Walker {
     keys_map: HashMap<String, TomlNode>,
     entries_map: HashMap<String, TomlNode>,
@@ -23,17 +63,23 @@ pub struct Walker {
     mapper: Mapper,
     manifest: Manifest,
     errs: Vec<TomlParsingError>,
+    //lines with a `# appraiser: hide` trailing comment
+    hidden_lines: HashSet<u32>,
 }
 
 impl Walker {
     pub fn consume(
-        self,
+        mut self,
     ) -> (
         SymbolTree,
         Manifest,
         HashMap<String, Dependency>,
         Vec<TomlParsingError>,
     ) {
+        for dep in self.deps.values_mut() {
+            dep.hidden = self.hidden_lines.contains(&dep.key_range.start.line)
+                || self.hidden_lines.contains(&dep.range.end.line);
+        }
         (
             SymbolTree {
                 keys: self.keys_map,
@@ -45,16 +91,53 @@ impl Walker {
         )
     }
 
+    //capacity is the estimated node count, see `capacity_hint`
     pub fn new(text: &str, capacity: usize) -> Self {
         let mapper = Mapper::new_utf16(text, false);
         Self {
             keys_map: HashMap::with_capacity(capacity),
             entries_map: HashMap::with_capacity(capacity),
-            deps: HashMap::with_capacity(capacity),
+            //a dependency has a few nodes, e.g. key, entry, version
+            deps: HashMap::with_capacity(capacity / 4),
             mapper,
             manifest: Manifest::default(),
-            errs: Vec::with_capacity(capacity),
+            errs: Vec::new(),
+            hidden_lines: hidden_lines(text),
+        }
+    }
+
+    //a table key can appear in several headers, e.g. `[dependencies]` and `[dependencies.a]`
+    pub fn walk_header(&mut self, key: &Key) {
+        let Some(range) = key.text_ranges().next().and_then(|r| self.mapper.range(r)) else {
+            return;
+        };
+        self.manifest
+            .headers
+            .insert(key.value().to_string(), into_lsp_range(range));
+    }
+
+    fn walk_members(&mut self, id: &str, node: &Node) {
+        let Node::Array(arr) = node else {
+            return;
+        };
+        let mut members = Vec::new();
+        for (i, item) in arr.items().read().iter().enumerate() {
+            let Node::Str(s) = item else {
+                continue;
+            };
+            let Some(range) = self.mapper.range(join_ranges(item.text_ranges())) else {
+                continue;
+            };
+            members.push((
+                Value::new(format!("{}.{}", id, i), s.value().to_string()),
+                into_lsp_range(range),
+            ));
         }
+        self.manifest
+            .workspace
+            .get_or_insert_with(Default::default)
+            .members
+            .members = members;
     }
 
     pub fn walk_root(&mut self, id: &str, name: &str, node: &Node) {
@@ -62,7 +145,54 @@ impl Walker {
             Node::Table(t) => {
                 let parsed_table = CargoTable::from_str(name).unwrap();
                 match parsed_table {
-                    CargoTable::Package => {}
+                    CargoTable::Package => {
+                        let entries = t.entries().read();
+                        for (key, entry) in entries.iter() {
+                            let id = id.to_string() + "." + key.value();
+                            if key.value() == "resolver" {
+                                self.manifest.package.resolver = self.walk_resolver(
+                                    &id,
+                                    key,
+                                    entry,
+                                    parsed_table,
+                                    KeyKind::Package(PackageKeyKind::Resolver),
+                                    EntryKind::Package(PackageEntryKind::Resolver),
+                                );
+                                continue;
+                            }
+                            if key.value() == "publish" {
+                                self.manifest.package.publish = self.walk_publish(&id, key, entry);
+                                continue;
+                            }
+                            if key.value() != "rust-version" {
+                                continue;
+                            }
+                            let Node::Str(s) = entry else {
+                                continue;
+                            };
+                            let (_, key_range) = self.insert_key(
+                                &id,
+                                parsed_table,
+                                key,
+                                KeyKind::Package(PackageKeyKind::RustVersion),
+                            );
+                            self.insert_entry(
+                                &id,
+                                entry,
+                                parsed_table,
+                                EntryKind::Package(PackageEntryKind::RustVersion),
+                            );
+                            if let Err(e) = validate_rust_version(s.value()) {
+                                let range = self
+                                    .entries_map
+                                    .get(&id)
+                                    .map_or(key_range, |n| n.range);
+                                self.errs.push(TomlParsingError::new(id.to_string(), e, range));
+                            }
+                            self.manifest.package.rust_version =
+                                Some(Value::new(id, s.value().to_string()));
+                        }
+                    }
                     CargoTable::Workspace => {
                         let entries = t.entries().read();
                         for (key, entry) in entries.iter() {
@@ -81,6 +211,29 @@ impl Walker {
                                         parsed_table,
                                         EntryKind::Workspace(WorkspaceEntryKind::Members),
                                     );
+                                    self.walk_members(&id, entry);
+                                }
+                                "resolver" => {
+                                    let resolver = self.walk_resolver(
+                                        &id,
+                                        key,
+                                        entry,
+                                        parsed_table,
+                                        KeyKind::Workspace(WorkspaceKeyKind::Resolver),
+                                        EntryKind::Workspace(WorkspaceEntryKind::Resolver),
+                                    );
+                                    self.manifest
+                                        .workspace
+                                        .get_or_insert_with(Default::default)
+                                        .resolver = resolver;
+                                }
+                                "lints" => {
+                                    self.enter_generic(&id, key.value(), CargoTable::Lints, entry);
+                                    let lints = self.walk_lints(&id, entry);
+                                    self.manifest
+                                        .workspace
+                                        .get_or_insert_with(Default::default)
+                                        .lints = lints;
                                 }
                                 "dependencies" => {
                                     let Node::Table(table) = entry else {
@@ -94,7 +247,7 @@ impl Walker {
                                         let mut dep = Dependency {
                                             id: new_id.clone(),
                                             name: key.value().to_string(),
-                                            table: crate::entity::DependencyTable::WorkspaceDependencies,
+                                            table: DependencyTable::WorkspaceDependencies,
                                             range: into_lsp_range(
                                                 self.mapper
                                                     .range(join_ranges(entry.text_ranges()))
@@ -203,9 +356,78 @@ impl Walker {
                             }
                         }
                     }
+                    CargoTable::Patch => {
+                        let entries = t.entries().read();
+                        for (registry, entry) in entries.iter() {
+                            let Node::Table(registry_table) = entry else {
+                                continue;
+                            };
+                            let entries = registry_table.entries().read();
+                            for (key, entry) in entries.iter() {
+                                let new_id =
+                                    id.to_string() + "." + registry.value() + "." + key.value();
+                                let mut dep = Dependency {
+                                    id: new_id.clone(),
+                                    name: key.value().to_string(),
+                                    table: DependencyTable::Patch,
+                                    range: into_lsp_range(
+                                        self.mapper
+                                            .range(join_ranges(entry.text_ranges()))
+                                            .unwrap(),
+                                    ),
+                                    patch_registry: Some(registry.value().to_string()),
+                                    is_virtual: true,
+                                    ..Default::default()
+                                };
+                                self.enter_dependency(&new_id, key, parsed_table, entry, &mut dep);
+                                self.deps.insert(new_id, dep);
+                            }
+                        }
+                    }
+                    CargoTable::Replace => {
+                        let entries = t.entries().read();
+                        for (key, entry) in entries.iter() {
+                            let new_id = id.to_string() + "." + key.value();
+                            let mut dep = Dependency {
+                                id: new_id.clone(),
+                                name: spec_name(parsed_table, key.value()).to_string(),
+                                table: DependencyTable::Replace,
+                                range: into_lsp_range(
+                                    self.mapper.range(join_ranges(entry.text_ranges())).unwrap(),
+                                ),
+                                is_virtual: true,
+                                ..Default::default()
+                            };
+                            self.enter_dependency(&new_id, key, parsed_table, entry, &mut dep);
+                            //the replaced version is part of the key
+                            if let Some((_, version)) = key.value().split_once(':') {
+                                dep.version =
+                                    Some(Value::new(new_id.clone(), format!("={}", version)));
+                            }
+                            self.deps.insert(new_id, dep);
+                        }
+                    }
+                    CargoTable::Features => {
+                        self.enter_generic(id, name, parsed_table, node);
+                        self.walk_features(id, t);
+                    }
+                    CargoTable::Lints => {
+                        self.enter_generic(id, name, parsed_table, node);
+                        self.manifest.lints = self.walk_lints(id, node);
+                    }
                     _ => self.enter_generic(id, name, parsed_table, node),
                 }
             }
+            Node::Array(_) => {
+                let parsed_table = CargoTable::from_str(name).unwrap();
+                self.enter_generic(id, name, parsed_table, node);
+                if matches!(
+                    parsed_table,
+                    CargoTable::Bin | CargoTable::Example | CargoTable::Test | CargoTable::Bench
+                ) {
+                    self.walk_targets(id, parsed_table, node);
+                }
+            }
             _ => self.enter_generic(id, name, CargoTable::from_str(name).unwrap(), node),
         }
     }
@@ -220,9 +442,19 @@ impl Walker {
         let key_id = id.to_string() + ".key";
         let key_range = into_lsp_range(self.mapper.range(join_ranges(key.text_ranges())).unwrap());
 
-        if let Err(e) = validate_crate_name(key.value()) {
-            self.errs
-                .push(TomlParsingError::new(id.to_string(), e, key_range));
+        //lint names and target settings are not crate names
+        if !matches!(
+            table,
+            CargoTable::Lints
+                | CargoTable::Bin
+                | CargoTable::Example
+                | CargoTable::Test
+                | CargoTable::Bench
+        ) {
+            if let Err(e) = validate_crate_name(spec_name(table, key.value())) {
+                self.errs
+                    .push(TomlParsingError::new(id.to_string(), e, key_range));
+            }
         }
 
         self.keys_map.insert(
@@ -238,6 +470,206 @@ impl Walker {
         (key_id.to_string(), key_range)
     }
 
+    //`resolver` in `[package]` or `[workspace]`, cargo reads it on resolve
+    fn walk_resolver(
+        &mut self,
+        id: &str,
+        key: &Key,
+        entry: &Node,
+        table: CargoTable,
+        key_kind: KeyKind,
+        entry_kind: EntryKind,
+    ) -> Option<Value<String>> {
+        let Node::Str(s) = entry else {
+            return None;
+        };
+        self.insert_key(id, table, key, key_kind);
+        self.insert_entry(id, entry, table, entry_kind);
+        if let Err(e) = validate_resolver(s.value()) {
+            let range = self.entries_map.get(id).unwrap().range;
+            self.errs.push(TomlParsingError::new(id.to_string(), e, range));
+        }
+        Some(Value::new(id.to_string(), s.value().to_string()))
+    }
+
+    //`[lints]` or `[workspace.lints]`, the generic entries are replaced by classified ones
+    //`workspace = true` in `[lints]` is left to `enter_generic`
+    fn walk_lints(&mut self, id: &str, node: &Node) -> Vec<Lint> {
+        let table = CargoTable::Lints;
+        let Node::Table(t) = node else {
+            return Vec::new();
+        };
+        let mut lints = Vec::new();
+        for (tool, entry) in t.entries().read().iter() {
+            let Node::Table(tool_table) = entry else {
+                continue;
+            };
+            let tool_id = id.to_string() + "." + tool.value();
+            self.insert_key(&tool_id, table, tool, KeyKind::Lint(LintKeyKind::Tool));
+            for (key, entry) in tool_table.entries().read().iter() {
+                let lint_id = tool_id.to_string() + "." + key.value();
+                self.insert_key(&lint_id, table, key, KeyKind::Lint(LintKeyKind::Lint));
+                let mut lint = Lint {
+                    id: lint_id.to_string(),
+                    tool: tool.value().to_string(),
+                    name: key.value().to_string(),
+                    ..Default::default()
+                };
+                match entry {
+                    Node::Str(s) => {
+                        let kind = EntryKind::Lint(LintEntryKind::Level);
+                        self.insert_entry(&lint_id, entry, table, kind);
+                        lint.level = Some(Value::new(lint_id.to_string(), s.value().to_string()));
+                    }
+                    Node::Table(lint_table) => {
+                        for (field, value) in lint_table.entries().read().iter() {
+                            let field_id = lint_id.to_string() + "." + field.value();
+                            match (field.value(), value) {
+                                ("level", Node::Str(s)) => {
+                                    self.insert_entry(
+                                        &field_id,
+                                        value,
+                                        table,
+                                        EntryKind::Lint(LintEntryKind::Level),
+                                    );
+                                    lint.level = Some(Value::new(field_id, s.value().to_string()));
+                                }
+                                ("priority", Node::Integer(i)) => {
+                                    self.insert_entry(
+                                        &field_id,
+                                        value,
+                                        table,
+                                        EntryKind::Lint(LintEntryKind::Priority),
+                                    );
+                                    let priority = match i.value() {
+                                        IntegerValue::Negative(v) => v,
+                                        IntegerValue::Positive(v) => v as i64,
+                                    };
+                                    lint.priority = Some(Value::new(field_id, priority));
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                if let Some(level) = &lint.level {
+                    if let Err(e) = validate_lint_level(level.value()) {
+                        let range = self.entries_map.get(level.id()).unwrap().range;
+                        self.errs.push(TomlParsingError::new(level.id().to_string(), e, range));
+                    }
+                }
+                lints.push(lint);
+            }
+        }
+        lints
+    }
+
+    //`[[bin]]` and the other target arrays, the entries are inserted by `enter_generic`
+    //the keys of the nth element are `bin.n.name`, `bin.n.required-features`...
+    fn walk_targets(&mut self, id: &str, table: CargoTable, node: &Node) {
+        let Node::Array(arr) = node else {
+            return;
+        };
+        for (i, item) in arr.items().read().iter().enumerate() {
+            let Node::Table(t) = item else {
+                continue;
+            };
+            let target_id = id.to_string() + "." + &i.to_string();
+            let mut target = Target::new(target_id.to_string(), table);
+            for (key, entry) in t.entries().read().iter() {
+                let key_id = target_id.to_string() + "." + key.value();
+                let kind = match (key.value(), entry) {
+                    ("name", Node::Str(s)) => {
+                        target.name = Some(Value::new(key_id.to_string(), s.value().to_string()));
+                        TargetKeyKind::Name
+                    }
+                    ("required-features", Node::Array(features)) => {
+                        target.required_features = features
+                            .items()
+                            .read()
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(j, v)| match v {
+                                Node::Str(s) => Some(Value::new(
+                                    key_id.to_string() + "." + &j.to_string(),
+                                    s.value().to_string(),
+                                )),
+                                _ => None,
+                            })
+                            .collect();
+                        TargetKeyKind::RequiredFeatures
+                    }
+                    ("harness", Node::Bool(b)) => {
+                        target.harness = Some(Value::new(key_id.to_string(), b.value()));
+                        TargetKeyKind::Harness
+                    }
+                    _ => TargetKeyKind::Setting,
+                };
+                self.insert_key(&key_id, table, key, KeyKind::Target(kind));
+            }
+            self.manifest.targets.push(target);
+        }
+    }
+
+    //collect feature values, the nodes are inserted by `enter_generic`
+    fn walk_features(&mut self, id: &str, table: &taplo::dom::node::Table) {
+        let entries = table.entries().read();
+        for (key, entry) in entries.iter() {
+            let Node::Array(arr) = entry else {
+                continue;
+            };
+            let feature_id = id.to_string() + "." + key.value();
+            let values: Vec<_> = arr
+                .items()
+                .read()
+                .iter()
+                .enumerate()
+                .filter_map(|(i, v)| match v {
+                    Node::Str(s) => Some(Value::new(
+                        feature_id.to_string() + "." + &i.to_string(),
+                        s.value().to_string(),
+                    )),
+                    _ => None,
+                })
+                .collect();
+            self.manifest.features.insert(key.value().to_string(), values);
+        }
+        for name in self.manifest.cyclic_features() {
+            let feature_id = id.to_string() + "." + name;
+            let Some(node) = self.entries_map.get(&feature_id) else {
+                continue;
+            };
+            self.errs.push(TomlParsingError::new(
+                feature_id,
+                TomlError::CyclicFeature(name.to_string()),
+                node.range,
+            ));
+        }
+    }
+
+    //`publish` is either a bool or a list of registries, an empty list means unpublishable
+    fn walk_publish(&mut self, id: &str, key: &Key, entry: &Node) -> Option<Value<bool>> {
+        let publish = match entry {
+            Node::Bool(b) => b.value(),
+            Node::Array(arr) => !arr.items().read().is_empty(),
+            _ => return None,
+        };
+        self.insert_key(
+            id,
+            CargoTable::Package,
+            key,
+            KeyKind::Package(PackageKeyKind::Publish),
+        );
+        self.insert_entry(
+            id,
+            entry,
+            CargoTable::Package,
+            EntryKind::Package(PackageEntryKind::Publish),
+        );
+        Some(Value::new(id.to_string(), publish))
+    }
+
     fn insert_entry(&mut self, id: &str, node: &Node, table: CargoTable, kind: EntryKind) {
         let range = self.mapper.range(join_ranges(node.text_ranges())).unwrap();
         let lsp_range = into_lsp_range(range);
@@ -266,7 +698,7 @@ impl Walker {
                     KeyKind::Dependency(dep.id.to_string(), DependencyKeyKind::CrateName),
                 );
 
-                if let Err(e) = validate_crate_name(key.value()) {
+                if let Err(e) = validate_crate_name(spec_name(table, key.value())) {
                     self.errs.push(TomlParsingError::new(key_id, e, key_range));
                 }
             }
@@ -280,10 +712,11 @@ impl Walker {
                     KeyKind::Dependency(dep.id.to_string(), DependencyKeyKind::CrateName),
                 );
 
-                if let Err(e) = validate_crate_name(key.value()) {
+                if let Err(e) = validate_crate_name(spec_name(table, key.value())) {
                     self.errs
                         .push(TomlParsingError::new(key_id.to_string(), e, key_range));
                 }
+                dep.key_range = key_range;
                 self.insert_entry(
                     id,
                     node,
@@ -326,6 +759,14 @@ impl Walker {
                         ),
                     );
                     let items = arr.items().read();
+                    if items.is_empty() {
+                        let range = self.mapper.range(join_ranges(node.text_ranges())).unwrap();
+                        self.errs.push(TomlParsingError::new(
+                            id.to_string(),
+                            TomlError::EmptyFeatures,
+                            into_lsp_range(range),
+                        ));
+                    }
                     let mut features = Vec::with_capacity(items.len());
                     for (i, f) in items.iter().enumerate() {
                         let new_id = id.to_string() + "." + &i.to_string();
@@ -357,19 +798,43 @@ impl Walker {
                         }
                     }
                     dep.features = Some(features);
+                } else if key.value() == "artifact" {
+                    self.insert_entry(
+                        id,
+                        node,
+                        table,
+                        EntryKind::Dependency(
+                            dep.id.to_string(),
+                            DependencyEntryKind::TableDependencyArtifact,
+                        ),
+                    );
+                    let items = arr.items().read();
+                    dep.artifact = Some(
+                        items
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, a)| match a {
+                                Node::Str(s) => Some(Value::new(
+                                    id.to_string() + "." + &i.to_string(),
+                                    s.value().to_string(),
+                                )),
+                                _ => None,
+                            })
+                            .collect(),
+                    );
                 }
             }
             //simple dependency or table dependency string key value
             Node::Str(s) => {
                 let entry_kind = match key.value() {
                     "version" => {
-                        let (key_id, _) = self.insert_key(
+                        let _ = self.insert_key(
                             id,
                             table,
                             key,
                             KeyKind::Dependency(dep.id.to_string(), DependencyKeyKind::Version),
                         );
-                        dep.version = Some(Value::new(key_id, s.value().to_string()));
+                        dep.version = Some(Value::new(id.to_string(), s.value().to_string()));
                         EntryKind::Dependency(
                             dep.id.to_string(),
                             DependencyEntryKind::TableDependencyVersion,
@@ -390,6 +855,14 @@ impl Walker {
                         )
                     }
                     "path" => {
+                        if let Err(e) = validate_path(s.value()) {
+                            let range = self.mapper.range(join_ranges(node.text_ranges())).unwrap();
+                            self.errs.push(TomlParsingError::new(
+                                id.to_string(),
+                                e,
+                                into_lsp_range(range),
+                            ));
+                        }
                         dep.path = Some(Value::new(id.to_string(), s.value().to_string()));
                         EntryKind::Dependency(
                             dep.id.to_string(),
@@ -424,14 +897,30 @@ impl Walker {
                             DependencyEntryKind::TableDependencyPackage,
                         )
                     }
+                    "artifact" => {
+                        dep.artifact =
+                            Some(vec![Value::new(id.to_string(), s.value().to_string())]);
+                        EntryKind::Dependency(
+                            dep.id.to_string(),
+                            DependencyEntryKind::TableDependencyArtifact,
+                        )
+                    }
+                    "target" => {
+                        dep.target = Some(Value::new(id.to_string(), s.value().to_string()));
+                        EntryKind::Dependency(
+                            dep.id.to_string(),
+                            DependencyEntryKind::TableDependencyTarget,
+                        )
+                    }
                     _ => {
                         //insert key
-                        self.insert_key(
+                        let (_, key_range) = self.insert_key(
                             id,
                             table,
                             key,
                             KeyKind::Dependency(dep.id.to_string(), DependencyKeyKind::CrateName),
                         );
+                        dep.key_range = key_range;
                         dep.version = Some(Value::new(id.to_string(), s.value().to_string()));
                         match dep.is_virtual {
                             true => EntryKind::Dependency(
@@ -446,6 +935,16 @@ impl Walker {
                     }
                 };
                 self.insert_entry(id, node, table, entry_kind);
+                //the string is the version requirement
+                if dep.version.as_ref().is_some_and(|v| v.id() == id) {
+                    let req = s.value();
+                    if let Err(e) = validate_requirement_padding(req)
+                        .and_then(|_| validate_requirement_bound(req))
+                    {
+                        let range = self.entries_map.get(id).unwrap().range;
+                        self.errs.push(TomlParsingError::new(id.to_string(), e, range));
+                    }
+                }
             }
             Node::Bool(b) => {
                 let entry_kind = match key.value() {
@@ -462,14 +961,27 @@ impl Walker {
                             DependencyEntryKind::TableDependencyWorkspace,
                         )
                     }
-                    "default-features" => EntryKind::Dependency(
-                        dep.id.to_string(),
-                        DependencyEntryKind::TableDependencyDefaultFeatures,
-                    ),
-                    "optional" => EntryKind::Dependency(
-                        dep.id.to_string(),
-                        DependencyEntryKind::TableDependencyOptional,
-                    ),
+                    "default-features" => {
+                        dep.default_features = Some(Value::new(id.to_string(), b.value()));
+                        EntryKind::Dependency(
+                            dep.id.to_string(),
+                            DependencyEntryKind::TableDependencyDefaultFeatures,
+                        )
+                    }
+                    "optional" => {
+                        dep.optional = Some(Value::new(id.to_string(), b.value()));
+                        EntryKind::Dependency(
+                            dep.id.to_string(),
+                            DependencyEntryKind::TableDependencyOptional,
+                        )
+                    }
+                    "lib" => {
+                        dep.lib = Some(Value::new(id.to_string(), b.value()));
+                        EntryKind::Dependency(
+                            dep.id.to_string(),
+                            DependencyEntryKind::TableDependencyLib,
+                        )
+                    }
                     _ => EntryKind::Dependency(
                         dep.id.to_string(),
                         DependencyEntryKind::TableDependencyUnknownBool,
@@ -1,6 +1,6 @@
-use std::collections::{hash_map::Entry, HashMap};
+use std::collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap};
 
-use tower_lsp::lsp_types::Uri;
+use tower_lsp::lsp_types::{Position, TextDocumentContentChangeEvent, Uri};
 
 use crate::entity::{EntryDiff, TomlParsingError};
 
@@ -8,15 +8,44 @@ use super::document::Document;
 
 pub struct Workspace {
     pub documents: HashMap<Uri, Document>,
+    //the latest text of every reconsiled document, including texts with fatal parsing errors,
+    //incremental changes are applied to it
+    texts: HashMap<Uri, String>,
 }
 
 impl Workspace {
     pub fn new() -> Self {
         Self {
             documents: HashMap::new(),
+            texts: HashMap::new(),
         }
     }
 
+    //apply the changes of `textDocument/didChange` to the cached text,
+    //None if the text is unchanged, or unknown and the changes are not a full replacement
+    pub fn update(
+        &mut self,
+        uri: &Uri,
+        changes: &[TextDocumentContentChangeEvent],
+    ) -> Option<String> {
+        let cached = self.texts.get(uri);
+        if cached.is_none() && changes.first().map_or(true, |c| c.range.is_some()) {
+            return None;
+        }
+        let cached = cached.map_or("", |s| s.as_str());
+        let text = apply_changes(cached, changes);
+        if text == cached {
+            return None;
+        }
+        self.texts.insert(uri.clone(), text.clone());
+        Some(text)
+    }
+
+    //forget the cached text of a closed document, the next open sends the full text
+    pub fn close(&mut self, uri: &Uri) {
+        self.texts.remove(uri);
+    }
+
     pub fn document(&self, uri: &Uri) -> Option<&Document> {
         self.documents.get(uri)
     }
@@ -37,6 +66,54 @@ impl Workspace {
             .and_then(|doc| if doc.rev != rev { None } else { Some(doc) })
     }
 
+    //the union of the features every member enables on a dependency, keyed by package name
+    //"default" is included unless every member disables default features
+    pub fn enabled_features(&self) -> BTreeMap<String, BTreeSet<String>> {
+        let mut res: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for doc in self.documents.values() {
+            for dep in doc.dependencies.values() {
+                if dep.is_virtual {
+                    continue;
+                }
+                //`workspace = true` inherits from `[workspace.dependencies]`
+                let workspace_dep = dep
+                    .workspace
+                    .as_ref()
+                    .filter(|w| *w.value())
+                    .and_then(|_| doc.root_manifest.as_ref())
+                    .and_then(|root| self.document(root))
+                    .and_then(|root| root.workspace_dependency(&dep.name));
+                let name = workspace_dep.unwrap_or(dep).package_name();
+                let features = res.entry(name.to_string()).or_default();
+                for d in std::iter::once(dep).chain(workspace_dep) {
+                    if let Some(fs) = d.features.as_ref() {
+                        features.extend(fs.iter().map(|f| f.value().to_string()));
+                    }
+                }
+                let default_features = workspace_dep
+                    .unwrap_or(dep)
+                    .default_features
+                    .as_ref()
+                    .map_or(true, |v| *v.value());
+                if default_features {
+                    features.insert("default".to_string());
+                }
+            }
+        }
+        res
+    }
+
+    //the packages depending on `name` in the resolve graphs of the documents, sorted
+    pub fn reverse_deps(&self, name: &str) -> Vec<String> {
+        let parents: BTreeSet<&String> = self
+            .documents
+            .values()
+            .filter_map(|doc| doc.reverse_deps.get(name))
+            .flatten()
+            .collect();
+        parents.into_iter().cloned().collect()
+    }
+
     pub fn mark_all_dirty(&mut self) -> Vec<(Uri, usize)> {
         let mut uris = Vec::new();
         for doc in self.documents.values_mut() {
@@ -51,8 +128,11 @@ impl Workspace {
         uri: &Uri,
         text: &str,
     ) -> Result<(&Document, EntryDiff), Vec<TomlParsingError>> {
+        if self.texts.get(uri).map_or(true, |t| t != text) {
+            self.texts.insert(uri.clone(), text.to_string());
+        }
         let mut new_doc = Document::parse(uri, text);
-        if !new_doc.parsing_errors.is_empty() {
+        if new_doc.parsing_errors.iter().any(|e| e.is_fatal()) {
             return Err(new_doc.parsing_errors);
         }
         match self.documents.entry(uri.clone()) {
@@ -73,3 +153,150 @@ impl Workspace {
         }
     }
 }
+
+//changes are applied in order, a change without range replaces the whole text
+pub fn apply_changes(text: &str, changes: &[TextDocumentContentChangeEvent]) -> String {
+    let mut text = text.to_string();
+    for change in changes {
+        match change.range {
+            Some(range) => {
+                let start = offset_at(&text, range.start);
+                let end = offset_at(&text, range.end).max(start);
+                text.replace_range(start..end, &change.text);
+            }
+            None => text = change.text.clone(),
+        }
+    }
+    text
+}
+
+//the byte offset of a position counted in utf-16 code units,
+//a position past the end of a line is the end of the line, before `\r\n` or `\n`
+fn offset_at(text: &str, pos: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..pos.line {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let line = match text[line_start..].find('\n') {
+        Some(i) => &text[line_start..line_start + i],
+        None => &text[line_start..],
+    };
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= pos.character as usize {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Uri};
+
+    use super::{apply_changes, Workspace};
+    use crate::usecase::Document;
+
+    #[test]
+    fn test_enabled_features() {
+        let root_uri = Uri::from_str("file:///C:/Users/Cargo.toml").unwrap();
+        let mut state = Workspace::new();
+        let mut docs = vec![
+            Document::parse(
+                &root_uri,
+                r#"
+                [workspace.dependencies]
+                serde = { version = "1", features = ["derive"] }
+                "#,
+            ),
+            Document::parse(
+                &Uri::from_str("file:///C:/Users/a/Cargo.toml").unwrap(),
+                r#"
+                [dependencies]
+                serde = { workspace = true, features = ["rc"] }
+                "#,
+            ),
+            Document::parse(
+                &Uri::from_str("file:///C:/Users/b/Cargo.toml").unwrap(),
+                r#"
+                [dependencies]
+                serde = { version = "1", default-features = false, features = ["alloc"] }
+                tokio = { version = "1", default-features = false }
+                "#,
+            ),
+        ];
+        for doc in docs.iter_mut() {
+            doc.root_manifest = Some(root_uri.clone());
+        }
+        for doc in docs {
+            state.documents.insert(doc.uri.clone(), doc);
+        }
+        let features = state.enabled_features();
+        let serde: Vec<_> = features["serde"].iter().map(|s| s.as_str()).collect();
+        assert_eq!(serde, vec!["alloc", "default", "derive", "rc"]);
+        assert!(features["tokio"].is_empty());
+    }
+
+    fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(
+                Position::new(start.0, start.1),
+                Position::new(end.0, end.1),
+            )),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_apply_changes() {
+        let text = "[dependencies]\nserde = \"1\"\ntokio = \"1\"\n";
+        //edits are applied in order, the second range is in the edited text
+        let changes = [
+            change((1, 9), (1, 10), "1.0"),
+            change((2, 0), (2, 5), "rayon"),
+            change((3, 0), (3, 0), "anyhow = \"1\"\n"),
+        ];
+        assert_eq!(
+            apply_changes(text, &changes),
+            "[dependencies]\nserde = \"1.0\"\nrayon = \"1\"\nanyhow = \"1\"\n"
+        );
+
+        //`\r\n` is not part of the line, columns are utf-16 code units
+        let text = "[package]\r\nname = \"😀\"\r\n";
+        assert_eq!(
+            apply_changes(text, &[change((1, 10), (1, 11), "x")]),
+            "[package]\r\nname = \"😀x\r\n"
+        );
+        assert_eq!(
+            apply_changes(text, &[change((0, 20), (1, 0), "")]),
+            "[package]name = \"😀\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_update() {
+        let uri = Uri::from_str("file:///C:/Users/Cargo.toml").unwrap();
+        let mut state = Workspace::new();
+        //a range change needs the opened text
+        assert_eq!(state.update(&uri, &[change((0, 0), (0, 0), "a")]), None);
+        let text = "[dependencies]\nserde = \"1\"\n";
+        state.reconsile(&uri, text).unwrap();
+        assert_eq!(state.update(&uri, &[change((1, 9), (1, 10), "1")]), None);
+        let updated = state.update(&uri, &[change((1, 9), (1, 10), "2")]).unwrap();
+        assert_eq!(updated, "[dependencies]\nserde = \"2\"\n");
+        //the cached text follows the updates
+        let updated = state.update(&uri, &[change((1, 10), (1, 10), ".0")]).unwrap();
+        assert_eq!(updated, "[dependencies]\nserde = \"2.0\"\n");
+        //a closed document needs to be opened again
+        state.close(&uri);
+        assert_eq!(state.update(&uri, &[change((1, 9), (1, 10), "3")]), None);
+    }
+}